@@ -10,10 +10,12 @@ use bytes::BytesMut;
 use log::{error, info, warn};
 use prost::Message;
 use rand::Rng;
-use rocks::prelude::*;
+use rocks::prelude::{ColumnFamilyOptions, CompressionType, CuckooTableOptions, DEFAULT_COLUMN_FAMILY_NAME};
+use storage::{prefix_upper_bound, MemoryStorage, RocksStorage, Storage, WriteBatch};
 use types::H256;
 
 use chain::{BlockHeader, IndexedBlock, IndexedBlockHeader, IndexedTransaction, Transaction};
+use config::RocksDbConfig;
 use proto::chain::ContractType;
 
 pub type BoxError = Box<dyn Error>;
@@ -25,12 +27,15 @@ pub enum CheckResult {
     BreakAt(u64),
 }
 
+// * Column family indices.
+const COL_DEFAULT: usize = 0;
+const COL_BLOCK_HEADER: usize = 1;
+const COL_TRANSACTION: usize = 2;
+const COL_TRANSACTION_BLOCK: usize = 3;
+const NUM_COLS: usize = 4;
+
 pub struct ChainDB {
-    db: DB,
-    default: ColumnFamily,
-    block_header: ColumnFamily,
-    transaction: ColumnFamily,
-    transaction_block: ColumnFamily,
+    db: Box<dyn Storage>,
 }
 
 impl Drop for ChainDB {
@@ -40,18 +45,11 @@ impl Drop for ChainDB {
 }
 
 impl ChainDB {
-    pub fn new<P: AsRef<Path>>(db_path: P) -> ChainDB {
+    pub fn new<P: AsRef<Path>>(db_path: P, config: &RocksDbConfig) -> ChainDB {
         create_dir_all(&db_path).expect("create db directory");
 
-        let db_options = DBOptions::default()
-            .create_if_missing(true)
-            .create_missing_column_families(true)
-            .increase_parallelism(num_cpus::get() as _)
-            .allow_mmap_reads(true) // for Cuckoo table
-            .max_open_files(1024);
-
         let column_families = vec![
-            ColumnFamilyDescriptor::new(
+            (
                 DEFAULT_COLUMN_FAMILY_NAME,
                 ColumnFamilyOptions::default()
                     .optimize_for_small_db()
@@ -60,12 +58,12 @@ impl ChainDB {
                     .compression(CompressionType::NoCompression),
             ),
             // block_hash => BlockHeader
-            ColumnFamilyDescriptor::new(
+            (
                 "block-header",
                 ColumnFamilyOptions::default().max_write_buffer_number(6),
             ),
             // [block_hash, transaction_index: u64, transaction_hash] => Transaction
-            ColumnFamilyDescriptor::new(
+            (
                 "transaction",
                 ColumnFamilyOptions::default()
                     .prefix_extractor_fixed(32)
@@ -74,7 +72,7 @@ impl ChainDB {
             ),
             // transaction_hash => [block_hash, transaction_index: u64]
             // Key and value lengths are fixed
-            ColumnFamilyDescriptor::new(
+            (
                 "transaction-block",
                 ColumnFamilyOptions::default()
                     .table_factory_cuckoo(CuckooTableOptions::default())
@@ -84,44 +82,60 @@ impl ChainDB {
             ),
         ];
 
-        let (db, mut handles) = DB::open_with_column_families(&db_options, db_path, column_families).unwrap();
-        let txn_blk = handles.pop().unwrap();
-        let txn = handles.pop().unwrap();
-        let blk = handles.pop().unwrap();
-        let default = handles.pop().unwrap();
+        let db = RocksStorage::open(config, db_path, column_families).unwrap();
+
+        ChainDB::with_storage(Box::new(db))
+    }
 
-        assert!(handles.is_empty());
+    /// Create a chain-db in memory, all blocks are lost when dropped.
+    pub fn new_in_memory() -> ChainDB {
+        ChainDB::with_storage(Box::new(MemoryStorage::new(NUM_COLS)))
+    }
 
-        ChainDB {
-            db: db,
-            default: default,
-            block_header: blk,
-            transaction: txn,
-            transaction_block: txn_blk,
-        }
+    pub fn with_storage(storage: Box<dyn Storage>) -> ChainDB {
+        ChainDB { db: storage }
+    }
+
+    /// Get a value, not-found is an error.
+    fn get_raw(&self, col: usize, key: &[u8]) -> Result<Vec<u8>, BoxError> {
+        self.db
+            .get(col, key)?
+            .ok_or_else(|| Box::new(io::Error::new(io::ErrorKind::NotFound, "not found")) as BoxError)
+    }
+
+    /// Key/value pairs in `[lower_bound, upper_bound)`.
+    fn range<'a>(
+        &'a self,
+        col: usize,
+        lower_bound: &[u8],
+        upper_bound: &[u8],
+    ) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> + 'a {
+        self.db.iter_from(col, lower_bound, Some(upper_bound))
+    }
+
+    /// Key/value pairs where the key starts with `prefix`.
+    fn prefix<'a>(&'a self, col: usize, prefix: &[u8]) -> impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> + 'a {
+        self.db.iter_from(col, prefix, prefix_upper_bound(prefix).as_deref())
     }
 
     pub fn reset_node_id(&self) -> Vec<u8> {
         let mut rng = rand::thread_rng();
         let mut node_id = vec![b'A'; 64];
         rng.fill(&mut node_id[32..]);
-        self.default
-            .put(WriteOptions::default_instance(), b"NODE_ID", &node_id)
-            .unwrap();
+        self.db.put(COL_DEFAULT, b"NODE_ID", &node_id).unwrap();
         node_id
     }
 
     pub fn get_node_id(&self) -> Vec<u8> {
-        if let Ok(node_id) = self.default.get(ReadOptions::default_instance(), b"NODE_ID") {
-            node_id.to_vec()
+        if let Ok(Some(node_id)) = self.db.get(COL_DEFAULT, b"NODE_ID") {
+            node_id
         } else {
             self.reset_node_id()
         }
     }
 
     pub fn get_block_height(&self) -> i64 {
-        self.default
-            .get(ReadOptions::default_instance(), b"BLOCK_HEIGHT")
+        self.get_raw(COL_DEFAULT, b"BLOCK_HEIGHT")
             .map(|val| BE::read_u64(&*val) as i64)
             .unwrap_or(0)
     }
@@ -131,18 +145,29 @@ impl ChainDB {
         if height > self.get_block_height() {
             let mut val = [0u8; 8];
             BE::write_u64(&mut val, height as u64);
-            self.default
-                .put(WriteOptions::default_instance(), b"BLOCK_HEIGHT", &val)
-                .unwrap();
+            self.db.put(COL_DEFAULT, b"BLOCK_HEIGHT", &val).unwrap();
         }
     }
 
     pub fn force_update_block_height(&self, height: i64) -> Result<(), BoxError> {
         let mut val = [0u8; 8];
         BE::write_u64(&mut val, height as u64);
-        self.default
-            .put(WriteOptions::default_instance(), b"BLOCK_HEIGHT", &val)
-            .map_err(From::from)
+        self.db.put(COL_DEFAULT, b"BLOCK_HEIGHT", &val).map_err(From::from)
+    }
+
+    /// Fix block height if it lags behind inserted blocks, which happens when interrupted between
+    /// `insert_block` and `update_block_height`. Returns the repaired block height.
+    pub fn repair_block_height(&self) -> Result<i64, BoxError> {
+        let old_height = self.get_block_height();
+        let mut height = old_height;
+        while self.has_block_number(height as u64 + 1) {
+            height += 1;
+        }
+        if height != old_height {
+            warn!("repair block height {} => {}", old_height, height);
+            self.force_update_block_height(height)?;
+        }
+        Ok(height)
     }
 
     /// Highest block id, counted from 0
@@ -151,11 +176,11 @@ impl ChainDB {
     }
 
     pub fn insert_block(&self, block: &IndexedBlock) -> Result<(), Box<dyn Error>> {
-        let mut batch = WriteBatch::with_reserved_bytes(1024);
+        let mut batch = WriteBatch::new();
 
         let mut buf = BytesMut::with_capacity(block.header.raw.encoded_len());
         block.header.raw.encode(&mut buf)?;
-        batch.put_cf(&self.block_header, block.header.hash.as_bytes(), &buf);
+        batch.put(COL_BLOCK_HEADER, block.header.hash.as_bytes(), &buf);
 
         for (index, txn) in block.transactions.iter().enumerate() {
             buf.clear();
@@ -165,28 +190,26 @@ impl ChainDB {
             let mut idx_key = [0u8; 8];
             BE::write_u64(&mut idx_key[..], index as u64);
 
-            batch.putv_cf(
-                &self.transaction,
+            batch.putv(
+                COL_TRANSACTION,
                 &[block.hash().as_bytes(), &idx_key, txn.hash.as_bytes()],
                 &[&buf],
             );
             // reverse index
             // transaction_hash => [block_hash, transaction_index: u64]
-            batch.putv_cf(
-                &self.transaction_block,
+            batch.putv(
+                COL_TRANSACTION_BLOCK,
                 &[txn.hash.as_bytes()],
                 &[block.hash().as_bytes(), &idx_key],
             );
         }
 
-        self.db.write(WriteOptions::default_instance(), &batch)?;
+        self.db.write(&batch)?;
         Ok(())
     }
 
     pub fn has_block_id(&self, id: &H256) -> bool {
-        self.block_header
-            .get(ReadOptions::default_instance(), id.as_bytes())
-            .is_ok()
+        matches!(self.db.get(COL_BLOCK_HEADER, id.as_bytes()), Ok(Some(_)))
     }
 
     pub fn has_block(&self, block: &IndexedBlock) -> bool {
@@ -199,12 +222,7 @@ impl ChainDB {
         let mut upper_bound = [0xffu8; 32];
         BE::write_u64(&mut upper_bound[..8], num);
 
-        let it = self.block_header.new_iterator(
-            &ReadOptions::default()
-                .iterate_lower_bound(&lower_bound[..])
-                .iterate_upper_bound(&upper_bound[..]),
-        );
-        it.count() > 0
+        matches!(self.range(COL_BLOCK_HEADER, &lower_bound, &upper_bound).next(), Some(Ok(_)))
     }
 
     pub fn get_block_from_header(&self, header: IndexedBlockHeader) -> Result<IndexedBlock, BoxError> {
@@ -212,14 +230,10 @@ impl ChainDB {
         upper_bound.push(0xFF); // [0xcafebabe00 .. 0xcafebabeff]
 
         let transactions = self
-            .transaction
-            .new_iterator(
-                &ReadOptions::default()
-                    .iterate_lower_bound(&header.hash.as_bytes())
-                    .iterate_upper_bound(&upper_bound),
-            )
-            .map(|(key, val)| {
-                let txn = Transaction::decode(val)?;
+            .range(COL_TRANSACTION, header.hash.as_bytes(), &upper_bound)
+            .map(|kv| {
+                let (key, val) = kv?;
+                let txn = Transaction::decode(&*val)?;
                 Ok(IndexedTransaction::new(H256::from_slice(&key[32 + 8..]), txn))
             })
             .collect::<Result<Vec<_>, BoxError>>();
@@ -231,19 +245,13 @@ impl ChainDB {
         let mut upper_bound = hash.as_bytes().to_vec();
         upper_bound.push(0xFF); // [0xcafebabe00 .. 0xcafebabeff]
 
-        let ropts = ReadOptions::default()
-            .iterate_lower_bound(&hash.as_bytes())
-            .iterate_upper_bound(&upper_bound);
-        let txns = self
-            .transaction
-            .new_iterator(&ropts)
-            .map(|(key, val)| {
-                let txn = Transaction::decode(val)?;
+        self.range(COL_TRANSACTION, hash.as_bytes(), &upper_bound)
+            .map(|kv| {
+                let (key, val) = kv?;
+                let txn = Transaction::decode(&*val)?;
                 Ok(IndexedTransaction::new(H256::from_slice(&key[32 + 8..]), txn))
             })
-            .collect::<Result<Vec<_>, BoxError>>();
-        drop(ropts);
-        txns
+            .collect::<Result<Vec<_>, BoxError>>()
     }
 
     pub fn get_transaction_hashes_by_block_number(&self, num: i64) -> Result<Vec<H256>, BoxError> {
@@ -252,34 +260,18 @@ impl ChainDB {
         let mut upper_bound = [0u8; 8];
         BE::write_u64(&mut upper_bound[..], num as u64 + 1);
 
-        let ropts = ReadOptions::default()
-            .iterate_lower_bound(&lower_bound)
-            .iterate_upper_bound(&upper_bound);
-        let txn_hashes = self
-            .transaction
-            .new_iterator(&ropts)
-            .keys()
-            .map(|key| Ok(H256::from_slice(&key[32 + 8..])))
-            .collect::<Result<Vec<_>, BoxError>>();
-        drop(ropts);
-        txn_hashes
+        self.range(COL_TRANSACTION, &lower_bound, &upper_bound)
+            .map(|kv| Ok(H256::from_slice(&kv?.0[32 + 8..])))
+            .collect::<Result<Vec<_>, BoxError>>()
     }
 
     pub fn get_transaction_hashes_by_block_hash(&self, hash: &H256) -> Result<Vec<H256>, BoxError> {
         let mut upper_bound = hash.as_bytes().to_vec();
         upper_bound.push(0xFF); // [0xcafebabe00 .. 0xcafebabeff]
 
-        let ropts = ReadOptions::default()
-            .iterate_lower_bound(&hash.as_bytes())
-            .iterate_upper_bound(&upper_bound);
-        let txn_hashes = self
-            .transaction
-            .new_iterator(&ropts)
-            .keys()
-            .map(|key| Ok(H256::from_slice(&key[32 + 8..])))
-            .collect::<Result<Vec<_>, BoxError>>();
-        drop(ropts);
-        txn_hashes
+        self.range(COL_TRANSACTION, hash.as_bytes(), &upper_bound)
+            .map(|kv| Ok(H256::from_slice(&kv?.0[32 + 8..])))
+            .collect::<Result<Vec<_>, BoxError>>()
     }
 
     pub fn get_block_header_by_number(&self, num: i64) -> Result<IndexedBlockHeader, BoxError> {
@@ -295,9 +287,7 @@ impl ChainDB {
     }
 
     pub fn get_block_header(&self, hash: &H256) -> Result<IndexedBlockHeader, BoxError> {
-        self.block_header
-            .get(ReadOptions::default_instance(), hash.as_bytes())
-            .map_err(From::from)
+        self.get_raw(COL_BLOCK_HEADER, hash.as_bytes())
             .and_then(|raw_header| BlockHeader::decode(&*raw_header).map_err(From::from))
             .map(|header| IndexedBlockHeader::new(hash.clone(), header))
     }
@@ -308,14 +298,11 @@ impl ChainDB {
         BE::write_u64(&mut lower_bound[..8], num);
         let mut upper_bound = [0xff_u8; 32];
         BE::write_u64(&mut upper_bound[..8], num);
-        let ropt = ReadOptions::default()
-            .iterate_lower_bound(&lower_bound[..])
-            .iterate_upper_bound(&upper_bound[..])
-            .pin_data(true);
-
-        self.block_header
-            .new_iterator(&ropt)
-            .map(|(key, val)| IndexedBlockHeader::new(H256::from_slice(key), BlockHeader::decode(val).unwrap()))
+        self.range(COL_BLOCK_HEADER, &lower_bound, &upper_bound)
+            .map(|kv| {
+                let (key, val) = kv.unwrap();
+                IndexedBlockHeader::new(H256::from_slice(&key), BlockHeader::decode(&*val).unwrap())
+            })
             .collect()
     }
 
@@ -325,19 +312,9 @@ impl ChainDB {
         let mut upper_bound = [0xff_u8; 32];
         BE::write_u64(&mut upper_bound[..8], num);
 
-        let ropts = ReadOptions::default()
-            .iterate_lower_bound(&lower_bound[..])
-            .iterate_upper_bound(&upper_bound[..])
-            .pin_data(true);
-        let it = self.block_header.new_iterator(&ropts);
-
-        // FIXME: iterator key lifetime leaks, key might becomes same key
-        // ref: https://github.com/bh1xuw/rust-rocks/issues/15
-        let found = it
-            // .take_while(|(key, _)| &key[..8] == lower_bound)
-            .map(|(key, val)| (key.to_vec(), val.to_vec()))
-            .collect::<Vec<_>>();
-        drop(ropts); // holds lifetime of bound slice.
+        let found = self
+            .range(COL_BLOCK_HEADER, &lower_bound, &upper_bound)
+            .collect::<io::Result<Vec<_>>>()?;
 
         if found.is_empty() {
             return Err(Box::new(io::Error::new(io::ErrorKind::NotFound, "block not found")));
@@ -360,9 +337,7 @@ impl ChainDB {
     }
 
     pub fn get_block_by_id(&self, id: &H256) -> Result<IndexedBlock, BoxError> {
-        self.block_header
-            .get(ReadOptions::default_instance(), id.as_bytes())
-            .map_err(From::from)
+        self.get_raw(COL_BLOCK_HEADER, id.as_bytes())
             .and_then(|raw_header| BlockHeader::decode(&*raw_header).map_err(From::from))
             .map(|header| IndexedBlockHeader::new(id.clone(), header))
             .and_then(|header| self.get_block_from_header(header))
@@ -373,55 +348,39 @@ impl ChainDB {
     }
 
     pub fn get_transaction_by_id(&self, id: &H256) -> Result<IndexedTransaction, BoxError> {
-        let mut key = self
-            .transaction_block
-            .get(ReadOptions::default_instance(), id.as_bytes())?
-            .to_vec();
+        let mut key = self.get_raw(COL_TRANSACTION_BLOCK, id.as_bytes())?;
         key.extend_from_slice(id.as_bytes());
         let txn = self
-            .transaction
-            .get(ReadOptions::default_instance(), &key)
+            .get_raw(COL_TRANSACTION, &key)
             .map(|raw| Transaction::decode(&*raw).unwrap())
             .map(|txn| IndexedTransaction::new(id.clone(), txn))?;
         Ok(txn)
     }
 
     pub fn get_transaction_index(&self, id: &H256) -> Result<i32, BoxError> {
-        let key = self
-            .transaction_block
-            .get(ReadOptions::default_instance(), id.as_bytes())?;
+        let key = self.get_raw(COL_TRANSACTION_BLOCK, id.as_bytes())?;
         Ok(BE::read_u64(&key[32..]) as i32)
     }
 
     pub fn get_transaction_block_hash(&self, id: &H256) -> Result<H256, BoxError> {
-        let key = self
-            .transaction_block
-            .get(ReadOptions::default_instance(), id.as_bytes())?;
+        let key = self.get_raw(COL_TRANSACTION_BLOCK, id.as_bytes())?;
         Ok(H256::from_slice(&key[..32]))
     }
 
     pub fn get_block_header_by_transaction_hash(&self, txn_hash: &H256) -> Result<IndexedBlockHeader, BoxError> {
-        let block_key = self
-            .transaction_block
-            .get(ReadOptions::default_instance(), txn_hash.as_bytes())?;
-        self.block_header
-            .get(ReadOptions::default_instance(), &block_key[..32])
+        let block_key = self.get_raw(COL_TRANSACTION_BLOCK, txn_hash.as_bytes())?;
+        self.get_raw(COL_BLOCK_HEADER, &block_key[..32])
             .map(|raw| BlockHeader::decode(&*raw).unwrap())
             .map(|header| IndexedBlockHeader::new(H256::from_slice(&block_key[..32]), header))
-            .map_err(From::from)
     }
 
     pub fn delete_transaction(&self, txn: &IndexedTransaction, wb: &mut WriteBatch) -> Result<(), BoxError> {
-        let block_key = self
-            .transaction_block
-            .get(ReadOptions::default_instance(), txn.hash.as_bytes())?;
-
-        if let Err(e) = self.block_header.get(ReadOptions::default_instance(), &block_key[..32]) {
-            if e.is_not_found() {
-                wb.deletev_cf(&self.transaction, &[&*block_key, txn.hash.as_bytes()]);
-                wb.delete_cf(&self.transaction_block, txn.hash.as_bytes());
-                return Ok(());
-            }
+        let block_key = self.get_raw(COL_TRANSACTION_BLOCK, txn.hash.as_bytes())?;
+
+        if let Ok(None) = self.db.get(COL_BLOCK_HEADER, &block_key[..32]) {
+            wb.deletev(COL_TRANSACTION, &[&*block_key, txn.hash.as_bytes()]);
+            wb.delete(COL_TRANSACTION_BLOCK, txn.hash.as_bytes());
+            return Ok(());
         }
 
         Err(Box::new(io::Error::new(
@@ -431,63 +390,53 @@ impl ChainDB {
     }
 
     pub fn delete_block_by_number(&self, num: u64) -> Result<(), BoxError> {
-        let mut lower_bound = [0u8; 8];
-        BE::write_u64(&mut lower_bound[..], num);
+        let mut block_number = [0u8; 8];
+        BE::write_u64(&mut block_number[..], num);
 
-        let mut wb = WriteBatch::with_reserved_bytes(1024);
+        let mut wb = WriteBatch::new();
 
-        self.block_header
-            .new_iterator(&ReadOptions::default().iterate_lower_bound(&lower_bound))
-            .keys()
-            .take_while(|key| &key[..8] == &lower_bound)
-            .for_each(|key| {
-                info!("delete block {}", hex::encode(key));
-                wb.delete_cf(&self.block_header, key);
-            });
-        self.transaction
-            .new_iterator(&ReadOptions::default().iterate_lower_bound(&lower_bound))
-            .keys()
-            .take_while(|key| &key[..8] == &lower_bound)
-            .for_each(|key| {
-                info!("delete transaction {}", hex::encode(&key[32 + 8..]));
-                wb.delete_cf(&self.transaction, key);
-                wb.delete_cf(&self.transaction_block, &key[32 + 8..]);
-            });
+        for kv in self.prefix(COL_BLOCK_HEADER, &block_number) {
+            let (key, _) = kv?;
+            info!("delete block {}", hex::encode(&key));
+            wb.delete(COL_BLOCK_HEADER, &key);
+        }
+        for kv in self.prefix(COL_TRANSACTION, &block_number) {
+            let (key, _) = kv?;
+            info!("delete transaction {}", hex::encode(&key[32 + 8..]));
+            wb.delete(COL_TRANSACTION, &key);
+            wb.delete(COL_TRANSACTION_BLOCK, &key[32 + 8..]);
+        }
 
-        self.db.write(WriteOptions::default_instance(), &wb)?;
+        self.db.write(&wb)?;
 
         Ok(())
     }
 
     pub fn delete_block(&self, block: &IndexedBlock) -> bool {
-        let mut wb = WriteBatch::with_reserved_bytes(1024);
-
-        wb.delete_cf(&self.block_header, block.hash().as_bytes());
-
-        let header = &block.header;
-        self.transaction
-            .new_iterator(&ReadOptions::default().iterate_lower_bound(&header.hash.as_bytes()))
-            .keys()
-            .take_while(|key| &key[..32] == header.hash.as_bytes())
-            .for_each(|key| {
-                wb.delete_cf(&self.transaction, &key);
-                wb.delete_cf(&self.transaction_block, &key[32 + 8..]);
+        let mut wb = WriteBatch::new();
+
+        wb.delete(COL_BLOCK_HEADER, block.hash().as_bytes());
+
+        let scanned = self
+            .prefix(COL_TRANSACTION, block.hash().as_bytes())
+            .try_for_each(|kv| {
+                let (key, _) = kv?;
+                wb.delete(COL_TRANSACTION, &key);
+                wb.delete(COL_TRANSACTION_BLOCK, &key[32 + 8..]);
+                Ok::<_, io::Error>(())
             });
 
-        self.db.write(WriteOptions::default_instance(), &wb).is_ok()
+        scanned.is_ok() && self.db.write(&wb).is_ok()
     }
 
-    fn delete_block_without_reverse_index(&self, block: &IndexedBlock, wb: &mut WriteBatch) {
-        wb.delete_cf(&self.block_header, block.hash().as_bytes());
+    fn delete_block_without_reverse_index(&self, block: &IndexedBlock, wb: &mut WriteBatch) -> io::Result<()> {
+        wb.delete(COL_BLOCK_HEADER, block.hash().as_bytes());
 
-        let header = &block.header;
-        self.transaction
-            .new_iterator(&ReadOptions::default().iterate_lower_bound(&header.hash.as_bytes()))
-            .keys()
-            .take_while(|key| &key[..32] == header.hash.as_bytes())
-            .for_each(|key| {
-                wb.delete_cf(&self.transaction, &key);
-            });
+        for kv in self.prefix(COL_TRANSACTION, block.hash().as_bytes()) {
+            let (key, _) = kv?;
+            wb.delete(COL_TRANSACTION, &key);
+        }
+        Ok(())
     }
 
     fn relink_transactions_to_block(&self, block: &IndexedBlock, wb: &mut WriteBatch) {
@@ -500,10 +449,7 @@ impl ChainDB {
             (&mut corrent_reverse_index[..32]).copy_from_slice(block.hash().as_bytes());
             BE::write_u64(&mut corrent_reverse_index[32..], i as u64);
 
-            let reverse_index = self
-                .transaction_block
-                .get(ReadOptions::default_instance(), txn.hash.as_bytes())
-                .unwrap();
+            let reverse_index = self.get_raw(COL_TRANSACTION_BLOCK, txn.hash.as_bytes()).unwrap();
 
             if corrent_reverse_index != &*reverse_index {
                 println!(
@@ -512,17 +458,16 @@ impl ChainDB {
                     hex::encode(&*reverse_index),
                     hex::encode(&corrent_reverse_index),
                 );
-                wb.put_cf(&self.transaction_block, txn.hash.as_ref(), &corrent_reverse_index);
+                wb.put(COL_TRANSACTION_BLOCK, txn.hash.as_ref(), &corrent_reverse_index);
             }
         });
     }
 
-    pub fn block_hashes_from(&self, start_block_hash: &[u8], count: usize) -> Vec<Vec<u8>> {
-        self.block_header
-            .new_iterator(&ReadOptions::default().iterate_lower_bound(start_block_hash))
-            .keys()
+    pub fn block_hashes_from(&self, start_block_hash: &[u8], count: usize) -> Result<Vec<Vec<u8>>, BoxError> {
+        self.db
+            .iter_from(COL_BLOCK_HEADER, start_block_hash, None)
             .take(count)
-            .map(|key| key.to_vec())
+            .map(|kv| Ok(kv?.0))
             .collect()
     }
 
@@ -571,7 +516,7 @@ impl ChainDB {
             }
         }
 
-        let mut wb = WriteBatch::with_reserved_bytes(1024);
+        let mut wb = WriteBatch::new();
 
         let mut txn_whitelist = HashSet::new();
         let mut orphan_txns = HashSet::new();
@@ -586,9 +531,9 @@ impl ChainDB {
 
         for fork in tobe_purged_forks {
             for header in fork.iter() {
-                // wb.delete_cf(&self.block_header, header.hash.as_bytes());
+                // wb.delete(COL_BLOCK_HEADER, header.hash.as_bytes());
                 let block = self.get_block_from_header(header.clone()).unwrap();
-                self.delete_block_without_reverse_index(&block, &mut wb);
+                self.delete_block_without_reverse_index(&block, &mut wb)?;
                 println!("! delete block {:?}", header.hash);
                 for txn in block.transactions {
                     if !txn_whitelist.contains(&txn) {
@@ -601,7 +546,7 @@ impl ChainDB {
         if dry_run {
             return Ok(());
         }
-        self.db.write(WriteOptions::default_instance(), &wb)?;
+        self.db.write(&wb)?;
 
         if !orphan_txns.is_empty() {
             let mut f = OpenOptions::new()
@@ -618,10 +563,9 @@ impl ChainDB {
     }
 
     pub fn visit(&self) -> Result<(), Box<dyn Error>> {
-        let it = self.transaction.new_iterator(ReadOptions::default_instance());
-
-        for (key, raw) in it {
-            let txn = Transaction::decode(raw)?;
+        for kv in self.db.iter(COL_TRANSACTION) {
+            let (key, raw) = kv?;
+            let txn = Transaction::decode(&*raw)?;
             match ContractType::from_i32(txn.raw_data.as_ref().unwrap().contract.as_ref().unwrap().r#type) {
                 Some(ContractType::TransferContract) => {
                     println!("txn id: {} => {:?}", hex::encode(key), txn.result);
@@ -636,19 +580,14 @@ impl ChainDB {
     }
 
     pub fn block_headers<'a>(&'a self) -> impl Iterator<Item = IndexedBlockHeader> + 'a {
-        self.block_header
-            .new_iterator(ReadOptions::default_instance())
-            .map(|(blk_id, raw_header)| {
-                IndexedBlockHeader::new(H256::from_slice(blk_id), BlockHeader::decode(raw_header).unwrap())
-            })
+        self.db.iter(COL_BLOCK_HEADER).map(|kv| {
+            let (blk_id, raw_header) = kv.unwrap();
+            IndexedBlockHeader::new(H256::from_slice(&blk_id), BlockHeader::decode(&*raw_header).unwrap())
+        })
     }
 
     pub fn blocks<'a>(&'a self) -> impl Iterator<Item = IndexedBlock> + 'a {
-        self.block_header
-            .new_iterator(ReadOptions::default_instance())
-            .map(|(blk_id, raw_header)| {
-                IndexedBlockHeader::new(H256::from_slice(blk_id), BlockHeader::decode(raw_header).unwrap())
-            })
+        self.block_headers()
             .map(move |header| self.get_block_from_header(header).unwrap())
     }
 
@@ -665,14 +604,8 @@ impl ChainDB {
             BE::write_u64(&mut upper_bound[..8], num as u64);
 
             let mut ref_hashes = self
-                .block_header
-                .new_iterator(
-                    &ReadOptions::default()
-                        .iterate_lower_bound(&lower_bound[..])
-                        .iterate_upper_bound(&upper_bound[..]),
-                )
-                .keys()
-                .map(|raw_hash| H256::from_slice(raw_hash))
+                .range(COL_BLOCK_HEADER, &lower_bound, &upper_bound)
+                .map(|(raw_hash, _)| H256::from_slice(&raw_hash))
                 .collect::<Vec<_>>();
 
             let wrap_pos = 65536 - (num + 1) % 65536;
@@ -683,8 +616,7 @@ impl ChainDB {
     }
 
     pub fn get_parent_hash_verified_block_number(&self) -> u64 {
-        self.default
-            .get(ReadOptions::default_instance(), b"PARENT_HASH_VERIFIED")
+        self.get_raw(COL_DEFAULT, b"PARENT_HASH_VERIFIED")
             .map(|raw| BE::read_u64(&*raw))
             .unwrap_or(0)
     }
@@ -692,8 +624,8 @@ impl ChainDB {
     pub fn update_parent_hash_verified_block_number(&self, num: u64) -> Result<(), BoxError> {
         let mut raw = [0u8; 8];
         BE::write_u64(&mut raw[..], num);
-        self.default
-            .put(WriteOptions::default_instance(), b"PARENT_HASH_VERIFIED", &raw)
+        self.db
+            .put(COL_DEFAULT, b"PARENT_HASH_VERIFIED", &raw)
             .map_err(From::from)
     }
 
@@ -709,13 +641,9 @@ impl ChainDB {
             hex::encode(&parent_hash)
         );
 
-        for header in self
-            .block_header
-            .new_iterator(&ReadOptions::default().iterate_lower_bound(start_block.hash().as_bytes()))
-            .map(|(blk_id, raw_header)| {
-                IndexedBlockHeader::new(H256::from_slice(blk_id), BlockHeader::decode(raw_header).unwrap())
-            })
-        {
+        for kv in self.db.iter_from(COL_BLOCK_HEADER, start_block.hash().as_bytes(), None) {
+            let (blk_id, raw_header) = kv?;
+            let header = IndexedBlockHeader::new(H256::from_slice(&blk_id), BlockHeader::decode(&*raw_header).unwrap());
             if header.raw.raw_data.as_ref().unwrap().parent_hash != parent_hash {
                 let parent_block_number = BE::read_u64(&parent_hash[..8]);
                 // block_number - 1 to handle multiple forks
@@ -752,8 +680,7 @@ impl ChainDB {
     }
 
     pub fn get_merkle_tree_verified_block_number(&self) -> u64 {
-        self.default
-            .get(ReadOptions::default_instance(), b"MERKLE_TREE_VERIFIED")
+        self.get_raw(COL_DEFAULT, b"MERKLE_TREE_VERIFIED")
             .map(|raw| BE::read_u64(&*raw))
             .unwrap_or(0)
     }
@@ -761,18 +688,18 @@ impl ChainDB {
     pub fn update_merkle_tree_verified_block_number(&self, num: u64) -> Result<(), BoxError> {
         let mut raw = [0u8; 8];
         BE::write_u64(&mut raw[..], num);
-        self.default
-            .put(WriteOptions::default_instance(), b"MERKLE_TREE_VERIFIED", &raw)
+        self.db
+            .put(COL_DEFAULT, b"MERKLE_TREE_VERIFIED", &raw)
             .map_err(From::from)
     }
 
     pub fn verify_merkle_tree(&self, patch: &HashMap<H256, H256>) -> Result<bool, Box<dyn Error>> {
         let start_block = self.get_block_by_number(self.get_merkle_tree_verified_block_number())?;
-        let ropt = ReadOptions::default().iterate_lower_bound(start_block.hash().as_bytes());
         info!("verify merkle tree from {}", start_block.number());
 
-        for (blk_id, raw_header) in self.block_header.new_iterator(&ropt) {
-            let header = IndexedBlockHeader::new(H256::from_slice(blk_id), BlockHeader::decode(raw_header).unwrap());
+        for kv in self.db.iter_from(COL_BLOCK_HEADER, start_block.hash().as_bytes(), None) {
+            let (blk_id, raw_header) = kv?;
+            let header = IndexedBlockHeader::new(H256::from_slice(&blk_id), BlockHeader::decode(&*raw_header).unwrap());
             let block = self.get_block_from_header(header).unwrap();
 
             if !block.verify_merkle_root_hash() {
@@ -792,30 +719,16 @@ impl ChainDB {
     }
 
     pub fn get_db_property(&self, key: &str) -> u64 {
-        self.db.get_int_property(key).unwrap_or_default()
+        self.db.property(key).unwrap_or_default()
     }
 
     pub fn get_accumulated_db_property(&self, key: &str) -> u64 {
-        [
-            &self.default,
-            &self.block_header,
-            &self.transaction,
-            &self.transaction_block,
-        ]
-        .iter()
-        .map(|cf| cf.get_int_property(key).unwrap_or_default())
-        .sum()
+        self.db.accumulated_property(key).unwrap_or_default()
     }
 
     pub fn report_status(&self) {
-        let n_compactions = self
-            .db
-            .get_int_property("rocksdb.num-running-compactions")
-            .unwrap_or_default();
-        let n_flushes = self
-            .db
-            .get_int_property("rocksdb.num-running-flushes")
-            .unwrap_or_default();
+        let n_compactions = self.get_db_property("rocksdb.num-running-compactions");
+        let n_flushes = self.get_db_property("rocksdb.num-running-flushes");
         info!(
             "background db status: compactions={}, flushes={}",
             n_compactions, n_flushes
@@ -824,14 +737,8 @@ impl ChainDB {
 
     pub fn await_background_jobs(&self) {
         loop {
-            let n_compactions = self
-                .db
-                .get_int_property("rocksdb.num-running-compactions")
-                .unwrap_or_default();
-            let n_flushes = self
-                .db
-                .get_int_property("rocksdb.num-running-flushes")
-                .unwrap_or_default();
+            let n_compactions = self.get_db_property("rocksdb.num-running-compactions");
+            let n_flushes = self.get_db_property("rocksdb.num-running-flushes");
             if n_compactions + n_flushes <= 1 {
                 break;
             }
@@ -844,18 +751,11 @@ impl ChainDB {
     }
 
     pub fn compact_db(&self) -> Result<(), BoxError> {
-        self.default.compact_range(&Default::default(), ..)?;
-        self.block_header.compact_range(&Default::default(), ..)?;
-        self.transaction.compact_range(&Default::default(), ..)?;
-        self.transaction_block.compact_range(&Default::default(), ..)?;
+        self.db.compact()?;
         Ok(())
     }
 
     pub unsafe fn prepare_close(&self) {
-        info!("flush db ... {:?}", self.db.flush(&FlushOptions::default()));
-        info!("cancal background work ...");
-        self.db.cancel_background_work(/* wait: */ true);
-        info!("syncing WAL ... {:?}", self.db.sync_wal());
-        // eprintln!("Close DB ... {:?}", self.db.close());
+        self.db.prepare_close();
     }
 }
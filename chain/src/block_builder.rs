@@ -44,6 +44,14 @@ impl BlockBuilder {
         self
     }
 
+    /// Set account state root, after transactions are applied.
+    pub fn set_account_state_root(&mut self, root: &H256) {
+        self.header
+            .raw_data
+            .as_mut()
+            .map(|h| h.account_state_root = root.as_bytes().into());
+    }
+
     pub fn push_transaction(&mut self, txn: Transaction) {
         self.txns.push(txn);
     }
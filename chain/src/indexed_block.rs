@@ -141,6 +141,11 @@ impl IndexedBlock {
         &self.header.raw.raw_data.as_ref().unwrap().merkle_root_hash
     }
 
+    /// Account state root, empty if not set by the producer.
+    pub fn account_state_root(&self) -> &[u8] {
+        &self.header.raw.raw_data.as_ref().unwrap().account_state_root
+    }
+
     pub fn verify_merkle_root_hash(&self) -> bool {
         self.merkle_root_hash() == merkle_root(&self.transactions).as_bytes()
     }
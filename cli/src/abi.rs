@@ -0,0 +1,362 @@
+//! ABI encoding and decoding of smart contract calls.
+//!
+//! Addresses are in Tron format, base58check or hex.
+
+use crypto::keccak256;
+use ethabi::param_type::{Reader, Writer};
+use ethabi::token::{LenientTokenizer, Tokenizer};
+use ethabi::{ParamType, Token};
+use keys::Address;
+use proto::common::smart_contract::abi::{Entry, EntryType, Param, StateMutabilityType};
+use proto::common::smart_contract::Abi;
+use serde_json::Value;
+
+use crate::{custom_error, Result};
+
+/// Parse ABI in JSON, either the solc output format or the format returned by the node.
+pub fn parse_abi(json: &str) -> Result<Abi> {
+    let val: Value = serde_json::from_str(json)?;
+    // Truffle artifact or `{"entrys": [...]}` from java-tron
+    let entries = match val {
+        Value::Array(entries) => entries,
+        Value::Object(mut obj) => match obj.remove("abi").or_else(|| obj.remove("entrys")) {
+            Some(Value::Array(entries)) => entries,
+            _ => return Err(custom_error("malformed ABI")),
+        },
+        _ => return Err(custom_error("malformed ABI")),
+    };
+    Ok(Abi {
+        entries: entries.iter().map(parse_entry).collect::<Result<_>>()?,
+    })
+}
+
+fn parse_entry(val: &Value) -> Result<Entry> {
+    let entry_type = match &val["type"] {
+        Value::Number(num) => EntryType::from_i32(num.as_i64().unwrap_or_default() as i32),
+        Value::String(s) => match s.to_lowercase().as_str() {
+            "constructor" => Some(EntryType::Constructor),
+            "function" => Some(EntryType::Function),
+            "event" => Some(EntryType::Event),
+            "fallback" => Some(EntryType::Fallback),
+            "receive" => Some(EntryType::Receive),
+            _ => None,
+        },
+        // default to function in solc
+        Value::Null => Some(EntryType::Function),
+        _ => None,
+    }
+    .ok_or_else(|| custom_error("malformed ABI entry type"))?;
+
+    let mutability = match val.get("stateMutability").or_else(|| val.get("state_mutability")) {
+        Some(Value::Number(num)) => StateMutabilityType::from_i32(num.as_i64().unwrap_or_default() as i32),
+        Some(Value::String(s)) => match s.to_lowercase().as_str() {
+            "pure" => Some(StateMutabilityType::Pure),
+            "view" => Some(StateMutabilityType::View),
+            "nonpayable" => Some(StateMutabilityType::Nonpayable),
+            "payable" => Some(StateMutabilityType::Payable),
+            _ => None,
+        },
+        _ => Some(StateMutabilityType::UnknownStateMutabilityType),
+    }
+    .ok_or_else(|| custom_error("malformed ABI state mutability"))?;
+
+    Ok(Entry {
+        anonymous: val["anonymous"].as_bool().unwrap_or_default(),
+        constant: val["constant"].as_bool().unwrap_or_default(),
+        name: val["name"].as_str().unwrap_or_default().to_owned(),
+        inputs: parse_params(&val["inputs"]),
+        outputs: parse_params(&val["outputs"]),
+        r#type: entry_type as i32,
+        payable: val["payable"].as_bool().unwrap_or_default(),
+        state_mutability: mutability as i32,
+    })
+}
+
+fn parse_params(val: &Value) -> Vec<Param> {
+    val.as_array()
+        .map(|params| {
+            params
+                .iter()
+                .map(|param| Param {
+                    indexed: param["indexed"].as_bool().unwrap_or_default(),
+                    name: param["name"].as_str().unwrap_or_default().to_owned(),
+                    r#type: param["type"].as_str().unwrap_or_default().to_owned(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn param_types(params: &[Param]) -> Result<Vec<ParamType>> {
+    params
+        .iter()
+        .map(|param| Reader::read(&param.r#type).map_err(|e| custom_error(&e.to_string())))
+        .collect()
+}
+
+/// Parse a function signature like `transfer(address,uint256)`.
+pub fn parse_signature(signature: &str) -> Result<(String, Vec<ParamType>)> {
+    let signature = signature.replace(' ', "");
+    let (name, rest) = match signature.find('(') {
+        Some(pos) if signature.ends_with(')') => (&signature[..pos], &signature[pos + 1..signature.len() - 1]),
+        _ => return Err(custom_error("malformed function signature")),
+    };
+    if rest.is_empty() {
+        return Ok((name.to_owned(), vec![]));
+    }
+
+    let mut types = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                types.push(&rest[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    types.push(&rest[start..]);
+
+    let types = types
+        .into_iter()
+        .map(|ty| Reader::read(ty).map_err(|e| custom_error(&e.to_string())))
+        .collect::<Result<_>>()?;
+    Ok((name.to_owned(), types))
+}
+
+/// Canonical signature of an ABI entry.
+pub fn signature_of(entry: &Entry) -> String {
+    let types: Vec<_> = entry.inputs.iter().map(|param| &param.r#type[..]).collect();
+    format!("{}({})", entry.name, types.join(","))
+}
+
+/// Find function by name or full signature.
+pub fn find_function<'a>(abi: &'a Abi, name_or_signature: &str) -> Result<&'a Entry> {
+    let mut functions = abi
+        .entries
+        .iter()
+        .filter(|entry| entry.r#type == EntryType::Function as i32)
+        .filter(|entry| {
+            if name_or_signature.contains('(') {
+                signature_of(entry) == name_or_signature.replace(' ', "")
+            } else {
+                entry.name == name_or_signature
+            }
+        });
+    match (functions.next(), functions.next()) {
+        (Some(entry), None) => Ok(entry),
+        (Some(_), Some(_)) => Err(custom_error("ambiguous function name, use full signature")),
+        _ => Err(custom_error("function not found in ABI")),
+    }
+}
+
+pub fn constructor_of(abi: &Abi) -> Option<&Entry> {
+    abi.entries
+        .iter()
+        .find(|entry| entry.r#type == EntryType::Constructor as i32)
+}
+
+pub fn is_constant(entry: &Entry) -> bool {
+    entry.constant
+        || entry.state_mutability == StateMutabilityType::View as i32
+        || entry.state_mutability == StateMutabilityType::Pure as i32
+}
+
+/// Tokenizer accepting Tron addresses.
+struct TronTokenizer;
+
+impl Tokenizer for TronTokenizer {
+    fn tokenize_address(value: &str) -> std::result::Result<[u8; 20], ethabi::Error> {
+        if value.starts_with("0x") && value.len() == 42 {
+            return LenientTokenizer::tokenize_address(&value[2..]);
+        }
+        let addr = crate::util::parse_address(value).map_err(|_| ethabi::Error::InvalidData)?;
+        let mut raw = [0u8; 20];
+        raw.copy_from_slice(addr.as_tvm_bytes());
+        Ok(raw)
+    }
+
+    fn tokenize_string(value: &str) -> std::result::Result<String, ethabi::Error> {
+        LenientTokenizer::tokenize_string(value)
+    }
+
+    fn tokenize_bool(value: &str) -> std::result::Result<bool, ethabi::Error> {
+        LenientTokenizer::tokenize_bool(value)
+    }
+
+    fn tokenize_bytes(value: &str) -> std::result::Result<Vec<u8>, ethabi::Error> {
+        LenientTokenizer::tokenize_bytes(value.trim_start_matches("0x"))
+    }
+
+    fn tokenize_fixed_bytes(value: &str, len: usize) -> std::result::Result<Vec<u8>, ethabi::Error> {
+        LenientTokenizer::tokenize_fixed_bytes(value.trim_start_matches("0x"), len)
+    }
+
+    fn tokenize_uint(value: &str) -> std::result::Result<[u8; 32], ethabi::Error> {
+        LenientTokenizer::tokenize_uint(&value.replace('_', ""))
+    }
+
+    fn tokenize_int(value: &str) -> std::result::Result<[u8; 32], ethabi::Error> {
+        LenientTokenizer::tokenize_int(&value.replace('_', ""))
+    }
+}
+
+/// ABI encode arguments.
+pub fn encode_args(types: &[ParamType], args: &[&str]) -> Result<Vec<u8>> {
+    if types.len() != args.len() {
+        return Err(custom_error(&format!(
+            "expected {} arguments, got {}",
+            types.len(),
+            args.len()
+        )));
+    }
+    let tokens = types
+        .iter()
+        .zip(args)
+        .map(|(ty, arg)| TronTokenizer::tokenize(ty, arg).map_err(|e| custom_error(&format!("{}: {}", arg, e))))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ethabi::encode(&tokens))
+}
+
+/// Keccak256 hash of the canonical signature.
+fn long_signature(name: &str, types: &[ParamType]) -> Vec<u8> {
+    let types: Vec<_> = types.iter().map(Writer::write).collect();
+    keccak256(format!("{}({})", name, types.join(",")).as_bytes())
+        .as_bytes()
+        .to_vec()
+}
+
+/// Call data of a function call, selector followed by encoded arguments.
+pub fn encode_call(name: &str, types: &[ParamType], args: &[&str]) -> Result<Vec<u8>> {
+    let mut data = long_signature(name, types)[..4].to_vec();
+    data.extend(encode_args(types, args)?);
+    Ok(data)
+}
+
+/// Decode return values, in `(name, value)` pairs.
+pub fn decode_outputs(params: &[Param], data: &[u8]) -> Result<Vec<(String, String)>> {
+    let tokens = ethabi::decode(&param_types(params)?, data).map_err(|e| custom_error(&e.to_string()))?;
+    Ok(params
+        .iter()
+        .zip(tokens.iter())
+        .map(|(param, token)| (param.name.clone(), format_token(token)))
+        .collect())
+}
+
+/// Decode an event log, returns a line like `Transfer(from: ..., to: ..., value: ...)`.
+pub fn decode_log(abi: &Abi, topics: &[Vec<u8>], data: &[u8]) -> Option<String> {
+    let event = abi.entries.iter().find(|entry| {
+        entry.r#type == EntryType::Event as i32
+            && !entry.anonymous
+            && param_types(&entry.inputs)
+                .map(|types| topics.first().map(|t| &t[..]) == Some(&long_signature(&entry.name, &types)[..]))
+                .unwrap_or(false)
+    })?;
+
+    let unindexed_types = event
+        .inputs
+        .iter()
+        .filter(|param| !param.indexed)
+        .map(|param| Reader::read(&param.r#type))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .ok()?;
+    let mut unindexed_tokens = ethabi::decode(&unindexed_types, data).ok()?.into_iter();
+    let mut indexed_topics = topics[1..].iter();
+
+    let fields = event
+        .inputs
+        .iter()
+        .map(|param| {
+            let value = if param.indexed {
+                let topic = indexed_topics.next()?;
+                let ty = Reader::read(&param.r#type).ok()?;
+                match ty {
+                    // dynamic types are stored as hash
+                    ParamType::String | ParamType::Bytes | ParamType::Array(_) | ParamType::Tuple(_) => {
+                        format!("0x{}", hex::encode(topic))
+                    }
+                    _ => format_token(&ethabi::decode(&[ty], topic).ok()?[0]),
+                }
+            } else {
+                format_token(&unindexed_tokens.next()?)
+            };
+            Some(format!("{}: {}", param.name, value))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(format!("{}({})", event.name, fields.join(", ")))
+}
+
+/// Format a token, in decimal integer and Tron address.
+pub fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(addr) => Address::from_tvm_bytes(addr.as_bytes()).to_string(),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Uint(val) => val.to_string(),
+        Token::Int(val) if val.bit(255) => format!("-{}", (!*val).overflowing_add(1.into()).0),
+        Token::Int(val) => val.to_string(),
+        Token::Bool(val) => val.to_string(),
+        Token::String(val) => format!("{:?}", val),
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            format!("[{}]", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+        Token::Tuple(tokens) => format!("({})", tokens.iter().map(format_token).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_call() {
+        let (name, types) = parse_signature("transfer(address, uint256)").unwrap();
+        assert_eq!(name, "transfer");
+        let data = encode_call(&name, &types, &["TJRabPrwbZy45sbavfcjinPJC18kjpRTv8", "1_000"]).unwrap();
+        assert_eq!(hex::encode(&data[..4]), "a9059cbb");
+        let to: Address = "TJRabPrwbZy45sbavfcjinPJC18kjpRTv8".parse().unwrap();
+        assert_eq!(&data[4 + 12..4 + 32], to.as_tvm_bytes());
+        assert_eq!(data[4 + 32 + 30..], [0x03, 0xe8]);
+
+        let (_, types) = parse_signature("f((uint256,bool),address[2])").unwrap();
+        assert_eq!(types.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_abi_and_decode() {
+        let abi = parse_abi(
+            r#"[
+                {"type": "function", "name": "balanceOf", "stateMutability": "view",
+                 "inputs": [{"name": "who", "type": "address"}],
+                 "outputs": [{"name": "", "type": "int256"}]},
+                {"type": 3, "name": "Transfer", "inputs": [
+                    {"name": "from", "type": "address", "indexed": true},
+                    {"name": "value", "type": "uint256"}]}
+            ]"#,
+        )
+        .unwrap();
+        let func = find_function(&abi, "balanceOf(address)").unwrap();
+        assert!(is_constant(func));
+
+        let outputs = decode_outputs(&func.outputs, &[0xff; 32]).unwrap();
+        assert_eq!(outputs[0].1, "-1");
+
+        let from = "TJRabPrwbZy45sbavfcjinPJC18kjpRTv8".parse::<Address>().unwrap();
+        let mut topic = vec![0u8; 12];
+        topic.extend_from_slice(from.as_tvm_bytes());
+        let topics = vec![
+            long_signature("Transfer", &[ParamType::Address, ParamType::Uint(256)]),
+            topic,
+        ];
+        let mut data = vec![0u8; 32];
+        data[31] = 42;
+        assert_eq!(
+            decode_log(&abi, &topics, &data).unwrap(),
+            "Transfer(from: TJRabPrwbZy45sbavfcjinPJC18kjpRTv8, value: 42)"
+        );
+    }
+}
@@ -3,6 +3,8 @@ use keys::Address;
 use proto::chain::transaction::Contract;
 use proto::contract as contract_pb;
 
+use crate::util::parse_address;
+
 pub fn main(matches: &ArgMatches) -> Option<Contract> {
     match matches.subcommand() {
         ("create", Some(arg_matches)) => create(arg_matches),
@@ -15,8 +17,8 @@ pub fn main(matches: &ArgMatches) -> Option<Contract> {
 fn create(matches: &ArgMatches) -> Option<Contract> {
     use proto::common::AccountType;
 
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
-    let to: Address = matches.value_of("RECIPIENT")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
+    let to: Address = parse_address(matches.value_of("RECIPIENT")?).ok()?;
 
     let account_type = match matches.value_of("type") {
         Some("Normal") => AccountType::Normal,
@@ -34,7 +36,7 @@ fn create(matches: &ArgMatches) -> Option<Contract> {
 }
 
 fn set_name(matches: &ArgMatches) -> Option<Contract> {
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
     let name = matches.value_of("NAME").expect("required; qed");
 
     let inner = contract_pb::AccountUpdateContract {
@@ -45,7 +47,7 @@ fn set_name(matches: &ArgMatches) -> Option<Contract> {
 }
 
 fn set_id(matches: &ArgMatches) -> Option<Contract> {
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
     let id = matches.value_of("ID").expect("required; qed");
 
     let inner = contract_pb::SetAccountIdContract {
@@ -0,0 +1,163 @@
+use chrono::DateTime;
+use clap::ArgMatches;
+use keys::Address;
+use proto::chain::transaction::Contract;
+use proto::contract as contract_pb;
+
+use crate::util::{parse_address, parse_amount, parse_amount_with_currency};
+use crate::{custom_error, Result};
+
+pub fn main(matches: &ArgMatches) -> Result<Contract> {
+    match matches.subcommand() {
+        ("transfer", Some(arg_matches)) => transfer(arg_matches),
+        ("issue", Some(arg_matches)) => issue(arg_matches),
+        ("participate_issue", Some(arg_matches)) => participate_issue(arg_matches),
+        ("update", Some(arg_matches)) => update(arg_matches),
+        ("unfreeze", Some(arg_matches)) => unfreeze(arg_matches),
+        _ => unreachable!("checked; qed"),
+    }
+}
+
+fn transfer(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let to: Address = parse_address(matches.value_of("RECIPIENT").expect("required; qed"))?;
+    let amount = parse_amount(matches.value_of("AMOUNT").expect("required; qed"))
+        .ok_or_else(|| custom_error("malformed AMOUNT"))?;
+    let token_id = matches.value_of("token-id").expect("required; qed");
+
+    let inner = contract_pb::TransferAssetContract {
+        owner_address: from.as_bytes().into(),
+        to_address: to.as_bytes().into(),
+        asset_name: token_id.into(),
+        amount,
+    };
+
+    Ok(inner.into())
+}
+
+fn issue(matches: &ArgMatches) -> Result<Contract> {
+    use proto::contract::asset_issue_contract::FrozenSupply;
+
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let name = matches.value_of("NAME").expect("required; qed");
+    let abbr = matches.value_of("abbr").unwrap_or(name);
+    let total_supply = parse_amount(matches.value_of("SUPPLY").expect("required; qed"))
+        .ok_or_else(|| custom_error("malformed SUPPLY"))?;
+    let precision = matches.value_of("precision").expect("has default; qed").parse()?;
+
+    let frozen_supply = matches
+        .values_of("freeze")
+        .map(|vals| {
+            vals.map(|pair| {
+                if let [amount, days] = &pair.split('=').collect::<Vec<_>>()[..] {
+                    Ok(FrozenSupply {
+                        frozen_amount: parse_amount(amount).ok_or_else(|| custom_error("malformed freeze AMOUNT"))?,
+                        frozen_days: days.parse()?,
+                    })
+                } else {
+                    Err(custom_error("malformed AMOUNT=DAYS freeze"))
+                }
+            })
+            .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let (trx_num, num) = match &matches
+        .value_of("exchange-rate")
+        .expect("has default; qed")
+        .split(':')
+        .collect::<Vec<_>>()[..]
+    {
+        [trx_num, num] => (
+            parse_amount(trx_num).ok_or_else(|| custom_error("malformed exchange rate"))?,
+            parse_amount(num).ok_or_else(|| custom_error("malformed exchange rate"))?,
+        ),
+        _ => return Err(custom_error("malformed TRX:ICO exchange rate")),
+    };
+
+    let (start_time, end_time) = match &matches
+        .values_of("issuing-period")
+        .expect("required; qed")
+        .collect::<Vec<_>>()[..]
+    {
+        [start, end] => (
+            DateTime::parse_from_rfc3339(start)?.timestamp_millis(),
+            DateTime::parse_from_rfc3339(end)?.timestamp_millis(),
+        ),
+        _ => unreachable!("restricted by cli.yml; qed"),
+    };
+
+    let inner = contract_pb::AssetIssueContract {
+        owner_address: from.as_bytes().into(),
+        name: name.into(),
+        abbr: abbr.into(),
+        total_supply,
+        frozen_supply,
+        trx_num: trx_num as i32,
+        num: num as i32,
+        precision,
+        start_time,
+        end_time,
+        description: matches.value_of("description").unwrap_or_default().into(),
+        url: matches.value_of("url").expect("required; qed").into(),
+        free_asset_bandwidth_limit: matches
+            .value_of("bandwidth-limit-per-account")
+            .and_then(parse_amount)
+            .unwrap_or_default(),
+        public_free_asset_bandwidth_limit: matches
+            .value_of("bandwidth-limit-for-issuer")
+            .and_then(parse_amount)
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+
+    Ok(inner.into())
+}
+
+fn participate_issue(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let issuer: Address = parse_address(matches.value_of("ISSUER").expect("required; qed"))?;
+    let amount = parse_amount_with_currency(matches.value_of("AMOUNT").expect("required; qed"), "TRX", 6)
+        .ok_or_else(|| custom_error("malformed AMOUNT"))?;
+    let token_id = matches.value_of("token-id").expect("required; qed");
+
+    let inner = contract_pb::ParticipateAssetIssueContract {
+        owner_address: from.as_bytes().into(),
+        to_address: issuer.as_bytes().into(),
+        asset_name: token_id.into(),
+        amount,
+    };
+
+    Ok(inner.into())
+}
+
+fn update(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+
+    let inner = contract_pb::UpdateAssetContract {
+        owner_address: from.as_bytes().into(),
+        description: matches.value_of("description").unwrap_or_default().into(),
+        url: matches.value_of("url").expect("required; qed").into(),
+        new_limit: matches
+            .value_of("bandwidth-limit-per-account")
+            .and_then(parse_amount)
+            .unwrap_or_default(),
+        new_public_limit: matches
+            .value_of("bandwidth-limit-for-issuer")
+            .and_then(parse_amount)
+            .unwrap_or_default(),
+    };
+
+    Ok(inner.into())
+}
+
+fn unfreeze(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+
+    let inner = contract_pb::UnfreezeAssetContract {
+        owner_address: from.as_bytes().into(),
+    };
+
+    Ok(inner.into())
+}
@@ -0,0 +1,283 @@
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+
+use clap::ArgMatches;
+use keys::Address;
+use proto::chain::transaction::Contract;
+use proto::common::smart_contract::Abi;
+use proto::common::SmartContract;
+use proto::contract as contract_pb;
+
+use crate::abi;
+use crate::util::{parse_address, parse_amount, parse_amount_with_currency};
+use crate::{custom_error, graphql_query, Result};
+
+/// Default fee limit when not given by `--fee-limit`, 150 TRX, the same as TronWeb.
+const DEFAULT_FEE_LIMIT: i64 = 150_000_000;
+
+pub fn main(matches: &ArgMatches, global_matches: &ArgMatches) -> Result<()> {
+    let cntr = match matches.subcommand() {
+        ("deploy", Some(arg_matches)) => deploy(arg_matches)?,
+        ("call", Some(arg_matches)) => return call(arg_matches, global_matches),
+        ("logs", Some(arg_matches)) => return logs(arg_matches, global_matches),
+        ("set_energy_share", Some(arg_matches)) => set_energy_share(arg_matches)?,
+        ("set_energy_limit", Some(arg_matches)) => set_energy_limit(arg_matches)?,
+        ("clear_abi", Some(arg_matches)) => clear_abi(arg_matches)?,
+        _ => unreachable!("checked; qed"),
+    };
+    crate::pack_and_send(cntr, DEFAULT_FEE_LIMIT, global_matches)
+}
+
+/// Returns (call_token_id, call_token_value).
+fn token_value_of(matches: &ArgMatches) -> Result<(i64, i64)> {
+    match (matches.value_of("token-id"), matches.value_of("token-value")) {
+        (Some(id), Some(value)) => Ok((
+            id.parse()?,
+            parse_amount(value).ok_or_else(|| custom_error("malformed token value"))?,
+        )),
+        _ => Ok((0, 0)),
+    }
+}
+
+/// Read hex from a file, or the argument itself.
+fn read_hex(hex_or_path: &str) -> Result<Vec<u8>> {
+    let content = if Path::new(hex_or_path).is_file() {
+        fs::read_to_string(hex_or_path)?
+    } else {
+        hex_or_path.to_owned()
+    };
+    hex::decode(content.trim().trim_start_matches("0x")).map_err(From::from)
+}
+
+fn deploy(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let mut code = read_hex(matches.value_of("bin").expect("required; qed"))?;
+    let call_value = parse_amount_with_currency(matches.value_of("value").expect("has default; qed"), "TRX", 6)
+        .ok_or_else(|| custom_error("malformed call value"))?;
+    let energy_share = matches.value_of("energy-share").expect("has default; qed").parse()?;
+    let energy_limit = parse_amount(matches.value_of("energy-limit").expect("has default; qed"))
+        .ok_or_else(|| custom_error("malformed energy limit"))?;
+    let (call_token_id, call_token_value) = token_value_of(matches)?;
+
+    let abi = match matches.value_of("abi") {
+        Some(path) => Some(abi::parse_abi(&fs::read_to_string(path)?)?),
+        None => None,
+    };
+    if let Some(args) = matches.values_of("constructor-args") {
+        let args: Vec<_> = args.collect();
+        let constructor = abi
+            .as_ref()
+            .and_then(abi::constructor_of)
+            .ok_or_else(|| custom_error("constructor not found in ABI"))?;
+        code.extend(abi::encode_args(&abi::param_types(&constructor.inputs)?, &args)?);
+    }
+
+    let new_contract = SmartContract {
+        origin_address: from.as_bytes().into(),
+        abi,
+        bytecode: code,
+        call_value,
+        consume_user_energy_percent: energy_share,
+        name: matches.value_of("name").expect("has default; qed").into(),
+        origin_energy_limit: energy_limit,
+        ..Default::default()
+    };
+
+    let inner = contract_pb::CreateSmartContract {
+        owner_address: from.as_bytes().into(),
+        new_contract: Some(new_contract),
+        call_token_value,
+        call_token_id,
+    };
+
+    Ok(inner.into())
+}
+
+fn call(matches: &ArgMatches, global_matches: &ArgMatches) -> Result<()> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let contract: Address = parse_address(matches.value_of("CONTRACT").expect("required; qed"))?;
+    let call_value = parse_amount_with_currency(matches.value_of("value").expect("has default; qed"), "TRX", 6)
+        .ok_or_else(|| custom_error("malformed call value"))?;
+    let (call_token_id, call_token_value) = token_value_of(matches)?;
+
+    let contract_abi = fetch_abi(&contract, global_matches)?;
+    let (data, entry) = match matches.value_of("METHOD") {
+        Some(method) => {
+            let args: Vec<_> = matches.values_of("ARGS").map(|args| args.collect()).unwrap_or_default();
+            let entry = contract_abi
+                .as_ref()
+                .and_then(|contract_abi| abi::find_function(contract_abi, method).ok());
+            let (name, types) = if method.contains('(') {
+                abi::parse_signature(method)?
+            } else {
+                let entry = entry.ok_or_else(|| custom_error("function not found in ABI, use full signature"))?;
+                (entry.name.clone(), abi::param_types(&entry.inputs)?)
+            };
+            (abi::encode_call(&name, &types, &args)?, entry)
+        }
+        None => (read_hex(matches.value_of("data").expect("required; qed"))?, None),
+    };
+
+    let inner = contract_pb::TriggerSmartContract {
+        owner_address: from.as_bytes().into(),
+        contract_address: contract.as_bytes().into(),
+        call_value,
+        data,
+        call_token_value,
+        call_token_id,
+    };
+
+    if matches.is_present("const") || entry.map(abi::is_constant).unwrap_or(false) {
+        let query = format!(
+            "{{ call(data: {}) {{ data energyUsed vmStatus logs {{ topics data }} }} }}",
+            call_data_of(&inner)
+        );
+        let result = graphql_query(&query, global_matches)?;
+        let result = &result["call"];
+        println!("VM Status => {}", result["vmStatus"].as_str().unwrap_or_default());
+        println!("Energy Used => {}", result["energyUsed"].as_str().unwrap_or_default());
+
+        let data = hex::decode(result["data"].as_str().unwrap_or_default())?;
+        match entry {
+            Some(entry) if !data.is_empty() => {
+                for (name, value) in abi::decode_outputs(&entry.outputs, &data)? {
+                    println!("  {} => {}", if name.is_empty() { "_" } else { &name }, value);
+                }
+            }
+            _ => println!("Result => {}", hex::encode(&data)),
+        }
+        if let Some(contract_abi) = contract_abi.as_ref() {
+            print_logs(contract_abi, &result["logs"]);
+        }
+        return Ok(());
+    }
+
+    let query = format!(
+        "{{ energy: estimateEnergy(data: {}) energyPrice: chain {{ parameter(id: {}) {{ value }} }} }}",
+        call_data_of(&inner),
+        ENERGY_PRICE_PARAMETER_ID
+    );
+    let result = graphql_query(&query, global_matches)?;
+    let energy: i64 = result["energy"].as_str().unwrap_or_default().parse()?;
+    let energy_price = result["energyPrice"]["parameter"]["value"].as_i64().unwrap_or_default();
+    println!("Estimated Energy => {}", energy);
+
+    // 20% headroom for state changes between estimation and execution
+    let fee_limit = energy * energy_price * 12 / 10;
+    crate::pack_and_send(inner.into(), fee_limit, global_matches)
+}
+
+/// The `EnergyPrice` chain parameter, in SUN.
+const ENERGY_PRICE_PARAMETER_ID: i32 = 11;
+
+/// `CallData` input object of GraphQL.
+fn call_data_of(trigger: &contract_pb::TriggerSmartContract) -> String {
+    format!(
+        r#"{{ from: "{}", to: "{}", data: "{}", value: "{}", tokenId: {}, tokenValue: "{}" }}"#,
+        Address::try_from(&trigger.owner_address).expect("valid address; qed"),
+        Address::try_from(&trigger.contract_address).expect("valid address; qed"),
+        hex::encode(&trigger.data),
+        trigger.call_value,
+        trigger.call_token_id,
+        trigger.call_token_value
+    )
+}
+
+/// Fetch ABI of the contract from the node.
+fn fetch_abi(contract: &Address, matches: &ArgMatches) -> Result<Option<Abi>> {
+    let query = format!(r#"{{ account(address: "{}") {{ abi }} }}"#, contract);
+    let result = graphql_query(&query, matches)?;
+    match result["account"]["abi"].as_str() {
+        Some(json) => abi::parse_abi(json).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn print_logs(contract_abi: &Abi, logs: &serde_json::Value) {
+    for log in logs.as_array().into_iter().flatten() {
+        let topics: Vec<_> = log["topics"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|topic| hex::decode(topic.as_str()?).ok())
+            .collect();
+        let data = hex::decode(log["data"].as_str().unwrap_or_default()).unwrap_or_default();
+        match abi::decode_log(contract_abi, &topics, &data) {
+            Some(event) => println!("Event => {}", event),
+            None => println!(
+                "Log => topics: [{}], data: {}",
+                topics.iter().map(hex::encode).collect::<Vec<_>>().join(", "),
+                hex::encode(&data)
+            ),
+        }
+    }
+}
+
+fn logs(matches: &ArgMatches, global_matches: &ArgMatches) -> Result<()> {
+    let contract: Address = parse_address(matches.value_of("CONTRACT").expect("required; qed"))?;
+    let contract_abi = fetch_abi(&contract, global_matches)?.ok_or_else(|| custom_error("contract has no ABI"))?;
+
+    let mut filter = format!(r#"addresses: ["{}"]"#, contract);
+    if let Some(num) = matches.value_of("from-block") {
+        filter += &format!(", fromBlock: {}", num.parse::<i64>()?);
+    }
+    if let Some(num) = matches.value_of("to-block") {
+        filter += &format!(", toBlock: {}", num.parse::<i64>()?);
+    }
+    let query = format!(
+        "{{ logs(filter: {{ {} }}) {{ topics data transaction {{ hash }} }} }}",
+        filter
+    );
+    let result = graphql_query(&query, global_matches)?;
+
+    for log in result["logs"].as_array().into_iter().flatten() {
+        println!(
+            "Transaction => {}",
+            log["transaction"]["hash"].as_str().unwrap_or_default()
+        );
+        print_logs(&contract_abi, &serde_json::Value::Array(vec![log.clone()]));
+    }
+    Ok(())
+}
+
+fn set_energy_share(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let contract: Address = parse_address(matches.value_of("CONTRACT").expect("required; qed"))?;
+    let percent = matches.value_of("PERCENT").expect("required; qed");
+
+    let inner = contract_pb::UpdateSettingContract {
+        owner_address: from.as_bytes().into(),
+        contract_address: contract.as_bytes().into(),
+        consume_user_energy_percent: percent.parse()?,
+    };
+
+    Ok(inner.into())
+}
+
+fn set_energy_limit(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let contract: Address = parse_address(matches.value_of("CONTRACT").expect("required; qed"))?;
+    let limit = parse_amount(matches.value_of("LIMIT").expect("required; qed"))
+        .ok_or_else(|| custom_error("malformed LIMIT"))?;
+
+    let inner = contract_pb::UpdateEnergyLimitContract {
+        owner_address: from.as_bytes().into(),
+        contract_address: contract.as_bytes().into(),
+        origin_energy_limit: limit,
+    };
+
+    Ok(inner.into())
+}
+
+fn clear_abi(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let contract: Address = parse_address(matches.value_of("CONTRACT").expect("required; qed"))?;
+
+    let inner = contract_pb::ClearAbiContract {
+        owner_address: from.as_bytes().into(),
+        contract_address: contract.as_bytes().into(),
+    };
+
+    Ok(inner.into())
+}
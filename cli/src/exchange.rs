@@ -0,0 +1,92 @@
+use clap::ArgMatches;
+use keys::Address;
+use proto::chain::transaction::Contract;
+use proto::contract as contract_pb;
+
+use crate::util::{parse_address, parse_amount, parse_token_amount, parse_token_id};
+use crate::{custom_error, Result};
+
+pub fn main(matches: &ArgMatches) -> Result<Contract> {
+    match matches.subcommand() {
+        ("create", Some(arg_matches)) => create(arg_matches),
+        ("inject", Some(arg_matches)) => inject(arg_matches),
+        ("withdraw", Some(arg_matches)) => withdraw(arg_matches),
+        ("trade", Some(arg_matches)) => trade(arg_matches),
+        _ => unreachable!("checked; qed"),
+    }
+}
+
+fn create(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let first_token_id = parse_token_id(matches.value_of("FIRST_TOKEN").expect("required; qed"));
+    let first_token_balance = parse_token_amount(
+        matches.value_of("FIRST_AMOUNT").expect("required; qed"),
+        &first_token_id,
+    )?;
+    let second_token_id = parse_token_id(matches.value_of("SECOND_TOKEN").expect("required; qed"));
+    let second_token_balance = parse_token_amount(
+        matches.value_of("SECOND_AMOUNT").expect("required; qed"),
+        &second_token_id,
+    )?;
+
+    let inner = contract_pb::ExchangeCreateContract {
+        owner_address: from.as_bytes().into(),
+        first_token_id,
+        first_token_balance,
+        second_token_id,
+        second_token_balance,
+    };
+
+    Ok(inner.into())
+}
+
+fn inject(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let id = matches.value_of("ID").expect("required; qed");
+    let token_id = parse_token_id(matches.value_of("TOKEN").expect("required; qed"));
+    let quant = parse_token_amount(matches.value_of("AMOUNT").expect("required; qed"), &token_id)?;
+
+    let inner = contract_pb::ExchangeInjectContract {
+        owner_address: from.as_bytes().into(),
+        exchange_id: id.parse()?,
+        token_id,
+        quant,
+    };
+
+    Ok(inner.into())
+}
+
+fn withdraw(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let id = matches.value_of("ID").expect("required; qed");
+    let token_id = parse_token_id(matches.value_of("TOKEN").expect("required; qed"));
+    let quant = parse_token_amount(matches.value_of("AMOUNT").expect("required; qed"), &token_id)?;
+
+    let inner = contract_pb::ExchangeWithdrawContract {
+        owner_address: from.as_bytes().into(),
+        exchange_id: id.parse()?,
+        token_id,
+        quant,
+    };
+
+    Ok(inner.into())
+}
+
+fn trade(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let id = matches.value_of("ID").expect("required; qed");
+    let token_id = parse_token_id(matches.value_of("TOKEN").expect("required; qed"));
+    let quant = parse_token_amount(matches.value_of("AMOUNT").expect("required; qed"), &token_id)?;
+    let expected = parse_amount(matches.value_of("expected").expect("has default; qed"))
+        .ok_or_else(|| custom_error("malformed expected amount"))?;
+
+    let inner = contract_pb::ExchangeTransactionContract {
+        owner_address: from.as_bytes().into(),
+        exchange_id: id.parse()?,
+        token_id,
+        quant,
+        expected,
+    };
+
+    Ok(inner.into())
+}
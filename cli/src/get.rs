@@ -0,0 +1,301 @@
+//! Read-only queries of chain state, via GraphQL.
+
+use clap::ArgMatches;
+use keys::Address;
+use serde_json::Value;
+
+use crate::util::parse_address;
+use crate::{custom_error, graphql_query, Result};
+
+pub fn main(matches: &ArgMatches, global_matches: &ArgMatches) -> Result<()> {
+    let (name, arg_matches) = matches.subcommand();
+    let arg_matches = arg_matches.expect("checked; qed");
+    let (query, path) = match name {
+        "account" => account(arg_matches)?,
+        "block" => block(arg_matches)?,
+        "transaction" => transaction(arg_matches)?,
+        "receipt" => receipt(arg_matches)?,
+        "witness" => witness(arg_matches)?,
+        "asset" => asset(arg_matches)?,
+        "proposal" => proposal(arg_matches)?,
+        "chain-parameters" => chain_parameters(),
+        "reward" => reward(arg_matches)?,
+        _ => unreachable!("checked; qed"),
+    };
+
+    let mut data = graphql_query(&query, global_matches)?;
+    let value = path.iter().fold(&mut data, |value, key| &mut value[*key]).take();
+    if value.is_null() {
+        return Err(custom_error("not found"));
+    }
+
+    if arg_matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else if name == "chain-parameters" {
+        print_parameters(&value["parameters"]);
+        println!(
+            "Next Maintenance Time => {}",
+            value["nextMaintenanceTime"].as_str().unwrap_or_default()
+        );
+    } else if name == "proposal" && arg_matches.is_present("pending") {
+        print_pending_parameters(&value["pendingParameters"]);
+        println!(
+            "Next Maintenance Time => {}",
+            value["nextMaintenanceTime"].as_str().unwrap_or_default()
+        );
+    } else if let Some(values) = value.as_array() {
+        for value in values {
+            print_object(value, 0);
+            println!();
+        }
+    } else {
+        print_object(&value, 0);
+    }
+    Ok(())
+}
+
+/// GraphQL query and the path to the result object in `data`.
+type Query = (String, Vec<&'static str>);
+
+fn address_of(matches: &ArgMatches) -> Result<Address> {
+    parse_address(matches.value_of("ADDRESS").expect("required; qed"))
+}
+
+fn hash_of(matches: &ArgMatches) -> Result<String> {
+    let hash = matches.value_of("HASH").expect("required; qed");
+    let hash = hash.trim_start_matches("0x");
+    if hash.len() != 64 || hex::decode(hash).is_err() {
+        return Err(custom_error("malformed hash"));
+    }
+    Ok(hash.to_owned())
+}
+
+fn account(matches: &ArgMatches) -> Result<Query> {
+    let query = format!(
+        r#"{{ account(address: "{}") {{
+            address type name balance power frozenBalance allowance reward creationTime
+            permissions {{ id threshold keys {{ address weight }} }}
+        }} }}"#,
+        address_of(matches)?
+    );
+    Ok((query, vec!["account"]))
+}
+
+fn block(matches: &ArgMatches) -> Result<Query> {
+    let selector = match matches.value_of("BLOCK") {
+        None => "".to_owned(),
+        Some(hash) if hash.trim_start_matches("0x").len() == 64 => {
+            format!(r#"(hash: "{}")"#, hash.trim_start_matches("0x"))
+        }
+        Some(number) => format!(r#"(number: "{}")"#, number.parse::<i64>()?),
+    };
+    let query = format!(
+        r#"{{ block{} {{
+            number hash timestamp witness {{ address }} parent {{ hash }} transactionsRoot transactionCount
+            transactions {{ hash }}
+        }} }}"#,
+        selector
+    );
+    Ok((query, vec!["block"]))
+}
+
+fn transaction(matches: &ArgMatches) -> Result<Query> {
+    let query = format!(
+        r#"{{ transaction(hash: "{}") {{
+            hash block {{ number }} index contractType permissionId expiration
+            from {{ address }} to {{ address }} value memo vmStatus
+        }} }}"#,
+        hash_of(matches)?
+    );
+    Ok((query, vec!["transaction"]))
+}
+
+fn receipt(matches: &ArgMatches) -> Result<Query> {
+    let query = format!(
+        r#"{{ transaction(hash: "{}") {{ result {{
+            success fee energyUsed energyUsage originEnergyUsage energyFee bandwidthUsage bandwidthFee
+            contractFee multisigFee vmStatus vmMessage vmResult createdContract
+        }} }} }}"#,
+        hash_of(matches)?
+    );
+    Ok((query, vec!["transaction", "result"]))
+}
+
+fn witness(matches: &ArgMatches) -> Result<Query> {
+    let query = format!(
+        r#"{{ witness(address: "{}") {{
+            account {{ address name }} url voteCount brokerage totalProduced totalMissed
+        }} }}"#,
+        address_of(matches)?
+    );
+    Ok((query, vec!["witness"]))
+}
+
+fn asset(matches: &ArgMatches) -> Result<Query> {
+    let selector = match matches.value_of("ASSET") {
+        Some(id) if id.chars().all(|c| c.is_ascii_digit()) => format!("id: {}", id),
+        Some(issuer) => format!(r#"issuer: "{}""#, parse_address(issuer)?),
+        None => unreachable!("required; qed"),
+    };
+    let query = format!(
+        r#"{{ asset({}) {{ id name symbol description url totalSupply decimals owner }} }}"#,
+        selector
+    );
+    Ok((query, vec!["asset"]))
+}
+
+fn proposal(matches: &ArgMatches) -> Result<Query> {
+    const FIELDS: &str = "id proposer state creationTime expirationTime maintenanceTime parameters { id key value } \
+                          approvals approvalCount requiredApprovalCount";
+
+    if matches.is_present("pending") {
+        let query = "{ chain { pendingParameters { id key value currentValue proposalId } nextMaintenanceTime } }";
+        return Ok((query.to_owned(), vec!["chain"]));
+    }
+    if let Some(id) = matches.value_of("ID") {
+        let id: i64 = id.parse()?;
+        let query = format!("{{ proposal(id: {}) {{ {} }} }}", id, FIELDS);
+        return Ok((query, vec!["proposal"]));
+    }
+    let selector = match matches.value_of("state") {
+        Some(state) => format!("(state: {})", state.to_uppercase()),
+        None => "".to_owned(),
+    };
+    let query = format!("{{ proposals{} {{ {} }} }}", selector, FIELDS);
+    Ok((query, vec!["proposals"]))
+}
+
+fn chain_parameters() -> Query {
+    let query = "{ chain { parameters { id key value } nextMaintenanceTime } }".to_owned();
+    (query, vec!["chain"])
+}
+
+fn reward(matches: &ArgMatches) -> Result<Query> {
+    let query = format!(
+        r#"{{ account(address: "{}") {{ address allowance reward }} }}"#,
+        address_of(matches)?
+    );
+    Ok((query, vec!["account"]))
+}
+
+/// Print chain parameters as an aligned table.
+fn print_parameters(params: &Value) {
+    let params = params.as_array().map(|params| &params[..]).unwrap_or_default();
+    let width = params
+        .iter()
+        .filter_map(|param| param["key"].as_str())
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    println!("{:>4}  {:<width$}  Value", "ID", "Key", width = width);
+    for param in params {
+        println!(
+            "{:>4}  {:<width$}  {}",
+            param["id"],
+            param["key"].as_str().unwrap_or_default(),
+            param["value"],
+            width = width
+        );
+    }
+}
+
+/// Print chain parameters to be changed as an aligned table.
+fn print_pending_parameters(params: &Value) {
+    let params = params.as_array().map(|params| &params[..]).unwrap_or_default();
+    if params.is_empty() {
+        println!("No chain parameter to be changed at next maintenance");
+        return;
+    }
+    let width = params
+        .iter()
+        .filter_map(|param| param["key"].as_str())
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:>4}  {:<width$}  {:>20}  {:>20}  Proposal",
+        "ID",
+        "Key",
+        "Current",
+        "New",
+        width = width
+    );
+    for param in params {
+        println!(
+            "{:>4}  {:<width$}  {:>20}  {:>20}  #{}",
+            param["id"],
+            param["key"].as_str().unwrap_or_default(),
+            param["currentValue"],
+            param["value"],
+            param["proposalId"],
+            width = width
+        );
+    }
+}
+
+/// Print a JSON object as `Key => Value` lines, nested objects are indented.
+fn print_object(value: &Value, indent: usize) {
+    let obj = match value.as_object() {
+        Some(obj) => obj,
+        None => {
+            println!("{:indent$}{}", "", format_scalar(value), indent = indent);
+            return;
+        }
+    };
+    for (key, value) in obj {
+        let key = title_case(key);
+        match value {
+            Value::Object(_) => {
+                println!("{:indent$}{} =>", "", key, indent = indent);
+                print_object(value, indent + 2);
+            }
+            Value::Array(values) if values.iter().any(Value::is_object) => {
+                println!("{:indent$}{} =>", "", key, indent = indent);
+                for (i, value) in values.iter().enumerate() {
+                    println!("{:indent$}#{}", "", i, indent = indent + 2);
+                    print_object(value, indent + 4);
+                }
+            }
+            Value::Array(values) => {
+                let values: Vec<_> = values.iter().map(format_scalar).collect();
+                println!("{:indent$}{} => [{}]", "", key, values.join(", "), indent = indent);
+            }
+            _ => println!("{:indent$}{} => {}", "", key, format_scalar(value), indent = indent),
+        }
+    }
+}
+
+fn format_scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_owned(),
+        _ => value.to_string(),
+    }
+}
+
+/// `totalProduced` => `Total Produced`.
+fn title_case(key: &str) -> String {
+    let mut title = String::with_capacity(key.len() + 4);
+    for (i, c) in key.chars().enumerate() {
+        if i == 0 {
+            title.extend(c.to_uppercase());
+        } else {
+            if c.is_ascii_uppercase() {
+                title.push(' ');
+            }
+            title.push(c);
+        }
+    }
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(title_case("totalProduced"), "Total Produced");
+        assert_eq!(title_case("id"), "Id");
+    }
+}
@@ -1,17 +1,29 @@
 use chrono::Utc;
 use clap::ArgMatches;
-use crypto::sha256;
-use keys::{Address, KeyPair};
-use prost::Message;
+use itertools::Itertools;
+use keys::Address;
 use proto::chain::transaction::{Contract, Raw as TransactionRaw};
 use proto::contract as contract_pb;
 
+mod abi;
 mod account;
+mod asset;
+mod contract;
+mod exchange;
+mod get;
+mod market;
 mod proposal;
 mod system;
+mod transaction;
 mod util;
+mod wallet;
 mod witness;
 
+use self::transaction::TransactionFile;
+
+/// Default expiration of transactions, in seconds.
+const DEFAULT_EXPIRATION: i64 = 60;
+
 pub(crate) type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 pub(crate) fn custom_error(msg: &str) -> Box<dyn std::error::Error> {
@@ -23,26 +35,29 @@ fn main() -> Result<()> {
     let matches = clap::App::from_yaml(yaml).get_matches();
 
     let cntr = match matches.subcommand() {
+        ("sign", Some(arg_matches)) => return transaction::sign(arg_matches, &matches),
+        ("sign-status", Some(arg_matches)) => return transaction::sign_status(arg_matches, &matches),
+        ("broadcast", Some(arg_matches)) => return transaction::broadcast(arg_matches, &matches),
+        ("get", Some(arg_matches)) => return get::main(arg_matches, &matches),
+        ("wallet", Some(arg_matches)) => return wallet::main(arg_matches),
         ("transfer", Some(arg_matches)) => transfer(arg_matches),
         ("account", Some(arg_matches)) => account::main(arg_matches),
         ("witness", Some(arg_matches)) => witness::main(arg_matches),
         ("system", Some(arg_matches)) => system::main(arg_matches),
-        ("proposal", Some(arg_matches)) => proposal::main(arg_matches).ok(),
-        // TODO:
-        // ("exchange", Some(arg_matches)) => exchange::main(arg_matches).ok(),
-        // ("market", Some(arg_matches)) => market::main(arg_matches).ok(),
-
-        // commands::transfer::main(arg_matches),
-        // ("list", Some(arg_matches)) => commands::list::main(arg_matches),
-        _ => unimplemented!(),
+        ("proposal", Some(arg_matches)) => Some(proposal::main(arg_matches)?),
+        ("asset", Some(arg_matches)) => Some(asset::main(arg_matches)?),
+        ("contract", Some(arg_matches)) => return contract::main(arg_matches, &matches),
+        ("exchange", Some(arg_matches)) => Some(exchange::main(arg_matches)?),
+        ("market", Some(arg_matches)) => Some(market::main(arg_matches)?),
+        _ => unreachable!("in cli.yml; qed"),
     };
 
-    pack_and_send(cntr.expect("illegal contract"), &matches)
+    pack_and_send(cntr.expect("illegal contract"), 0, &matches)
 }
 
 fn transfer(matches: &ArgMatches) -> Option<Contract> {
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
-    let to: Address = matches.value_of("RECIPIENT")?.parse().ok()?;
+    let from: Address = util::parse_address(matches.value_of("SENDER")?).ok()?;
+    let to: Address = util::parse_address(matches.value_of("RECIPIENT")?).ok()?;
     let amount = util::parse_amount_with_currency(matches.value_of("AMOUNT")?, "TRX", 6)?;
 
     let transfer = contract_pb::TransferContract {
@@ -54,42 +69,74 @@ fn transfer(matches: &ArgMatches) -> Option<Contract> {
     Some(transfer.into())
 }
 
-fn pack_and_send(cntr: Contract, matches: &ArgMatches) -> Result<()> {
-    let ref_block_hash = get_ref_block_hash(matches)?;
+/// Pack the contract into a transaction, sign and broadcast. `default_fee_limit` is used if `--fee-limit` is not given.
+pub(crate) fn pack_and_send(mut cntr: Contract, default_fee_limit: i64, matches: &ArgMatches) -> Result<()> {
+    let (ref_block_hash, ref_block_timestamp) = get_ref_block(matches)?;
+
+    if let Some(permission_id) = matches.value_of("permission-id") {
+        cntr.permission_id = permission_id.parse()?;
+    }
+
+    let expiration = match matches.value_of("expiration") {
+        Some(secs) => secs.parse::<i64>()?,
+        None => DEFAULT_EXPIRATION,
+    };
+    let fee_limit = match matches.value_of("fee-limit") {
+        Some(limit) => {
+            util::parse_amount_with_currency(limit, "TRX", 6).ok_or_else(|| custom_error("malformed fee limit"))?
+        }
+        None => default_fee_limit,
+    };
 
     let raw = TransactionRaw {
         contract: Some(cntr),
         ref_block_bytes: ref_block_hash[6..8].to_vec(),
         ref_block_hash: ref_block_hash[8..16].to_vec(),
-        expiration: Utc::now().timestamp_millis() + 60_000,
+        // NOTE: The chain clock might be ahead of wall clock, in the instant-seal dev mode.
+        expiration: Utc::now().timestamp_millis().max(ref_block_timestamp) + expiration * 1_000,
+        data: matches.value_of("memo").unwrap_or_default().into(),
+        fee_limit,
         ..Default::default()
     };
-    let mut buf = Vec::with_capacity(255);
-    raw.encode(&mut buf)?;
+    let mut txn = TransactionFile::from_raw(&raw)?;
 
-    let hex_priv_key = matches.value_of("private-key").unwrap();
-    let kp = KeyPair::from_private(hex_priv_key.parse()?)?;
+    if matches.is_present("skip-sign") {
+        return txn.save(matches.value_of("output"));
+    }
 
-    let sig = kp.private().sign(&buf)?;
+    let kp = transaction::signer_of(matches)?;
+    txn.sign(&kp)?;
 
-    println!("RAW => {}", hex::encode(&buf));
-    println!("TXN Hash => {:?}", sha256(&buf));
-    println!("SIG => {}", hex::encode(sig.as_bytes()));
+    println!("RAW => {}", txn.raw_data_hex);
+    println!("TXN Hash => {}", txn.txid);
+    println!("SIG => {}", txn.signature[0]);
 
-    if !matches.is_present("dont-broadcast") {
-        send_raw_transaction(&hex::encode(&buf), &hex::encode(sig.as_bytes()), matches)?;
+    if let Some(path) = matches.value_of("output") {
+        txn.save(Some(path))?;
+    }
+    if matches.is_present("dont-broadcast") {
+        return Ok(());
+    }
+    if matches.is_present("permission-id") {
+        // multisig, might require more signatures
+        if !transaction::broadcast_if_sufficient(&txn, matches)? && !matches.is_present("output") {
+            txn.save(None)?;
+        }
+    } else {
+        send_raw_transaction(&txn.raw_data_hex, &txn.signature, matches)?;
     }
 
     Ok(())
 }
 
-fn send_raw_transaction(raw: &str, signature: &str, matches: &ArgMatches) -> Result<()> {
+pub(crate) fn send_raw_transaction(raw: &str, signatures: &[String], matches: &ArgMatches) -> Result<()> {
     let rpc_url = matches.value_of("rpc-url").expect("has default; qed");
 
     let client = reqwest::blocking::Client::new();
-    let mutation = r#"{ "query": "mutation { txn: sendRawTransaction(rawData: \"RAW\", signatures: [\"SIG\"]) }" }"#;
+    let mutation = r#"{ "query": "mutation { txn: sendRawTransaction(rawData: \"RAW\", signatures: [SIGS]) }" }"#;
 
-    let mutation = mutation.replace("RAW", raw).replace("SIG", signature);
+    let sigs = signatures.iter().map(|sig| format!(r#"\"{}\""#, sig)).join(", ");
+    let mutation = mutation.replace("RAW", raw).replace("SIGS", &sigs);
     let resp = client
         .post(rpc_url)
         .header("User-Agent", "Opentron Cli/0.1.0")
@@ -110,14 +157,36 @@ fn send_raw_transaction(raw: &str, signature: &str, matches: &ArgMatches) -> Res
     Ok(())
 }
 
-fn get_ref_block_hash(matches: &ArgMatches) -> Result<Vec<u8>> {
+/// Run a GraphQL query, returns the `data` field of response.
+pub(crate) fn graphql_query(query: &str, matches: &ArgMatches) -> Result<serde_json::Value> {
+    let rpc_url = matches.value_of("rpc-url").expect("has default; qed");
+
+    let client = reqwest::blocking::Client::new();
+    let body = serde_json::json!({ "query": query });
+    let resp = client
+        .post(rpc_url)
+        .header("User-Agent", "Opentron Cli/0.1.0")
+        .header("Accept", "application/json")
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()?;
+
+    let mut payload: serde_json::Value = resp.json()?;
+    if !payload["errors"].is_null() {
+        return Err(custom_error(&format!("GraphQL error: {}", payload["errors"])));
+    }
+    Ok(payload["data"].take())
+}
+
+/// Hash and timestamp of the latest block.
+fn get_ref_block(matches: &ArgMatches) -> Result<(Vec<u8>, i64)> {
     let rpc_url = matches.value_of("rpc-url").expect("has default; qed");
 
     let client = reqwest::blocking::Client::new();
     // "operationName":null,
     // "variables":{},
     let query = r#"{
-        "query":"{ refBlock: block { hash } }"
+        "query":"{ refBlock: block { hash timestamp } }"
     }"#;
     let resp = client
         .post(rpc_url)
@@ -131,6 +200,7 @@ fn get_ref_block_hash(matches: &ArgMatches) -> Result<Vec<u8>> {
 
     // println!("{}", serde_json::to_string_pretty(&payload)?);
     let hash = &payload["data"]["refBlock"]["hash"];
-    println!("Ref Block Hash => {}", hash.as_str().unwrap());
-    hex::decode(hash.as_str().unwrap()).map_err(From::from)
+    eprintln!("Ref Block Hash => {}", hash.as_str().unwrap());
+    let timestamp = payload["data"]["refBlock"]["timestamp"].as_i64().unwrap_or_default();
+    Ok((hex::decode(hash.as_str().unwrap())?, timestamp))
 }
@@ -0,0 +1,46 @@
+use clap::ArgMatches;
+use keys::Address;
+use proto::chain::transaction::Contract;
+use proto::contract as contract_pb;
+
+use crate::util::{parse_address, parse_token_amount, parse_token_id};
+use crate::Result;
+
+pub fn main(matches: &ArgMatches) -> Result<Contract> {
+    match matches.subcommand() {
+        ("sell", Some(arg_matches)) => sell(arg_matches),
+        ("cancel", Some(arg_matches)) => cancel(arg_matches),
+        _ => unreachable!("checked; qed"),
+    }
+}
+
+fn sell(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let sell_token_id = parse_token_id(matches.value_of("SELL_TOKEN").expect("required; qed"));
+    let sell_token_quantity =
+        parse_token_amount(matches.value_of("SELL_AMOUNT").expect("required; qed"), &sell_token_id)?;
+    let buy_token_id = parse_token_id(matches.value_of("BUY_TOKEN").expect("required; qed"));
+    let buy_token_quantity = parse_token_amount(matches.value_of("BUY_AMOUNT").expect("required; qed"), &buy_token_id)?;
+
+    let inner = contract_pb::MarketSellAssetContract {
+        owner_address: from.as_bytes().into(),
+        sell_token_id: sell_token_id.into_bytes(),
+        sell_token_quantity,
+        buy_token_id: buy_token_id.into_bytes(),
+        buy_token_quantity,
+    };
+
+    Ok(inner.into())
+}
+
+fn cancel(matches: &ArgMatches) -> Result<Contract> {
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
+    let order_id = hex::decode(matches.value_of("ORDER_ID").expect("required; qed"))?;
+
+    let inner = contract_pb::MarketCancelOrderContract {
+        owner_address: from.as_bytes().into(),
+        order_id,
+    };
+
+    Ok(inner.into())
+}
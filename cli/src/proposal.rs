@@ -4,6 +4,7 @@ use proto::chain::transaction::Contract;
 use proto::contract as contract_pb;
 use std::collections::HashMap;
 
+use crate::util::parse_address;
 use crate::{custom_error, Result};
 
 pub fn main(matches: &ArgMatches) -> Result<Contract> {
@@ -16,7 +17,7 @@ pub fn main(matches: &ArgMatches) -> Result<Contract> {
 }
 
 fn create(matches: &ArgMatches) -> Result<Contract> {
-    let from: Address = matches.value_of("SENDER").expect("required; qed").parse()?;
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
     let params = matches
         .values_of("PARAMS")
         .expect("required in cli.yml; qed")
@@ -37,7 +38,7 @@ fn create(matches: &ArgMatches) -> Result<Contract> {
 }
 
 fn approve(matches: &ArgMatches) -> Result<Contract> {
-    let from: Address = matches.value_of("SENDER").expect("required; qed").parse()?;
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
     let id = matches.value_of("ID").expect("required; qed");
 
     let is_approval = matches.value_of("approve").expect("required; qed") == "yes";
@@ -52,7 +53,7 @@ fn approve(matches: &ArgMatches) -> Result<Contract> {
 }
 
 fn delete(matches: &ArgMatches) -> Result<Contract> {
-    let from: Address = matches.value_of("SENDER").expect("required; qed").parse()?;
+    let from: Address = parse_address(matches.value_of("SENDER").expect("required; qed"))?;
     let id = matches.value_of("ID").expect("required; qed");
 
     let inner = contract_pb::ProposalDeleteContract {
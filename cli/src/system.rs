@@ -4,6 +4,8 @@ use keys::Address;
 use proto::chain::transaction::Contract;
 use proto::contract as contract_pb;
 
+use crate::util::parse_address;
+
 pub fn main(matches: &ArgMatches) -> Option<Contract> {
     match matches.subcommand() {
         ("freeze", Some(arg_matches)) => freeze(arg_matches),
@@ -17,8 +19,8 @@ pub fn main(matches: &ArgMatches) -> Option<Contract> {
 fn freeze(matches: &ArgMatches) -> Option<Contract> {
     use proto::common::ResourceCode as ResourceType;
 
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
-    let to: Address = matches.value_of("RECIPIENT")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
+    let to: Address = parse_address(matches.value_of("RECIPIENT")?).ok()?;
     let amount = crate::util::parse_amount_with_currency(matches.value_of("AMOUNT")?, "TRX", 6)?;
 
     let resource_type = match matches.value_of("type") {
@@ -41,8 +43,8 @@ fn freeze(matches: &ArgMatches) -> Option<Contract> {
 fn unfreeze(matches: &ArgMatches) -> Option<Contract> {
     use proto::common::ResourceCode as ResourceType;
 
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
-    let to: Address = matches.value_of("RECIPIENT")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
+    let to: Address = parse_address(matches.value_of("RECIPIENT")?).ok()?;
 
     let resource_type = match matches.value_of("type") {
         Some("bandwidth") => ResourceType::Bandwidth,
@@ -62,7 +64,7 @@ fn unfreeze(matches: &ArgMatches) -> Option<Contract> {
 fn vote(matches: &ArgMatches) -> Option<Contract> {
     use proto::common::Vote;
 
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
     let votes = match matches.values_of("VOTES") {
         Some(vote_args) => vote_args
             .chunks(2)
@@ -70,7 +72,7 @@ fn vote(matches: &ArgMatches) -> Option<Contract> {
             .map(|chunk| {
                 if let &[addr, count] = &chunk.collect::<Vec<_>>()[..] {
                     Ok(Vote {
-                        vote_address: addr.parse::<Address>()?.as_bytes().to_owned(),
+                        vote_address: parse_address(addr)?.as_bytes().to_owned(),
                         vote_count: crate::util::parse_amount(count).expect("parse amount failed"),
                     })
                 } else {
@@ -92,7 +94,7 @@ fn vote(matches: &ArgMatches) -> Option<Contract> {
 }
 
 fn withdraw(matches: &ArgMatches) -> Option<Contract> {
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
 
     let inner = contract_pb::WithdrawBalanceContract {
         owner_address: from.as_bytes().into(),
@@ -0,0 +1,245 @@
+//! Transaction files, for offline signing.
+//!
+//! The JSON format is compatible with TronWeb:
+//!
+//! ```json
+//! { "txID": "...", "raw_data_hex": "...", "signature": ["..."] }
+//! ```
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{self, Read};
+
+use clap::ArgMatches;
+use crypto::sha256;
+use keys::{Address, KeyPair, Public, Signature};
+use prost::Message;
+use proto::chain::transaction::Raw as TransactionRaw;
+use serde::{Deserialize, Serialize};
+
+use crate::util::parse_address;
+use crate::{custom_error, graphql_query, wallet, Result};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TransactionFile {
+    #[serde(rename = "txID")]
+    pub txid: String,
+    pub raw_data_hex: String,
+    #[serde(default)]
+    pub signature: Vec<String>,
+}
+
+impl TransactionFile {
+    pub fn from_raw(raw: &TransactionRaw) -> Result<Self> {
+        let mut buf = Vec::with_capacity(255);
+        raw.encode(&mut buf)?;
+        Ok(TransactionFile {
+            txid: hex::encode(sha256(&buf).as_bytes()),
+            raw_data_hex: hex::encode(&buf),
+            signature: vec![],
+        })
+    }
+
+    /// Load from a file, `-` for stdin.
+    pub fn load(path: &str) -> Result<Self> {
+        let content = if path == "-" {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            content
+        } else {
+            fs::read_to_string(path)?
+        };
+        let txn: TransactionFile = serde_json::from_str(&content)?;
+
+        let raw = hex::decode(&txn.raw_data_hex)?;
+        if hex::encode(sha256(&raw).as_bytes()) != txn.txid {
+            return Err(custom_error("txID mismatches raw_data_hex"));
+        }
+        Ok(txn)
+    }
+
+    /// Save to the file, or print to stdout if None.
+    pub fn save(&self, path: Option<&str>) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        match path {
+            Some(path) => {
+                fs::write(path, content)?;
+                println!("Transaction => {}", path);
+            }
+            None => println!("{}", content),
+        }
+        Ok(())
+    }
+
+    pub fn raw_data(&self) -> Result<Vec<u8>> {
+        hex::decode(&self.raw_data_hex).map_err(From::from)
+    }
+
+    /// Append a signature, refuses to sign twice with the same key.
+    pub fn sign(&mut self, kp: &KeyPair) -> Result<()> {
+        if self.signers()?.contains(&kp.address()) {
+            return Err(custom_error("already signed by this key"));
+        }
+        let sig = kp.private().sign(&self.raw_data()?)?;
+        self.signature.push(hex::encode(sig.as_bytes()));
+        Ok(())
+    }
+
+    /// Addresses recovered from signatures.
+    pub fn signers(&self) -> Result<Vec<Address>> {
+        let raw = self.raw_data()?;
+        self.signature
+            .iter()
+            .map(|sig| {
+                let sig: Signature = sig.parse()?;
+                Ok(Address::from_public(&Public::recover(&raw, &sig)?))
+            })
+            .collect()
+    }
+
+    /// Fetch the permission of the owner account, and check collected signatures against it.
+    pub fn sign_status(&self, matches: &ArgMatches) -> Result<SignStatus> {
+        let raw = TransactionRaw::decode(&self.raw_data()?[..])?;
+        let cntr = raw
+            .contract
+            .ok_or_else(|| custom_error("transaction without contract"))?;
+        let owner = cntr
+            .owner_address()
+            .and_then(|addr| Address::try_from(addr).ok())
+            .ok_or_else(|| custom_error("unknown owner address of contract"))?;
+
+        let query = format!(
+            r#"{{ account(address: "{}") {{ permissions {{ id threshold keys {{ address weight }} }} }} }}"#,
+            owner
+        );
+        let data = graphql_query(&query, matches)?;
+        let perm = data["account"]["permissions"]
+            .as_array()
+            .and_then(|perms| {
+                perms
+                    .iter()
+                    .find(|perm| perm["id"].as_i64() == Some(cntr.permission_id as i64))
+            })
+            .ok_or_else(|| custom_error("permission id not found in owner account"))?;
+
+        let signers = self
+            .signers()?
+            .into_iter()
+            .map(|signer| {
+                let weight = perm["keys"]
+                    .as_array()
+                    .and_then(|keys| {
+                        keys.iter()
+                            .find(|key| key["address"].as_str() == Some(&signer.to_string()))
+                    })
+                    .and_then(|key| key["weight"].as_i64())
+                    .unwrap_or(0);
+                (signer, weight)
+            })
+            .collect();
+
+        Ok(SignStatus {
+            owner,
+            permission_id: cntr.permission_id,
+            threshold: perm["threshold"].as_i64().unwrap_or_default(),
+            signers,
+        })
+    }
+}
+
+/// Collected signature weight against the permission threshold.
+pub struct SignStatus {
+    pub owner: Address,
+    pub permission_id: i32,
+    pub threshold: i64,
+    /// Signers and their weights, 0 if not in the permission keys.
+    pub signers: Vec<(Address, i64)>,
+}
+
+impl SignStatus {
+    pub fn weight(&self) -> i64 {
+        self.signers.iter().map(|(_, weight)| weight).sum()
+    }
+
+    pub fn is_sufficient(&self) -> bool {
+        self.weight() >= self.threshold
+    }
+
+    pub fn print(&self) {
+        println!("Owner => {}", self.owner);
+        println!("Permission ID => {}", self.permission_id);
+        for (signer, weight) in &self.signers {
+            println!("  Signed by {} => weight {}", signer, weight);
+        }
+        println!("Weight => {}/{}", self.weight(), self.threshold);
+    }
+}
+
+/// The key pair used for signing, from `--private-key`, or unlocked from the wallet store by `--account`.
+pub fn signer_of(matches: &ArgMatches) -> Result<KeyPair> {
+    let account = matches.value_of("account").map(parse_address).transpose()?;
+
+    let kp = match (matches.value_of("private-key"), account) {
+        (Some(hex_priv_key), _) => KeyPair::from_private(hex_priv_key.parse()?)?,
+        (None, Some(account)) => return wallet::unlock(&account),
+        (None, None) => {
+            return Err(custom_error(
+                "--account or --private-key is required for signing, or use --skip-sign",
+            ))
+        }
+    };
+    if let Some(account) = account {
+        if kp.address() != account {
+            return Err(custom_error("private key mismatches --account"));
+        }
+    }
+    Ok(kp)
+}
+
+/// The `sign` subcommand.
+pub fn sign(matches: &ArgMatches, global_matches: &ArgMatches) -> Result<()> {
+    let mut txn = TransactionFile::load(matches.value_of("FILE").expect("required; qed"))?;
+    let kp = signer_of(global_matches)?;
+    txn.sign(&kp)?;
+    txn.save(global_matches.value_of("output"))?;
+
+    if matches.is_present("broadcast") {
+        broadcast_if_sufficient(&txn, global_matches)?;
+    }
+    Ok(())
+}
+
+/// The `sign-status` subcommand.
+pub fn sign_status(matches: &ArgMatches, global_matches: &ArgMatches) -> Result<()> {
+    let txn = TransactionFile::load(matches.value_of("FILE").expect("required; qed"))?;
+    let status = txn.sign_status(global_matches)?;
+    status.print();
+    if status.is_sufficient() {
+        println!("Threshold is met, ready for broadcast");
+    }
+    Ok(())
+}
+
+/// The `broadcast` subcommand.
+pub fn broadcast(matches: &ArgMatches, global_matches: &ArgMatches) -> Result<()> {
+    let txn = TransactionFile::load(matches.value_of("FILE").expect("required; qed"))?;
+    if txn.signature.is_empty() {
+        return Err(custom_error("transaction is not signed"));
+    }
+    if !broadcast_if_sufficient(&txn, global_matches)? {
+        return Err(custom_error("insufficient signature weight"));
+    }
+    Ok(())
+}
+
+/// Broadcast the transaction if signatures meet the permission threshold. Returns whether it is broadcast.
+pub fn broadcast_if_sufficient(txn: &TransactionFile, matches: &ArgMatches) -> Result<bool> {
+    let status = txn.sign_status(matches)?;
+    if !status.is_sufficient() {
+        status.print();
+        println!("! More signatures are required before broadcast");
+        return Ok(false);
+    }
+    crate::send_raw_transaction(&txn.raw_data_hex, &txn.signature, matches)?;
+    Ok(true)
+}
@@ -1,3 +1,16 @@
+use keys::Address;
+
+use crate::wallet;
+use crate::{custom_error, Result};
+
+/// Address in base58check or hex, or an alias in the wallet store.
+pub fn parse_address(address: &str) -> Result<Address> {
+    match address.parse() {
+        Ok(addr) => Ok(addr),
+        Err(e) => wallet::resolve_alias(address).ok_or_else(|| e.into()),
+    }
+}
+
 pub fn parse_amount_with_currency(amount: &str, symbol: &str, precision: u32) -> Option<i64> {
     if amount.ends_with(symbol) {
         amount
@@ -14,3 +27,21 @@ pub fn parse_amount_with_currency(amount: &str, symbol: &str, precision: u32) ->
 pub fn parse_amount(amount: &str) -> Option<i64> {
     amount.replace("_", "").parse().ok()
 }
+
+/// Token id of TRC10, `_` for TRX.
+pub fn parse_token_id(token: &str) -> String {
+    match token {
+        "TRX" | "_" => "_".into(),
+        _ => token.into(),
+    }
+}
+
+/// Amount of TRX or TRC10 token, TRX amount can be suffixed with the symbol.
+pub fn parse_token_amount(amount: &str, token_id: &str) -> Result<i64> {
+    let amount = if token_id == "_" {
+        parse_amount_with_currency(amount, "TRX", 6)
+    } else {
+        parse_amount(amount)
+    };
+    amount.ok_or_else(|| custom_error("malformed token amount"))
+}
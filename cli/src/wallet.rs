@@ -0,0 +1,330 @@
+//! Local wallet store, of encrypted keystore files.
+//!
+//! Keystore files follow the Web3 Secret Storage Definition (version 3), scrypt + aes-128-ctr,
+//! with addresses in base58check and an optional alias.
+
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use aes::cipher::{NewCipher, StreamCipher};
+use aes::Aes128Ctr;
+use clap::ArgMatches;
+use crypto::keccak256;
+use keys::{Address, KeyPair, Private};
+use serde::{Deserialize, Serialize};
+
+use crate::{custom_error, Result};
+
+/// scrypt parameters, the same as the "standard" of geth.
+const SCRYPT_LOG_N: u8 = 18;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const DKLEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Keystore {
+    pub version: u32,
+    pub id: String,
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    pub crypto: CryptoJson,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CryptoJson {
+    pub cipher: String,
+    pub ciphertext: String,
+    pub cipherparams: CipherParams,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KdfParams {
+    pub dklen: usize,
+    pub n: u64,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+impl Keystore {
+    pub fn encrypt(private: &Private, password: &str, alias: Option<String>) -> Result<Self> {
+        Self::encrypt_with_log_n(private, password, alias, SCRYPT_LOG_N)
+    }
+
+    fn encrypt_with_log_n(private: &Private, password: &str, alias: Option<String>, log_n: u8) -> Result<Self> {
+        let salt: [u8; 32] = rand::random();
+        let iv: [u8; 16] = rand::random();
+        let id: [u8; 16] = rand::random();
+
+        let kdfparams = KdfParams {
+            dklen: DKLEN,
+            n: 1 << log_n,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: hex::encode(salt),
+        };
+        let key = derive_key(password, &kdfparams)?;
+
+        let mut ciphertext = private.as_bytes().to_vec();
+        Aes128Ctr::new_from_slices(&key[..16], &iv)
+            .map_err(|_| custom_error("invalid cipher params"))?
+            .apply_keystream(&mut ciphertext);
+        let mac = keccak256(&[&key[16..], &ciphertext[..]].concat());
+
+        Ok(Keystore {
+            version: 3,
+            id: format_uuid(&id),
+            address: Address::from_private(private).to_string(),
+            alias,
+            crypto: CryptoJson {
+                cipher: "aes-128-ctr".into(),
+                ciphertext: hex::encode(&ciphertext),
+                cipherparams: CipherParams { iv: hex::encode(iv) },
+                kdf: "scrypt".into(),
+                kdfparams,
+                mac: hex::encode(mac.as_bytes()),
+            },
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<Private> {
+        if self.crypto.cipher != "aes-128-ctr" || self.crypto.kdf != "scrypt" {
+            return Err(custom_error("unsupported keystore cipher or kdf"));
+        }
+        let key = derive_key(password, &self.crypto.kdfparams)?;
+        let mut buf = hex::decode(&self.crypto.ciphertext)?;
+        let mac = keccak256(&[&key[16..], &buf[..]].concat());
+        if hex::encode(mac.as_bytes()) != self.crypto.mac {
+            return Err(custom_error("wrong password"));
+        }
+
+        let iv = hex::decode(&self.crypto.cipherparams.iv)?;
+        Aes128Ctr::new_from_slices(&key[..16], &iv)
+            .map_err(|_| custom_error("invalid cipher params"))?
+            .apply_keystream(&mut buf);
+        let private = Private::try_from(buf)?;
+        if Address::from_private(&private).to_string() != self.address {
+            return Err(custom_error("keystore address mismatches"));
+        }
+        Ok(private)
+    }
+
+    pub fn address(&self) -> Result<Address> {
+        self.address.parse().map_err(From::from)
+    }
+
+    fn path(&self) -> PathBuf {
+        wallet_dir().join(format!("{}.json", self.address))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(wallet_dir())?;
+        fs::write(self.path(), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// All keystores in the wallet directory.
+    pub fn load_all() -> Result<Vec<Keystore>> {
+        let dir = wallet_dir();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut keystores = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+                keystores.push(serde_json::from_str(&fs::read_to_string(path)?)?);
+            }
+        }
+        keystores.sort_by(|a: &Keystore, b| a.alias.cmp(&b.alias).then(a.address.cmp(&b.address)));
+        Ok(keystores)
+    }
+
+    /// Find the keystore by address or alias.
+    pub fn find(name: &str) -> Result<Option<Keystore>> {
+        Ok(Keystore::load_all()?
+            .into_iter()
+            .find(|ks| ks.address == name || ks.alias.as_deref() == Some(name)))
+    }
+}
+
+fn derive_key(password: &str, params: &KdfParams) -> Result<Vec<u8>> {
+    if !params.n.is_power_of_two() || params.dklen != DKLEN {
+        return Err(custom_error("invalid scrypt params"));
+    }
+    let log_n = params.n.trailing_zeros() as u8;
+    let scrypt_params =
+        scrypt::Params::new(log_n, params.r, params.p).map_err(|_| custom_error("invalid scrypt params"))?;
+    let mut key = vec![0u8; params.dklen];
+    scrypt::scrypt(
+        password.as_bytes(),
+        &hex::decode(&params.salt)?,
+        &scrypt_params,
+        &mut key,
+    )
+    .map_err(|_| custom_error("invalid scrypt params"))?;
+    Ok(key)
+}
+
+/// Random UUID (version 4).
+fn format_uuid(raw: &[u8; 16]) -> String {
+    let mut raw = *raw;
+    raw[6] = (raw[6] & 0x0f) | 0x40;
+    raw[8] = (raw[8] & 0x3f) | 0x80;
+    let s = hex::encode(raw);
+    format!("{}-{}-{}-{}-{}", &s[..8], &s[8..12], &s[12..16], &s[16..20], &s[20..])
+}
+
+/// The wallet directory, `OPENTRON_CLI_WALLET_DIR` or `~/.opentron/wallet`.
+pub fn wallet_dir() -> PathBuf {
+    match env::var_os("OPENTRON_CLI_WALLET_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".opentron")
+            .join("wallet"),
+    }
+}
+
+/// Address of an alias in the wallet store.
+pub fn resolve_alias(alias: &str) -> Option<Address> {
+    Keystore::load_all()
+        .ok()?
+        .into_iter()
+        .find(|ks| ks.alias.as_deref() == Some(alias))
+        .and_then(|ks| ks.address().ok())
+}
+
+/// Read password from `OPENTRON_CLI_PASSWORD`, or prompt from the terminal.
+pub fn read_password(prompt: &str, confirm: bool) -> Result<String> {
+    if let Ok(password) = env::var("OPENTRON_CLI_PASSWORD") {
+        return Ok(password);
+    }
+    let password = rpassword::read_password_from_tty(Some(prompt))?;
+    if confirm && rpassword::read_password_from_tty(Some("Repeat password: "))? != password {
+        return Err(custom_error("passwords do not match"));
+    }
+    Ok(password)
+}
+
+/// Unlock the key pair of an account in the wallet store.
+pub fn unlock(address: &Address) -> Result<KeyPair> {
+    let ks = Keystore::find(&address.to_string())?
+        .ok_or_else(|| custom_error("account not found in wallet, use --private-key or `wallet import`"))?;
+    let password = read_password(&format!("Password of {}: ", address), false)?;
+    KeyPair::from_private(ks.decrypt(&password)?).map_err(From::from)
+}
+
+pub fn main(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        ("new", Some(arg_matches)) => new(arg_matches),
+        ("import", Some(arg_matches)) => import(arg_matches),
+        ("list", _) => list(),
+        ("export", Some(arg_matches)) => export(arg_matches),
+        ("remove", Some(arg_matches)) => remove(arg_matches),
+        _ => unreachable!("checked; qed"),
+    }
+}
+
+fn alias_of(matches: &ArgMatches) -> Result<Option<String>> {
+    match matches.value_of("alias") {
+        Some(alias) => {
+            if alias.parse::<Address>().is_ok()
+                || !alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(custom_error("alias should be of [A-Za-z0-9_-], and not an address"));
+            }
+            if resolve_alias(alias).is_some() {
+                return Err(custom_error("alias already exists"));
+            }
+            Ok(Some(alias.to_owned()))
+        }
+        None => Ok(None),
+    }
+}
+
+fn add_to_wallet(private: &Private, alias: Option<String>) -> Result<()> {
+    let address = Address::from_private(private);
+    if Keystore::find(&address.to_string())?.is_some() {
+        return Err(custom_error("account already in wallet"));
+    }
+    let password = read_password("Password: ", true)?;
+    let ks = Keystore::encrypt(private, &password, alias)?;
+    ks.save()?;
+    println!("Address => {}", address);
+    println!("Keystore => {}", ks.path().display());
+    Ok(())
+}
+
+fn new(matches: &ArgMatches) -> Result<()> {
+    let alias = alias_of(matches)?;
+    let kp = KeyPair::generate();
+    add_to_wallet(kp.private(), alias)
+}
+
+fn import(matches: &ArgMatches) -> Result<()> {
+    let alias = alias_of(matches)?;
+    let private: Private = rpassword::read_password_from_tty(Some("Private key: "))?
+        .trim()
+        .parse()?;
+    add_to_wallet(&private, alias)
+}
+
+fn list() -> Result<()> {
+    for ks in Keystore::load_all()? {
+        println!("{} {}", ks.address, ks.alias.as_deref().unwrap_or_default());
+    }
+    Ok(())
+}
+
+fn export(matches: &ArgMatches) -> Result<()> {
+    let name = matches.value_of("ACCOUNT").expect("required; qed");
+    let ks = Keystore::find(name)?.ok_or_else(|| custom_error("account not found in wallet"))?;
+    let password = read_password(&format!("Password of {}: ", ks.address), false)?;
+    let private = ks.decrypt(&password)?;
+    println!("Address => {}", ks.address);
+    println!("Private Key => {}", private);
+    Ok(())
+}
+
+fn remove(matches: &ArgMatches) -> Result<()> {
+    let name = matches.value_of("ACCOUNT").expect("required; qed");
+    let ks = Keystore::find(name)?.ok_or_else(|| custom_error("account not found in wallet"))?;
+    if !matches.is_present("force") {
+        let password = read_password(&format!("Password of {}: ", ks.address), false)?;
+        ks.decrypt(&password)?;
+    }
+    fs::remove_file(ks.path())?;
+    println!("Removed => {}", ks.address);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystore_encrypt_decrypt() {
+        let kp = KeyPair::generate();
+        let ks = Keystore::encrypt_with_log_n(kp.private(), "secret", Some("alice".into()), 4).unwrap();
+        assert_eq!(ks.address().unwrap(), kp.address());
+        assert_eq!(ks.decrypt("secret").unwrap().as_bytes(), kp.private().as_bytes());
+        assert!(ks.decrypt("wrong").is_err());
+
+        let json = serde_json::to_string(&ks).unwrap();
+        let ks: Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(ks.alias.as_deref(), Some("alice"));
+        assert_eq!(ks.decrypt("secret").unwrap().as_bytes(), kp.private().as_bytes());
+    }
+}
@@ -3,6 +3,8 @@ use keys::Address;
 use proto::chain::transaction::Contract;
 use proto::contract as contract_pb;
 
+use crate::util::parse_address;
+
 pub fn main(matches: &ArgMatches) -> Option<Contract> {
     match matches.subcommand() {
         ("create", Some(arg_matches)) => create(arg_matches),
@@ -13,7 +15,7 @@ pub fn main(matches: &ArgMatches) -> Option<Contract> {
 }
 
 fn create(matches: &ArgMatches) -> Option<Contract> {
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
     let url = matches.value_of("URL").expect("required; qed");
 
     let inner = contract_pb::WitnessCreateContract {
@@ -25,7 +27,7 @@ fn create(matches: &ArgMatches) -> Option<Contract> {
 }
 
 fn update_brokerage(matches: &ArgMatches) -> Option<Contract> {
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
     let percent = matches.value_of("PERCENT").expect("required; qed");
 
     let inner = contract_pb::UpdateBrokerageContract {
@@ -37,7 +39,7 @@ fn update_brokerage(matches: &ArgMatches) -> Option<Contract> {
 }
 
 fn update_url(matches: &ArgMatches) -> Option<Contract> {
-    let from: Address = matches.value_of("SENDER")?.parse().ok()?;
+    let from: Address = parse_address(matches.value_of("SENDER")?).ok()?;
     let url = matches.value_of("URL").expect("required; qed");
 
     let inner = contract_pb::WitnessUpdateContract {
@@ -52,7 +52,35 @@ pub struct GenesisConfig {
     pub allocs: Vec<Alloc>,
 }
 
+/// The Blackhole account, creator of the genesis block, which burns TRX.
+pub const BLACKHOLE_ADDRESS: &str = "T9yD14Nj9j7xAB4dbGeiX9h8unkKHxuWwb";
+
+const DEFAULT_PARENT_HASH: &str = "0xe58f33f9baf9305dc6f82b9f1934ea8f0ade2defb951258d50167028c780351f";
+const DEFAULT_MANTRA: &str = "A new system must allow existing systems to be linked together without requiring any central control or coordination";
+
 impl GenesisConfig {
+    /// Genesis of a private network, the Blackhole alloc is appended.
+    pub fn new(timestamp: i64, witnesses: Vec<Witness>, mut allocs: Vec<Alloc>) -> Self {
+        allocs.push(Alloc {
+            address: BLACKHOLE_ADDRESS.into(),
+            name: "Blackhole".into(),
+            balance: i64::MIN,
+        });
+        GenesisConfig {
+            timestamp,
+            parent_hash: DEFAULT_PARENT_HASH.into(),
+            mantra: DEFAULT_MANTRA.into(),
+            creator: BLACKHOLE_ADDRESS.into(),
+            witnesses,
+            allocs,
+        }
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
         let content = fs::read_to_string(path)?;
         Ok(serde_json::from_str(&content)?)
@@ -114,4 +142,24 @@ mod tests {
 
         println!("block_id => {:?}", hex::encode(block.merkle_root_hash()));
     }
+
+    #[test]
+    fn new_private_genesis() {
+        let witness = Witness {
+            address: "TPBMeAqZd5JfbomoMX8BPMttSyHx4Notrx".into(),
+            url: "http://GR1.com".into(),
+            votes: 100000000,
+        };
+        let alloc = Alloc {
+            address: "TJrsCAaTePcnB2UWkqA53nF2dnyVovotrx".into(),
+            name: "InitialAccount1".into(),
+            balance: 99000000000000000,
+        };
+        let conf = GenesisConfig::new(0, vec![witness], vec![alloc]);
+        assert_eq!(conf.allocs.last().unwrap().name, "Blackhole");
+
+        let content = serde_json::to_string(&conf).unwrap();
+        let conf = GenesisConfig::load_from_str(&content).unwrap();
+        assert!(conf.to_indexed_block().is_ok());
+    }
 }
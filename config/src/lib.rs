@@ -6,8 +6,10 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 
 pub use genesis::GenesisConfig;
+pub use rocksdb::RocksDbConfig;
 
 pub mod genesis;
+pub mod rocksdb;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -80,14 +82,36 @@ pub struct StorageConfig {
     /// Path to ChainDB.
     #[serde(default = "default_data_dir")]
     pub data_dir: String,
-    // TODO: impl a different engine
+    /// Storage engine of both chain-db and state-db.
     #[serde(default = "Default::default")]
-    pub engine: String,
+    pub engine: StorageEngine,
     /// Path to StateDB.
     #[serde(default = "default_state_data_dir")]
     pub state_data_dir: String,
     #[serde(default = "default_state_cache_dir")]
     pub state_cache_dir: String,
+    /// Replay or roll back chain-db blocks at startup, to make chain-db and state-db consistent.
+    #[serde(default = "default_auto_recovery")]
+    pub auto_recovery: bool,
+}
+
+fn default_auto_recovery() -> bool {
+    true
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StorageEngine {
+    #[serde(rename = "rocksdb")]
+    RocksDB,
+    /// All data is lost when the node exits, for testing.
+    Memory,
+}
+
+impl Default for StorageEngine {
+    fn default() -> Self {
+        StorageEngine::RocksDB
+    }
 }
 
 fn default_data_dir() -> String {
@@ -106,7 +130,21 @@ fn default_state_cache_dir() -> String {
 #[serde(rename_all = "kebab-case")]
 pub struct DiscoveryProtoConfig {
     pub enable: bool,
+    /// Save discovered peers to `peers-file`.
+    #[serde(default = "default_discovery_persist")]
+    pub persist: bool,
     pub endpoint: String,
+    /// Where discovered peers are saved, related to run path.
+    #[serde(default = "default_discovery_peers_file")]
+    pub peers_file: String,
+}
+
+fn default_discovery_persist() -> bool {
+    true
+}
+
+fn default_discovery_peers_file() -> String {
+    "./peers.json".into()
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -141,13 +179,39 @@ pub struct ProtocolConfig {
 pub struct GraphQLConfig {
     pub enable: bool,
     pub endpoint: String,
+    /// The admin API, served on a separate endpoint.
+    #[serde(default = "Default::default")]
+    pub admin: AdminGraphQLConfig,
+}
+
+/// Config for node admin API.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct AdminGraphQLConfig {
+    pub enable: bool,
+    pub endpoint: String,
+    /// Bearer token required in the `Authorization` header, if set.
+    pub token: Option<String>,
+}
+
+impl Default for AdminGraphQLConfig {
+    fn default() -> Self {
+        AdminGraphQLConfig {
+            enable: false,
+            endpoint: "127.0.0.1:3001".into(),
+            token: None,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
 pub struct ProducerKey {
+    /// Witness address.
     pub address: String,
+    /// Private key of the witness, or of its witness permission when multisig is allowed.
     pub private_key: String,
 }
 
@@ -162,6 +226,9 @@ pub struct ProducerConfig {
     // Key paris in config file
     #[serde(default = "Default::default")]
     pub keypair: Vec<ProducerKey>,
+    /// Seal blocks as soon as transactions arrive, or on demand, instead of waiting for slots. For development only.
+    #[serde(default)]
+    pub instant_seal: bool,
 }
 
 impl Default for ProducerConfig {
@@ -170,6 +237,7 @@ impl Default for ProducerConfig {
             enable: false,
             keystore: None,
             keypair: vec![],
+            instant_seal: false,
         }
     }
 }
@@ -183,6 +251,8 @@ pub struct Config {
     pub graphql: GraphQLConfig,
     #[serde(default = "Default::default")]
     pub producer: ProducerConfig,
+    #[serde(default = "Default::default")]
+    pub rocksdb: RocksDbConfig,
 }
 
 impl Config {
@@ -204,4 +274,13 @@ mod tests {
     fn test_load_default_mainnet_config() {
         assert!(Config::load_from_str(include_str!("../../etc/conf.toml")).is_ok());
     }
+
+    #[test]
+    fn test_storage_engine() {
+        let config: StorageConfig = toml::from_str("engine = 'memory'").unwrap();
+        assert_eq!(config.engine, StorageEngine::Memory);
+        let config: StorageConfig = toml::from_str("").unwrap();
+        assert_eq!(config.engine, StorageEngine::RocksDB);
+        assert!(toml::from_str::<StorageConfig>("engine = 'leveldb'").is_err());
+    }
 }
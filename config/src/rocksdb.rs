@@ -0,0 +1,308 @@
+//! RocksDB tuning options, the `[rocksdb]` section.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Options of both chain-db and state-db.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct RocksDbConfig {
+    #[serde(default = "default_create_if_missing")]
+    pub create_if_missing: bool,
+    #[serde(default = "default_max_open_files")]
+    pub max_open_files: i32,
+    /// Default to number of cpus.
+    pub max_background_jobs: Option<i32>,
+    pub max_sub_compactions: Option<u32>,
+    pub max_manifest_file_size: Option<ReadableSize>,
+    /// 0 = TolerateCorruptedTailRecords, 1 = AbsoluteConsistency, 2 = PointInTimeRecovery,
+    /// 3 = SkipAnyCorruptedRecords.
+    pub wal_recovery_mode: Option<WalRecoveryMode>,
+    /// Directory of WAL files, default to the db directory.
+    ///
+    /// WAL files of each db are put in a sub-directory named after the db directory.
+    pub wal_dir: Option<String>,
+    #[serde(default)]
+    pub wal_ttl_seconds: u64,
+    #[serde(default)]
+    pub wal_size_limit: ReadableSize,
+    #[serde(default)]
+    pub enable_statistics: bool,
+    pub stats_dump_period: Option<ReadableDuration>,
+    #[serde(default)]
+    pub compaction_readahead_size: ReadableSize,
+    /// Applied to all column families.
+    #[serde(default)]
+    pub defaultcf: ColumnFamilyConfig,
+    /// Applied to the named column family, after `defaultcf`.
+    #[serde(default)]
+    pub cf: HashMap<String, ColumnFamilyConfig>,
+}
+
+impl Default for RocksDbConfig {
+    fn default() -> Self {
+        RocksDbConfig {
+            create_if_missing: default_create_if_missing(),
+            max_open_files: default_max_open_files(),
+            max_background_jobs: None,
+            max_sub_compactions: None,
+            max_manifest_file_size: None,
+            wal_recovery_mode: None,
+            wal_dir: None,
+            wal_ttl_seconds: 0,
+            wal_size_limit: ReadableSize(0),
+            enable_statistics: false,
+            stats_dump_period: None,
+            compaction_readahead_size: ReadableSize(0),
+            defaultcf: ColumnFamilyConfig::default(),
+            cf: HashMap::new(),
+        }
+    }
+}
+
+fn default_create_if_missing() -> bool {
+    true
+}
+
+fn default_max_open_files() -> i32 {
+    1024
+}
+
+/// Column family options. Unset options keep the built-in tuning of each column family.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct ColumnFamilyConfig {
+    pub compression: Option<Compression>,
+    pub compression_per_level: Option<Vec<Compression>>,
+    pub num_levels: Option<i32>,
+    pub write_buffer_size: Option<ReadableSize>,
+    pub max_write_buffer_number: Option<i32>,
+    pub target_file_size_base: Option<ReadableSize>,
+    pub level0_file_num_compaction_trigger: Option<i32>,
+}
+
+impl ColumnFamilyConfig {
+    /// Options in `other` override options in `self`.
+    pub fn merge(&self, other: &ColumnFamilyConfig) -> ColumnFamilyConfig {
+        ColumnFamilyConfig {
+            compression: other.compression.or(self.compression),
+            compression_per_level: other
+                .compression_per_level
+                .clone()
+                .or_else(|| self.compression_per_level.clone()),
+            num_levels: other.num_levels.or(self.num_levels),
+            write_buffer_size: other.write_buffer_size.or(self.write_buffer_size),
+            max_write_buffer_number: other.max_write_buffer_number.or(self.max_write_buffer_number),
+            target_file_size_base: other.target_file_size_base.or(self.target_file_size_base),
+            level0_file_num_compaction_trigger: other
+                .level0_file_num_compaction_trigger
+                .or(self.level0_file_num_compaction_trigger),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    No,
+    Snappy,
+    Zlib,
+    Bz2,
+    Lz4,
+    Lz4hc,
+    Zstd,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum WalRecoveryMode {
+    TolerateCorruptedTailRecords = 0,
+    AbsoluteConsistency = 1,
+    PointInTimeRecovery = 2,
+    SkipAnyCorruptedRecords = 3,
+}
+
+impl std::convert::TryFrom<u8> for WalRecoveryMode {
+    type Error = String;
+
+    fn try_from(val: u8) -> Result<Self, Self::Error> {
+        match val {
+            0 => Ok(WalRecoveryMode::TolerateCorruptedTailRecords),
+            1 => Ok(WalRecoveryMode::AbsoluteConsistency),
+            2 => Ok(WalRecoveryMode::PointInTimeRecovery),
+            3 => Ok(WalRecoveryMode::SkipAnyCorruptedRecords),
+            _ => Err(format!("invalid wal-recovery-mode: {}", val)),
+        }
+    }
+}
+
+impl From<WalRecoveryMode> for u8 {
+    fn from(mode: WalRecoveryMode) -> u8 {
+        mode as u8
+    }
+}
+
+/// Size in bytes, in integer or string with unit, like "128MB".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReadableSize(pub u64);
+
+impl ReadableSize {
+    pub fn as_bytes(&self) -> u64 {
+        self.0
+    }
+
+    pub fn as_mb(&self) -> u64 {
+        self.0 / 1024 / 1024
+    }
+}
+
+impl std::str::FromStr for ReadableSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let pos = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (num, unit) = s.split_at(pos);
+        let num: u64 = num.parse().map_err(|_| format!("invalid size: {:?}", s))?;
+        let unit = match unit.trim().to_uppercase().as_str() {
+            "" | "B" => 1,
+            "KB" | "K" => 1 << 10,
+            "MB" | "M" => 1 << 20,
+            "GB" | "G" => 1 << 30,
+            "TB" | "T" => 1 << 40,
+            _ => return Err(format!("invalid size unit: {:?}", s)),
+        };
+        Ok(ReadableSize(num * unit))
+    }
+}
+
+impl Serialize for ReadableSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ReadableSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SizeVisitor;
+
+        impl<'de> Visitor<'de> for SizeVisitor {
+            type Value = ReadableSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("size in bytes, or string like \"128MB\"")
+            }
+
+            fn visit_i64<E: de::Error>(self, val: i64) -> Result<Self::Value, E> {
+                if val < 0 {
+                    return Err(E::custom("negative size"));
+                }
+                Ok(ReadableSize(val as u64))
+            }
+
+            fn visit_u64<E: de::Error>(self, val: u64) -> Result<Self::Value, E> {
+                Ok(ReadableSize(val))
+            }
+
+            fn visit_str<E: de::Error>(self, val: &str) -> Result<Self::Value, E> {
+                val.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(SizeVisitor)
+    }
+}
+
+/// Duration, in string with unit, like "10m", "30s", "1h".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadableDuration(pub Duration);
+
+impl ReadableDuration {
+    pub fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+}
+
+impl std::str::FromStr for ReadableDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let pos = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (num, unit) = s.split_at(pos);
+        let num: u64 = num.parse().map_err(|_| format!("invalid duration: {:?}", s))?;
+        let duration = match unit.trim() {
+            "ms" => Duration::from_millis(num),
+            "s" => Duration::from_secs(num),
+            "m" => Duration::from_secs(num * 60),
+            "h" => Duration::from_secs(num * 60 * 60),
+            "d" => Duration::from_secs(num * 24 * 60 * 60),
+            _ => return Err(format!("invalid duration unit: {:?}", s)),
+        };
+        Ok(ReadableDuration(duration))
+    }
+}
+
+impl Serialize for ReadableDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}ms", self.0.as_millis()))
+    }
+}
+
+impl<'de> Deserialize<'de> for ReadableDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readable_size_and_duration() {
+        assert_eq!("128MB".parse::<ReadableSize>(), Ok(ReadableSize(128 * 1024 * 1024)));
+        assert_eq!("4 kb".parse::<ReadableSize>(), Ok(ReadableSize(4096)));
+        assert!("12PB".parse::<ReadableSize>().is_err());
+        assert_eq!(
+            "10m".parse::<ReadableDuration>(),
+            Ok(ReadableDuration(Duration::from_secs(600)))
+        );
+        assert!("10".parse::<ReadableDuration>().is_err());
+    }
+
+    #[test]
+    fn test_load_rocksdb_config() {
+        let config: RocksDbConfig = toml::from_str(
+            r#"
+            max-open-files = 40960
+            max-manifest-file-size = "128MB"
+            wal-recovery-mode = 2
+            wal-size-limit = 0
+            stats-dump-period = "10m"
+
+            [defaultcf]
+            compression-per-level = ["no", "no", "lz4", "lz4", "lz4", "zstd", "zstd"]
+
+            [cf.transaction]
+            write-buffer-size = "256MB"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.max_open_files, 40960);
+        assert_eq!(config.wal_recovery_mode, Some(WalRecoveryMode::PointInTimeRecovery));
+
+        let cf = config.defaultcf.merge(&config.cf["transaction"]);
+        assert_eq!(cf.compression_per_level.unwrap().len(), 7);
+        assert_eq!(cf.write_buffer_size, Some(ReadableSize(256 * 1024 * 1024)));
+
+        assert!(toml::from_str::<RocksDbConfig>("wal-recovery-mode = 4").is_err());
+    }
+}
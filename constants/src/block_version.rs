@@ -89,12 +89,19 @@ pub enum BlockVersion {
     /// - AllowTransactionFeePool
     /// - AllowBlackholeOptimization
     GreatVoyage4_1_2 = 20,
+    /// Stake 2.0.
+    ///
+    /// - UnfreezeDelayDays
+    GreatVoyage4_7_0 = 27,
 }
 
 impl BlockVersion {
     pub fn fork_policy(&self) -> ForkPolicy {
         match *self {
-            BlockVersion::GreatVoyage4_1_0 | BlockVersion::GreatVoyage4_0_1 | BlockVersion::GreatVoyage4_1_2 => {
+            BlockVersion::GreatVoyage4_1_0 |
+            BlockVersion::GreatVoyage4_0_1 |
+            BlockVersion::GreatVoyage4_1_2 |
+            BlockVersion::GreatVoyage4_7_0 => {
                 ForkPolicy::New {
                     // GMT 2020-08-07 06:00:00
                     timestamp: 1596780000_000,
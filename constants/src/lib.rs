@@ -64,6 +64,13 @@ pub const DEFAULT_ORIGIN_ENERGY_LIMIT: usize = 10_000_000;
 pub const MAX_NUM_OF_FROZEN_DAYS_FOR_RESOURCE: i64 = 3;
 pub const MIN_NUM_OF_FROZEN_DAYS_FOR_RESOURCE: i64 = 3;
 
+/// Stake 2.0, max number of unexpired unfreezing balances of an account.
+pub const MAX_NUM_OF_UNFREEZING_V2: usize = 32;
+/// Stake 2.0, default lock period of delegated resource, in blocks, 3d.
+pub const DEFAULT_DELEGATE_LOCK_PERIOD: i64 = 86_400;
+/// Stake 2.0, max lock period of delegated resource, in blocks, 365d.
+pub const MAX_DELEGATE_LOCK_PERIOD: i64 = 10_512_000;
+
 /// Max number of `FronzenSupply` in AssetIssue.
 pub const MAX_NUM_OF_FROZEN_SUPPLIES_IN_ASSET_ISSUE: usize = 10;
 
@@ -2,18 +2,29 @@ use std::collections::HashSet;
 use std::error::Error;
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicU32};
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
 
 use chain::{IndexedBlock, IndexedTransaction};
 use chain_db::ChainDB;
 use config::genesis::GenesisConfig;
-use config::Config;
-use log::info;
+use config::{Config, StorageEngine};
+use log::{info, warn};
 use manager::Manager;
+use proto::channel::{PbftCommitResult, PbftMessage};
 use proto::common::BlockId;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, oneshot};
 use types::H256;
 
+use self::pbft::PbftPool;
+use self::peer::PeerManager;
+
+pub mod pbft;
+pub mod peer;
+pub mod recovery;
+
+/// A block sealing request of the instant-seal dev mode, replied with the sealed block.
+pub type SealRequest = oneshot::Sender<Result<IndexedBlock, String>>;
+
 pub struct AppContext {
     pub outbound_ip: String,
     pub node_id: Vec<u8>,
@@ -22,6 +33,8 @@ pub struct AppContext {
     pub genesis_config: GenesisConfig,
     pub num_active_connections: AtomicU32,
     pub num_passive_connections: AtomicU32,
+    /// Connected peers of the channel protocol.
+    pub peers: PeerManager,
     pub running: AtomicBool,
     pub syncing: AtomicBool,
     pub chain_db: ChainDB,
@@ -39,6 +52,16 @@ pub struct AppContext {
     pub incoming_transaction_tx: broadcast::Sender<IndexedTransaction>,
     /// blocks from channel protocol
     pub incoming_block_tx: broadcast::Sender<IndexedBlock>,
+    /// block sealing requests of the instant-seal dev mode
+    pub seal_request_tx: mpsc::Sender<SealRequest>,
+    /// receiving end of `seal_request_tx`, taken by the block producer
+    pub seal_request_rx: Mutex<Option<mpsc::Receiver<SealRequest>>>,
+    /// PBFT votes
+    pub pbft: PbftPool,
+    /// PBFT messages to be relayed
+    pub advertising_pbft_tx: broadcast::Sender<PbftMessage>,
+    /// Signatures of PBFT-finalized blocks to be relayed
+    pub advertising_pbft_commit_tx: broadcast::Sender<PbftCommitResult>,
 }
 
 impl AppContext {
@@ -50,7 +73,13 @@ impl AppContext {
         let genesis_config = GenesisConfig::load_from_file(&genesis_path)?;
         let genesis_blk = genesis_config.to_indexed_block()?;
 
-        let chain_db = ChainDB::new(&config.storage.data_dir);
+        let chain_db = match config.storage.engine {
+            StorageEngine::RocksDB => ChainDB::new(&config.storage.data_dir, &config.rocksdb),
+            StorageEngine::Memory => {
+                warn!("using in-memory storage, all data will be lost on exit");
+                ChainDB::new_in_memory()
+            }
+        };
         if !chain_db.has_block(&genesis_blk) {
             if let Ok(_) = chain_db.get_genesis_block() {
                 panic!("genesis block config is inconsistent with chain-db");
@@ -74,6 +103,12 @@ impl AppContext {
         let mut db_manager = Manager::new(&config, &genesis_config);
         let ref_block_hashes = chain_db.ref_block_hashes_of_block_num(db_manager.latest_block_number());
         db_manager.init_ref_blocks(ref_block_hashes);
+        let report = recovery::recover(&chain_db, &mut db_manager, config.storage.auto_recovery)?;
+        report.print();
+
+        let peers = PeerManager::new(config.protocol.channel.active_nodes.clone());
+        let pbft = PbftPool::new(&config.producer);
+        let (seal_request_tx, seal_request_rx) = mpsc::channel(10);
 
         Ok(AppContext {
             chain_db,
@@ -86,6 +121,7 @@ impl AppContext {
             syncing: AtomicBool::new(false),
             num_active_connections: AtomicU32::new(0),
             num_passive_connections: AtomicU32::new(0),
+            peers,
             recent_block_ids: RwLock::new(HashSet::new()),
             manager: RwLock::new(db_manager),
             termination_signal: broadcast::channel(1024).0,
@@ -93,6 +129,11 @@ impl AppContext {
             advertising_block_tx: broadcast::channel(10).0,
             incoming_transaction_tx: broadcast::channel(1000).0,
             incoming_block_tx: broadcast::channel(10).0,
+            seal_request_tx,
+            seal_request_rx: Mutex::new(Some(seal_request_rx)),
+            pbft,
+            advertising_pbft_tx: broadcast::channel(1000).0,
+            advertising_pbft_commit_tx: broadcast::channel(10).0,
         })
     }
 }
@@ -0,0 +1,405 @@
+//! PBFT finality, enabled by the `AllowPbft` proposal.
+//!
+//! Every active witness signs a PREPARE for each block applied to its state-db. Once 2/3+1 PREPAREs of a block
+//! are collected, witnesses sign COMMITs, and 2/3+1 COMMITs finalize the block. Messages are relayed to all peers,
+//! so that every node collects votes and tracks the PBFT-finalized block number. Signatures of the finalized block
+//! are relayed as a commit result.
+//!
+//! A witness never signs two different blocks of the same `view_n`.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::RwLock;
+
+use chain::IndexedBlock;
+use config::ProducerConfig;
+use keys::{Address, KeyPair, Public, Signature};
+use log::{debug, info, warn};
+use manager::Manager;
+use prost::Message;
+use proto::channel::pbft_message::{DataType, Raw as PbftRaw, Type as PbftType};
+use proto::channel::{PbftCommitResult, PbftMessage};
+use state::keys::ChainParameter;
+
+use super::AppContext;
+
+/// Votes of blocks older than this are dropped.
+const MAX_PENDING_BLOCKS: i64 = 1_000;
+
+/// Votes are grouped by `(msg_type, view_n, data)`.
+type VoteKey = (i32, i64, Vec<u8>);
+
+/// Signed messages of local witness keys, `(msg_type, view_n, signer) => data`.
+type SignedKey = (i32, i64, Address);
+
+/// Collected PBFT votes and local witness keys.
+pub struct PbftPool {
+    keypairs: HashMap<Address, KeyPair>,
+    votes: RwLock<HashMap<VoteKey, HashMap<Address, PbftMessage>>>,
+    signed: RwLock<HashMap<SignedKey, Vec<u8>>>,
+    /// Commit signatures of the latest finalized block.
+    latest_commit: RwLock<Option<PbftCommitResult>>,
+}
+
+impl PbftPool {
+    pub fn new(config: &ProducerConfig) -> Self {
+        let mut keypairs = HashMap::new();
+        if config.enable {
+            for key in &config.keypair {
+                match key.private_key.parse().and_then(KeyPair::from_private) {
+                    Ok(kp) if kp.address().to_string() == key.address => {
+                        keypairs.insert(kp.address(), kp);
+                    }
+                    _ => warn!("PBFT signing is unavailable for witness {}", key.address),
+                }
+            }
+        }
+        PbftPool {
+            keypairs,
+            votes: RwLock::new(HashMap::new()),
+            signed: RwLock::new(HashMap::new()),
+            latest_commit: RwLock::new(None),
+        }
+    }
+
+    /// Commit signatures of the latest finalized block.
+    pub fn latest_commit(&self) -> Option<PbftCommitResult> {
+        self.latest_commit.read().unwrap().clone()
+    }
+
+    /// Sign a message with a local witness key. Returns None if the message is already signed, or if another
+    /// block of the same `view_n` is signed.
+    fn sign(&self, signer: Address, raw: PbftRaw) -> Option<PbftMessage> {
+        let kp = self.keypairs.get(&signer)?;
+        let mut signed = self.signed.write().unwrap();
+        let key = (raw.msg_type, raw.view_n, signer);
+        if let Some(data) = signed.get(&key) {
+            if data != &raw.data {
+                warn!(
+                    "refuse to sign conflicting PBFT message of block #{} with {}",
+                    raw.view_n, signer
+                );
+            }
+            return None;
+        }
+        let data = raw.data.clone();
+        match sign_message(raw, kp) {
+            Ok(msg) => {
+                signed.insert(key, data);
+                Some(msg)
+            }
+            Err(e) => {
+                warn!("sign PBFT message failed: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Add a verified vote. Returns the number of votes, or None if it is duplicated.
+    fn add_vote(&self, signer: Address, raw: &PbftRaw, msg: PbftMessage) -> Option<usize> {
+        let mut votes = self.votes.write().unwrap();
+        let voters = votes.entry((raw.msg_type, raw.view_n, raw.data.clone())).or_default();
+        if voters.contains_key(&signer) {
+            return None;
+        }
+        voters.insert(signer, msg);
+        Some(voters.len())
+    }
+
+    fn commits_of(&self, view_n: i64, data: &[u8]) -> Vec<PbftMessage> {
+        self.votes
+            .read()
+            .unwrap()
+            .get(&(PbftType::Commit as i32, view_n, data.to_vec()))
+            .map(|voters| voters.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Drop votes of finalized or stale blocks.
+    fn prune(&self, finalized: i64, latest: i64) {
+        let min_view_n = finalized.max(latest - MAX_PENDING_BLOCKS);
+        self.votes
+            .write()
+            .unwrap()
+            .retain(|(_, view_n, _), _| *view_n > min_view_n);
+        self.signed
+            .write()
+            .unwrap()
+            .retain(|(_, view_n, _), _| *view_n > min_view_n);
+    }
+}
+
+fn is_pbft_enabled(manager: &Manager) -> bool {
+    // NOTE: The parameter is missing in state-db inited by older versions, until the proposal is approved.
+    manager
+        .state()
+        .get(&ChainParameter::AllowPbft)
+        .unwrap()
+        .unwrap_or_default() !=
+        0
+}
+
+/// Number of votes required, 2/3+1 of active witnesses.
+pub fn threshold(num_witnesses: usize) -> usize {
+    num_witnesses * 2 / 3 + 1
+}
+
+pub fn sign_message(raw: PbftRaw, keypair: &KeyPair) -> Result<PbftMessage, keys::Error> {
+    let mut buf = Vec::with_capacity(raw.encoded_len());
+    raw.encode(&mut buf).unwrap();
+    let signature = keypair.private().sign(&buf)?;
+    Ok(PbftMessage {
+        raw_data: Some(raw),
+        signature: signature.as_bytes().to_vec(),
+    })
+}
+
+fn recover_signer(data: &[u8], signature: &[u8]) -> Option<Address> {
+    let sig = Signature::try_from(signature).ok()?;
+    Public::recover(data, &sig)
+        .ok()
+        .map(|public| Address::from_public(&public))
+}
+
+pub fn recover_message_signer(msg: &PbftMessage) -> Option<Address> {
+    let raw = msg.raw_data.as_ref()?;
+    let mut buf = Vec::with_capacity(raw.encoded_len());
+    raw.encode(&mut buf).unwrap();
+    recover_signer(&buf, &msg.signature)
+}
+
+/// Sign PREPAREs of a block with local witness keys. The block must have been applied to state-db.
+pub fn on_block_applied(ctx: &AppContext, block: &IndexedBlock) {
+    let (active_witnesses, epoch) = {
+        let manager = ctx.manager.read().unwrap();
+        if !is_pbft_enabled(&manager) {
+            return;
+        }
+        // The block might have been switched out or followed by newer ones, while the manager is unlocked.
+        if manager.latest_block_hash() != *block.hash() {
+            debug!("block #{} is not the latest applied block, skip PBFT signing", block.number());
+            return;
+        }
+        ctx.pbft
+            .prune(manager.pbft_block_number(), ctx.chain_db.get_block_height());
+        (manager.get_active_witnesses(), manager.current_epoch())
+    };
+
+    for &addr in ctx.pbft.keypairs.keys() {
+        if !active_witnesses.contains(&addr) {
+            continue;
+        }
+        let raw = PbftRaw {
+            msg_type: PbftType::Prepare as i32,
+            data_type: DataType::Block as i32,
+            view_n: block.number(),
+            epoch,
+            data: block.hash().as_bytes().to_vec(),
+        };
+        if let Some(msg) = ctx.pbft.sign(addr, raw) {
+            on_message(ctx, msg);
+        }
+    }
+    on_block(ctx, block);
+}
+
+/// Handle a block inserted into chain-db. COMMITs might arrive earlier than the block.
+pub fn on_block(ctx: &AppContext, block: &IndexedBlock) {
+    if !is_pbft_enabled(&ctx.manager.read().unwrap()) {
+        return;
+    }
+    try_finalize(ctx, block.number(), block.hash().as_bytes());
+}
+
+/// Handle a PBFT message, from a peer or signed locally. Valid messages are relayed to peers.
+///
+/// Returns false if the message is invalid or duplicated.
+pub fn on_message(ctx: &AppContext, msg: PbftMessage) -> bool {
+    let raw = match msg.raw_data {
+        Some(ref raw) if raw.data_type == DataType::Block as i32 => raw.clone(),
+        _ => return false,
+    };
+    let active_witnesses = {
+        let manager = ctx.manager.read().unwrap();
+        if !is_pbft_enabled(&manager) {
+            return false;
+        }
+        if raw.view_n <= manager.pbft_block_number() {
+            return false;
+        }
+        manager.get_active_witnesses()
+    };
+
+    let signer = match recover_message_signer(&msg) {
+        Some(signer) if active_witnesses.contains(&signer) => signer,
+        _ => {
+            debug!("PBFT message not signed by an active witness");
+            return false;
+        }
+    };
+    let num_votes = match ctx.pbft.add_vote(signer, &raw, msg.clone()) {
+        Some(n) => n,
+        None => return false,
+    };
+    let _ = ctx.advertising_pbft_tx.send(msg);
+
+    if num_votes != threshold(active_witnesses.len()) {
+        return true;
+    }
+    match PbftType::from_i32(raw.msg_type) {
+        Some(PbftType::Prepare) => {
+            debug!("block #{} prepared by PBFT", raw.view_n);
+            for &addr in ctx.pbft.keypairs.keys() {
+                if !active_witnesses.contains(&addr) {
+                    continue;
+                }
+                let commit = PbftRaw {
+                    msg_type: PbftType::Commit as i32,
+                    ..raw.clone()
+                };
+                if let Some(msg) = ctx.pbft.sign(addr, commit) {
+                    on_message(ctx, msg);
+                }
+            }
+        }
+        Some(PbftType::Commit) => {
+            try_finalize(ctx, raw.view_n, &raw.data);
+        }
+        _ => {}
+    }
+    true
+}
+
+/// Handle signatures of a PBFT commit from a peer. Valid commit results are relayed to peers.
+pub fn on_commit_result(ctx: &AppContext, result: PbftCommitResult) -> bool {
+    let raw = match PbftRaw::decode(&result.data[..]) {
+        Ok(raw) if raw.msg_type == PbftType::Commit as i32 && raw.data_type == DataType::Block as i32 => raw,
+        _ => return false,
+    };
+    let active_witnesses = {
+        let manager = ctx.manager.read().unwrap();
+        if !is_pbft_enabled(&manager) {
+            return false;
+        }
+        if raw.view_n <= manager.pbft_block_number() {
+            return false;
+        }
+        manager.get_active_witnesses()
+    };
+    let mut signers: Vec<_> = result
+        .signature
+        .iter()
+        .filter_map(|sig| recover_signer(&result.data, sig))
+        .filter(|signer| active_witnesses.contains(signer))
+        .collect();
+    signers.sort();
+    signers.dedup();
+    if signers.len() < threshold(active_witnesses.len()) {
+        debug!("PBFT commit of block #{} without enough signatures", raw.view_n);
+        return false;
+    }
+    finalize(ctx, raw.view_n, &raw.data, result)
+}
+
+/// Finalize the block if enough COMMITs are collected.
+fn try_finalize(ctx: &AppContext, view_n: i64, data: &[u8]) {
+    let commits = ctx.pbft.commits_of(view_n, data);
+    let num_witnesses = ctx.manager.read().unwrap().get_active_witnesses().len();
+    if commits.len() < threshold(num_witnesses) {
+        return;
+    }
+    let raw = commits[0].raw_data.as_ref().unwrap();
+    let mut buf = Vec::with_capacity(raw.encoded_len());
+    raw.encode(&mut buf).unwrap();
+    let result = PbftCommitResult {
+        data: buf,
+        signature: commits.into_iter().map(|msg| msg.signature).collect(),
+    };
+    finalize(ctx, view_n, data, result);
+}
+
+/// Update the PBFT-finalized block number, if the block is in chain-db.
+fn finalize(ctx: &AppContext, view_n: i64, data: &[u8], result: PbftCommitResult) -> bool {
+    match ctx.chain_db.get_block_header_by_number(view_n) {
+        Ok(header) if header.hash.as_bytes() == data => {}
+        _ => {
+            debug!("PBFT committed block #{} is not found locally", view_n);
+            return false;
+        }
+    }
+
+    match ctx.manager.write().unwrap().update_pbft_block_number(view_n) {
+        Ok(true) => {
+            info!("block #{} finalized by PBFT", view_n);
+            *ctx.pbft.latest_commit.write().unwrap() = Some(result.clone());
+            let _ = ctx.advertising_pbft_commit_tx.send(result);
+            ctx.pbft.prune(view_n, ctx.chain_db.get_block_height());
+            true
+        }
+        Ok(false) => false,
+        Err(e) => {
+            warn!("update PBFT block number failed: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold() {
+        assert_eq!(threshold(1), 1);
+        assert_eq!(threshold(3), 3);
+        assert_eq!(threshold(4), 3);
+        assert_eq!(threshold(27), 19);
+    }
+
+    #[test]
+    fn test_sign_and_recover() {
+        let kp = KeyPair::generate();
+        let raw = PbftRaw {
+            msg_type: PbftType::Prepare as i32,
+            data_type: DataType::Block as i32,
+            view_n: 100,
+            epoch: 1,
+            data: vec![0xaa; 32],
+        };
+        let msg = sign_message(raw, &kp).unwrap();
+        assert_eq!(recover_message_signer(&msg), Some(kp.address()));
+
+        let mut tampered = msg;
+        tampered.raw_data.as_mut().unwrap().view_n = 101;
+        assert_ne!(recover_message_signer(&tampered), Some(kp.address()));
+    }
+
+    #[test]
+    fn test_refuse_conflicting_signing() {
+        let kp = KeyPair::generate();
+        let addr = kp.address();
+        let pool = PbftPool {
+            keypairs: vec![(addr, kp)].into_iter().collect(),
+            votes: RwLock::new(HashMap::new()),
+            signed: RwLock::new(HashMap::new()),
+            latest_commit: RwLock::new(None),
+        };
+        let prepare = |view_n, data| PbftRaw {
+            msg_type: PbftType::Prepare as i32,
+            data_type: DataType::Block as i32,
+            view_n,
+            epoch: 1,
+            data: vec![data; 32],
+        };
+
+        assert!(pool.sign(addr, prepare(100, 0xaa)).is_some());
+        assert!(pool.sign(addr, prepare(100, 0xaa)).is_none());
+        assert!(pool.sign(addr, prepare(100, 0xbb)).is_none());
+        assert!(pool.sign(addr, prepare(101, 0xbb)).is_some());
+        let commit = PbftRaw {
+            msg_type: PbftType::Commit as i32,
+            ..prepare(100, 0xaa)
+        };
+        assert!(pool.sign(addr, commit).is_some());
+    }
+}
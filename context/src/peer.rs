@@ -0,0 +1,218 @@
+//! Connection tracking of the channel protocol.
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::RwLock;
+
+use tokio::sync::oneshot;
+
+/// Direction of a channel connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerDirection {
+    /// Outgoing connection, to one of the active nodes.
+    Active,
+    /// Incoming connection.
+    Passive,
+}
+
+/// Info of a connected peer.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    pub endpoint: SocketAddr,
+    pub node_id: Vec<u8>,
+    pub direction: PeerDirection,
+    /// Highest block number known to be held by the peer.
+    pub head_block_number: i64,
+    /// Latest measured ping-pong round trip, in ms.
+    pub latency: Option<i64>,
+    /// Handshake timestamp, in ms.
+    pub connected_at: i64,
+}
+
+struct PeerEntry {
+    info: PeerInfo,
+    disconnect_tx: Option<oneshot::Sender<()>>,
+}
+
+/// All connected peers, banned addresses and the active node list.
+pub struct PeerManager {
+    peers: RwLock<HashMap<SocketAddr, PeerEntry>>,
+    banned: RwLock<HashSet<IpAddr>>,
+    /// Endpoints to connect to, initialized from `protocol.channel.active-nodes`.
+    active_nodes: RwLock<Vec<String>>,
+}
+
+impl PeerManager {
+    pub fn new(active_nodes: Vec<String>) -> Self {
+        PeerManager {
+            peers: RwLock::new(HashMap::new()),
+            banned: RwLock::new(HashSet::new()),
+            active_nodes: RwLock::new(active_nodes),
+        }
+    }
+
+    /// Register a handshaked peer. The returned receiver fires when the peer is kicked.
+    pub fn register(&self, info: PeerInfo) -> oneshot::Receiver<()> {
+        let (disconnect_tx, disconnect_rx) = oneshot::channel();
+        let entry = PeerEntry {
+            info,
+            disconnect_tx: Some(disconnect_tx),
+        };
+        self.peers.write().unwrap().insert(entry.info.endpoint, entry);
+        disconnect_rx
+    }
+
+    pub fn unregister(&self, endpoint: &SocketAddr) {
+        self.peers.write().unwrap().remove(endpoint);
+    }
+
+    pub fn is_connected(&self, endpoint: &SocketAddr) -> bool {
+        self.peers.read().unwrap().contains_key(endpoint)
+    }
+
+    pub fn peers(&self) -> Vec<PeerInfo> {
+        let mut peers: Vec<_> = self
+            .peers
+            .read()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        peers.sort_by_key(|info| info.connected_at);
+        peers
+    }
+
+    pub fn num_peers(&self) -> usize {
+        self.peers.read().unwrap().len()
+    }
+
+    /// Update peer's head block number, never goes backwards.
+    pub fn update_head_block(&self, endpoint: &SocketAddr, number: i64) {
+        if let Some(entry) = self.peers.write().unwrap().get_mut(endpoint) {
+            entry.info.head_block_number = entry.info.head_block_number.max(number);
+        }
+    }
+
+    pub fn update_latency(&self, endpoint: &SocketAddr, latency: i64) {
+        if let Some(entry) = self.peers.write().unwrap().get_mut(endpoint) {
+            entry.info.latency = Some(latency);
+        }
+    }
+
+    /// Highest block number among all connected peers.
+    pub fn highest_block_number(&self) -> Option<i64> {
+        self.peers
+            .read()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.head_block_number)
+            .max()
+    }
+
+    /// Disconnect a peer. Returns false if the peer is not connected.
+    pub fn disconnect(&self, endpoint: &SocketAddr) -> bool {
+        match self.peers.write().unwrap().get_mut(endpoint) {
+            Some(entry) => {
+                if let Some(tx) = entry.disconnect_tx.take() {
+                    let _ = tx.send(());
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn active_nodes(&self) -> Vec<String> {
+        self.active_nodes.read().unwrap().clone()
+    }
+
+    /// Add an endpoint to the active node list. Returns false if it's already there.
+    pub fn add_active_node(&self, endpoint: &str) -> bool {
+        let mut nodes = self.active_nodes.write().unwrap();
+        if nodes.iter().any(|node| node == endpoint) {
+            false
+        } else {
+            nodes.push(endpoint.to_owned());
+            true
+        }
+    }
+
+    /// Remove an endpoint from the active node list. Returns false if it's not there.
+    pub fn remove_active_node(&self, endpoint: &str) -> bool {
+        let mut nodes = self.active_nodes.write().unwrap();
+        let len = nodes.len();
+        nodes.retain(|node| node != endpoint);
+        nodes.len() != len
+    }
+
+    pub fn is_banned(&self, ip: &IpAddr) -> bool {
+        self.banned.read().unwrap().contains(ip)
+    }
+
+    pub fn banned(&self) -> Vec<IpAddr> {
+        let mut banned: Vec<_> = self.banned.read().unwrap().iter().copied().collect();
+        banned.sort();
+        banned
+    }
+
+    /// Ban an ip address, and disconnect all peers from it.
+    pub fn ban(&self, ip: IpAddr) -> bool {
+        let newly_banned = self.banned.write().unwrap().insert(ip);
+        let endpoints: Vec<_> = self
+            .peers
+            .read()
+            .unwrap()
+            .keys()
+            .filter(|ep| ep.ip() == ip)
+            .copied()
+            .collect();
+        for ep in &endpoints {
+            self.disconnect(ep);
+        }
+        newly_banned
+    }
+
+    pub fn unban(&self, ip: &IpAddr) -> bool {
+        self.banned.write().unwrap().remove(ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dummy_peer(endpoint: &str) -> PeerInfo {
+        PeerInfo {
+            endpoint: endpoint.parse().unwrap(),
+            node_id: vec![0; 64],
+            direction: PeerDirection::Active,
+            head_block_number: 0,
+            latency: None,
+            connected_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_ban_disconnects_peer() {
+        let peers = PeerManager::new(vec![]);
+        let mut rx = peers.register(dummy_peer("10.0.0.1:18888"));
+        let _rx2 = peers.register(dummy_peer("10.0.0.2:18888"));
+
+        assert!(peers.ban("10.0.0.1".parse().unwrap()));
+        assert!(!peers.ban("10.0.0.1".parse().unwrap()));
+        assert!(rx.try_recv().is_ok());
+        assert!(peers.is_banned(&"10.0.0.1".parse().unwrap()));
+        assert!(!peers.is_banned(&"10.0.0.2".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_head_block_never_goes_backwards() {
+        let peers = PeerManager::new(vec![]);
+        let ep = "10.0.0.1:18888".parse().unwrap();
+        let _rx = peers.register(dummy_peer("10.0.0.1:18888"));
+
+        peers.update_head_block(&ep, 100);
+        peers.update_head_block(&ep, 50);
+        assert_eq!(peers.highest_block_number(), Some(100));
+    }
+}
@@ -0,0 +1,233 @@
+//! Consistency recovery of chain-db and state-db at startup.
+
+use std::error::Error;
+
+use chain_db::ChainDB;
+use log::{info, warn};
+use manager::Manager;
+
+/// What has been done to make chain-db and state-db consistent.
+#[derive(Debug, Default)]
+pub struct RecoveryReport {
+    /// Block height of chain-db before recovery.
+    pub chain_height: i64,
+    /// Block number of state-db before recovery.
+    pub state_block_number: i64,
+    /// Repaired block height of chain-db, when it lags behind inserted blocks.
+    pub repaired_height: Option<i64>,
+    /// Number of blocks applied to state-db.
+    pub num_replayed: i64,
+    /// Number of blocks deleted from chain-db.
+    pub num_rolled_back: i64,
+    /// Block number that both dbs agree on after recovery.
+    pub final_block_number: i64,
+}
+
+impl RecoveryReport {
+    pub fn print(&self) {
+        if self.repaired_height.is_none() && self.num_replayed == 0 && self.num_rolled_back == 0 {
+            if self.chain_height == self.final_block_number {
+                info!(
+                    "chain-db and state-db are consistent at block #{}",
+                    self.final_block_number
+                );
+            } else {
+                info!(
+                    "state-db #{} is {} blocks behind chain-db #{}",
+                    self.final_block_number,
+                    self.chain_height - self.final_block_number,
+                    self.chain_height
+                );
+            }
+            return;
+        }
+        info!("db recovery finished");
+        info!("  chain-db height => #{}", self.chain_height);
+        info!("  state-db block => #{}", self.state_block_number);
+        if let Some(height) = self.repaired_height {
+            info!("  repaired chain-db height => #{}", height);
+        }
+        info!("  replayed => {} blocks", self.num_replayed);
+        info!("  rolled back => {} blocks", self.num_rolled_back);
+        info!("  consistent at => #{}", self.final_block_number);
+    }
+}
+
+/// Make chain-db and state-db consistent.
+///
+/// Blocks in chain-db but not yet applied are replayed to state-db. If replaying fails, the failed block and all
+/// blocks after it are deleted from chain-db, to be synced again. A state-db ahead of chain-db can not be recovered.
+pub fn recover(chain_db: &ChainDB, manager: &mut Manager, replay: bool) -> Result<RecoveryReport, Box<dyn Error>> {
+    let mut report = RecoveryReport {
+        chain_height: chain_db.get_block_height(),
+        state_block_number: manager.latest_block_number(),
+        ..Default::default()
+    };
+
+    let chain_height = chain_db.repair_block_height()?;
+    if chain_height != report.chain_height {
+        report.repaired_height = Some(chain_height);
+    }
+
+    let state_block_hash = manager.latest_block_hash();
+    if !chain_db.has_block_id(&state_block_hash) {
+        return Err(format!(
+            "state-db latest block #{} {:?} not found in chain-db",
+            report.state_block_number, state_block_hash
+        )
+        .into());
+    }
+
+    if chain_height < report.state_block_number {
+        // block exists, but block height is broken
+        warn!(
+            "chain-db block height #{} is behind state-db #{}, repairing",
+            chain_height, report.state_block_number
+        );
+        chain_db.force_update_block_height(report.state_block_number)?;
+        report.repaired_height = Some(report.state_block_number);
+    } else if chain_height > report.state_block_number && replay {
+        info!(
+            "replaying chain-db blocks #{}..=#{}",
+            report.state_block_number + 1,
+            chain_height
+        );
+        for num in report.state_block_number + 1..=chain_height {
+            let result = chain_db
+                .get_block_by_number(num as u64)
+                .and_then(|block| manager.push_incoming_block(&block));
+            match result {
+                Ok(true) => report.num_replayed += 1,
+                Ok(false) | Err(_) => {
+                    warn!("replaying block #{} failed: {:?}", num, result);
+                    manager.discard_pending_layers();
+                    rollback(chain_db, num, chain_height)?;
+                    report.num_rolled_back = chain_height - num + 1;
+                    break;
+                }
+            }
+            if report.num_replayed % 1_000 == 0 {
+                info!("replayed {} blocks", report.num_replayed);
+            }
+        }
+    }
+
+    report.final_block_number = manager.latest_block_number();
+    Ok(report)
+}
+
+/// Delete blocks `from..=to` from chain-db, in reverse order.
+fn rollback(chain_db: &ChainDB, from: i64, to: i64) -> Result<(), Box<dyn Error>> {
+    for num in (from..=to).rev() {
+        chain_db.delete_block_by_number(num as u64)?;
+        chain_db.force_update_block_height(num - 1)?;
+    }
+    warn!("rolled back chain-db to block #{}", from - 1);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chain::{BlockBuilder, IndexedTransaction};
+    use config::genesis::{Alloc, GenesisConfig, Witness};
+    use config::{Config, StorageEngine};
+    use ::keys::KeyPair;
+    use prost::Message;
+    use proto::chain::transaction::Raw as TransactionRaw;
+    use proto::chain::Transaction;
+    use proto::contract::TransferContract;
+    use state::keys;
+
+    use super::*;
+
+    fn signed_transfer(sender: &KeyPair, to: &KeyPair, ref_block_hash: &[u8]) -> IndexedTransaction {
+        let cntr = TransferContract {
+            owner_address: sender.address().as_bytes().to_owned(),
+            to_address: to.address().as_bytes().to_owned(),
+            amount: 1_000_000,
+        };
+        let raw = TransactionRaw {
+            contract: Some(cntr.into()),
+            ref_block_bytes: vec![0, 0],
+            ref_block_hash: ref_block_hash[8..16].to_owned(),
+            timestamp: 3_000,
+            expiration: 60_000,
+            ..Default::default()
+        };
+        let mut buf = Vec::with_capacity(255);
+        raw.encode(&mut buf).unwrap();
+        let signature = sender.private().sign(&buf).unwrap();
+        let txn = Transaction {
+            raw_data: Some(raw),
+            signatures: vec![signature.as_bytes().to_owned()],
+            ..Default::default()
+        };
+        IndexedTransaction::from_raw(txn).unwrap()
+    }
+
+    #[test]
+    fn corrupt_block_is_rolled_back_without_touching_state() {
+        let witness = KeyPair::generate();
+        let sender = KeyPair::generate();
+        let receiver = KeyPair::generate();
+        let genesis = GenesisConfig::new(
+            0,
+            vec![Witness {
+                address: witness.address().to_string(),
+                url: "http://witness.com".into(),
+                votes: 100_000_000,
+            }],
+            vec![
+                Alloc {
+                    address: sender.address().to_string(),
+                    name: "Sender".into(),
+                    balance: 1_000_000_000,
+                },
+                Alloc {
+                    address: receiver.address().to_string(),
+                    name: "Receiver".into(),
+                    balance: 1_000_000,
+                },
+            ],
+        );
+        let genesis_blk = genesis.to_indexed_block().unwrap();
+
+        let mut config = Config::load_from_str(include_str!("../../etc/conf.local-test.toml")).unwrap();
+        config.storage.engine = StorageEngine::Memory;
+        let mut manager = Manager::new(&config, &genesis);
+        manager.init_ref_blocks(vec![*genesis_blk.hash()]);
+
+        // The second transfer is a duplicate, it fails after the first one has been applied.
+        let txn = signed_transfer(&sender, &receiver, genesis_blk.hash().as_bytes());
+        let mut builder = BlockBuilder::new(1)
+            .version(17)
+            .timestamp(3_000)
+            .parent_hash(genesis_blk.hash())
+            .witness(&witness.address());
+        builder.push_transaction(txn.raw.clone());
+        builder.push_transaction(txn.raw.clone());
+        let corrupt_blk = builder.build(&witness).unwrap();
+
+        let chain_db = ChainDB::new_in_memory();
+        chain_db.insert_block(&genesis_blk).unwrap();
+        chain_db.insert_block(&corrupt_blk).unwrap();
+        chain_db.update_block_height(1);
+
+        let balance_of = |manager: &Manager, kp: &KeyPair| {
+            manager.state().must_get(&keys::Account(kp.address())).balance
+        };
+        let report = recover(&chain_db, &mut manager, true).unwrap();
+
+        assert_eq!(report.chain_height, 1);
+        assert_eq!(report.num_replayed, 0);
+        assert_eq!(report.num_rolled_back, 1);
+        assert_eq!(report.final_block_number, 0);
+        assert_eq!(chain_db.get_block_height(), 0);
+        assert!(!chain_db.has_block(&corrupt_blk));
+
+        assert_eq!(manager.latest_block_hash(), *genesis_blk.hash());
+        assert_eq!(balance_of(&manager, &sender), 1_000_000_000);
+        assert_eq!(balance_of(&manager, &receiver), 1_000_000);
+        assert!(manager.state().get(&keys::TransactionReceipt(txn.hash)).unwrap().is_none());
+    }
+}
@@ -20,6 +20,10 @@ pub enum Error {
     InvalidAddress,
     /// Unable to generate a key pair.
     FailedKeyGeneration,
+    /// Invalid BIP-39 mnemonic phrase, or word count.
+    InvalidMnemonic,
+    /// Invalid BIP-32 derivation path.
+    InvalidDerivationPath,
 }
 
 impl fmt::Display for Error {
@@ -32,6 +36,8 @@ impl fmt::Display for Error {
             Error::InvalidPrivate => "Invalid Private",
             Error::InvalidAddress => "Invalid Address",
             Error::FailedKeyGeneration => "Key generation failed",
+            Error::InvalidMnemonic => "Invalid Mnemonic",
+            Error::InvalidDerivationPath => "Invalid Derivation Path",
         };
 
         msg.fmt(f)
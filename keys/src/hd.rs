@@ -0,0 +1,162 @@
+//! Hierarchical deterministic wallets, BIP-39 mnemonics and BIP-32 derivation.
+//!
+//! Tron registers coin type 195 in SLIP-44, so accounts are derived along `m/44'/195'/0'/0/i`.
+use hmac::{Hmac, Mac, NewMac};
+use libsecp256k1::{PublicKey, SecretKey};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha512;
+
+use crate::error::Error;
+use crate::keypair::KeyPair;
+use crate::private::Private;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// SLIP-44 coin type of Tron.
+pub const TRON_COIN_TYPE: u32 = 195;
+
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Generates a random English mnemonic of 12, 15, 18, 21 or 24 words.
+pub fn generate_mnemonic(word_count: usize) -> Result<String, Error> {
+    if ![12, 15, 18, 21, 24].contains(&word_count) {
+        return Err(Error::InvalidMnemonic);
+    }
+    let mut entropy = vec![0u8; word_count / 3 * 4];
+    OsRng.fill_bytes(&mut entropy);
+    let mnemonic = bip39::Mnemonic::from_entropy(&entropy).map_err(|_| Error::InvalidMnemonic)?;
+    Ok(mnemonic.to_string())
+}
+
+/// Converts a mnemonic phrase to the 64-byte seed, with an optional passphrase.
+pub fn mnemonic_to_seed(phrase: &str, passphrase: &str) -> Result<[u8; 64], Error> {
+    let mnemonic = bip39::Mnemonic::parse(phrase).map_err(|_| Error::InvalidMnemonic)?;
+    Ok(mnemonic.to_seed(passphrase))
+}
+
+/// Derivation path of the `index`-th address of an account, `m/44'/195'/{account}'/0/{index}`.
+pub fn tron_derivation_path(account: u32, index: u32) -> String {
+    format!("m/44'/{}'/{}'/0/{}", TRON_COIN_TYPE, account, index)
+}
+
+/// A BIP-32 extended private key.
+#[derive(Debug, Clone)]
+pub struct ExtendedPrivate {
+    private: Private,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedPrivate {
+    /// Master key of a seed.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, Error> {
+        let mut mac = HmacSha512::new_varkey(b"Bitcoin seed").expect("any key length; qed");
+        mac.update(seed);
+        let output = mac.finalize().into_bytes();
+
+        let secret = SecretKey::parse_slice(&output[..32])?;
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&output[32..]);
+        Ok(ExtendedPrivate {
+            private: Private::from(secret.serialize()),
+            chain_code,
+        })
+    }
+
+    /// Master key of a mnemonic phrase.
+    pub fn from_mnemonic(phrase: &str, passphrase: &str) -> Result<Self, Error> {
+        Self::from_seed(&mnemonic_to_seed(phrase, passphrase)?)
+    }
+
+    /// Private key.
+    pub fn private(&self) -> &Private {
+        &self.private
+    }
+
+    /// Chain code.
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    /// Key pair of the private key.
+    pub fn to_keypair(&self) -> Result<KeyPair, Error> {
+        KeyPair::from_private(self.private.clone())
+    }
+
+    /// Derives a child key, hardened if `index >= 2^31`.
+    pub fn derive_child(&self, index: u32) -> Result<Self, Error> {
+        let secret = SecretKey::parse_slice(self.private.as_bytes())?;
+
+        let mut mac = HmacSha512::new_varkey(&self.chain_code).expect("any key length; qed");
+        if index >= HARDENED_OFFSET {
+            mac.update(&[0]);
+            mac.update(self.private.as_bytes());
+        } else {
+            mac.update(&PublicKey::from_secret_key(&secret).serialize_compressed());
+        }
+        mac.update(&index.to_be_bytes());
+        let output = mac.finalize().into_bytes();
+
+        let mut child = SecretKey::parse_slice(&output[..32])?;
+        child.tweak_add_assign(&secret)?;
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&output[32..]);
+        Ok(ExtendedPrivate {
+            private: Private::from(child.serialize()),
+            chain_code,
+        })
+    }
+
+    /// Derives along a path like `m/44'/195'/0'/0/0`. Hardened indices are marked by `'` or `h`.
+    pub fn derive_path(&self, path: &str) -> Result<Self, Error> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(Error::InvalidDerivationPath);
+        }
+        parts.try_fold(self.clone(), |key, part| {
+            let (num, hardened) = match part.strip_suffix('\'').or_else(|| part.strip_suffix('h')) {
+                Some(num) => (num, true),
+                None => (part, false),
+            };
+            let index: u32 = num.parse().map_err(|_| Error::InvalidDerivationPath)?;
+            if index >= HARDENED_OFFSET {
+                return Err(Error::InvalidDerivationPath);
+            }
+            key.derive_child(if hardened { index + HARDENED_OFFSET } else { index })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bip32_derivation() {
+        // BIP-32 test vector 1.
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedPrivate::from_seed(&seed).unwrap();
+        assert_eq!(
+            master.private().to_string(),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+        assert_eq!(
+            hex::encode(master.chain_code()),
+            "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508"
+        );
+
+        let key = master.derive_path("m/0'/1").unwrap();
+        assert_eq!(
+            key.private().to_string(),
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"
+        );
+        assert_eq!(
+            hex::encode(key.chain_code()),
+            "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19"
+        );
+
+        assert!(master.derive_path("m/0/x").is_err());
+        assert!(master.derive_path("0/1").is_err());
+        assert_eq!(tron_derivation_path(0, 3), "m/44'/195'/0'/0/3");
+    }
+}
@@ -4,6 +4,7 @@
 
 mod address;
 mod error;
+mod hd;
 mod keypair;
 mod private;
 mod public;
@@ -11,6 +12,7 @@ mod signature;
 
 pub use address::{b58decode_check, b58encode_check, Address};
 pub use error::Error;
+pub use hd::{generate_mnemonic, mnemonic_to_seed, tron_derivation_path, ExtendedPrivate, TRON_COIN_TYPE};
 pub use keypair::KeyPair;
 pub use private::Private;
 pub use public::Public;
@@ -16,6 +16,7 @@ pub mod asset;
 mod exchange;
 mod proposal;
 mod resource;
+mod resource_v2;
 #[cfg(feature = "nile")]
 mod shielded;
 pub mod smart_contract;
@@ -9,6 +9,7 @@ use proto::contract as contract_pb;
 use proto::state::ResourceDelegation;
 use state::keys;
 
+use super::super::super::governance::maintenance::record_vote_change;
 use super::super::super::governance::reward::RewardController;
 use super::super::TransactionContext;
 use super::BuiltinContractExecutorExt;
@@ -18,6 +19,10 @@ impl BuiltinContractExecutorExt for contract_pb::FreezeBalanceContract {
     fn validate(&self, manager: &Manager, _ctx: &mut TransactionContext) -> Result<(), String> {
         let state_db = &manager.state_db;
 
+        if manager.support_unfreeze_delay() {
+            return Err("legacy freezing is closed since Stake 2.0, use FreezeBalanceV2 instead".into());
+        }
+
         let owner_address = Address::try_from(&self.owner_address).map_err(|_| "invalid owner_address")?;
         let owner_acct = state_db
             .get(&keys::Account(owner_address))
@@ -347,16 +352,8 @@ impl BuiltinContractExecutorExt for contract_pb::UnfreezeBalanceContract {
             .state_db
             .get(&keys::Votes(owner_addr))
             .map_err(|_| "db query error")?;
-        if let Some(votes) = maybe_votes {
-            for vote in &votes.votes {
-                let wit_addr = Address::try_from(&vote.vote_address).unwrap();
-                let mut wit = manager.state_db.must_get(&keys::Witness(wit_addr));
-                wit.vote_count -= vote.vote_count;
-                manager
-                    .state_db
-                    .put_key(keys::Witness(wit_addr), wit)
-                    .map_err(|_| "db insert error")?;
-            }
+        if maybe_votes.is_some() {
+            record_vote_change(manager, owner_addr, vec![])?;
             manager
                 .state_db
                 .delete_key(&keys::Votes(owner_addr))
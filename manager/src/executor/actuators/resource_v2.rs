@@ -0,0 +1,788 @@
+//! Stake 2.0 resource related, freeze, unfreeze with delay, delegate and undelegate.
+//!
+//! Enabled by the `UnfreezeDelayDays` proposal. Frozen balance has no frozen duration, unfrozen balance waits in the
+//! unfreezing queue of the account, and can be withdrawn after `UnfreezeDelayDays`.
+
+use std::convert::TryFrom;
+
+use ::keys::Address;
+use proto::chain::transaction::Result as TransactionResult;
+use proto::common::{AccountType, ResourceCode};
+use proto::contract as contract_pb;
+use proto::state::{Account, ResourceDelegation, UnfreezingV2, Votes};
+use state::keys;
+
+use super::super::super::governance::maintenance::record_vote_change;
+use super::super::super::governance::reward::RewardController;
+use super::super::super::resource::{frozen_v2_usage, transfer_undelegated_usage};
+use super::super::TransactionContext;
+use super::BuiltinContractExecutorExt;
+use crate::Manager;
+
+const DAY_IN_MS: i64 = 86_400_000;
+
+impl BuiltinContractExecutorExt for contract_pb::FreezeBalanceV2Contract {
+    fn validate(&self, manager: &Manager, _ctx: &mut TransactionContext) -> Result<(), String> {
+        if !manager.support_unfreeze_delay() {
+            return Err("Stake 2.0 is not enabled".into());
+        }
+
+        let owner_addr = Address::try_from(&self.owner_address).map_err(|_| "invalid owner_address")?;
+        let owner_acct = manager
+            .state_db
+            .get(&keys::Account(owner_addr))
+            .map_err(|_| "error while querying db")?
+            .ok_or_else(|| "owner account is not on chain")?;
+
+        if self.frozen_balance < 1_000_000 {
+            return Err("frozen balance must be greater than 1_TRX".into());
+        }
+        if self.frozen_balance > owner_acct.balance {
+            return Err(format!(
+                "insufficient frozen balance, balance={}, required={}",
+                owner_acct.balance, self.frozen_balance
+            ));
+        }
+        if ResourceCode::from_i32(self.resource).is_none() {
+            return Err("resource code is invalid, possible values: [BANDWIDTH, ENERGY]".into());
+        }
+
+        Ok(())
+    }
+
+    fn execute(&self, manager: &mut Manager, _ctx: &mut TransactionContext) -> Result<TransactionResult, String> {
+        let owner_addr = Address::try_from(&self.owner_address).unwrap();
+        let res_type = ResourceCode::from_i32(self.resource).unwrap();
+
+        let mut owner_acct = manager.state_db.must_get(&keys::Account(owner_addr));
+        let old_weight = weight_of(&owner_acct, res_type);
+
+        owner_acct.adjust_balance(-self.frozen_balance).unwrap();
+        *owner_acct.frozen_v2_amount_mut(res_type) += self.frozen_balance;
+
+        adjust_total_weight(manager, res_type, weight_of(&owner_acct, res_type) - old_weight)?;
+        manager
+            .state_db
+            .put_key(keys::Account(owner_addr), owner_acct)
+            .map_err(|_| "db insert error")?;
+
+        Ok(TransactionResult::success())
+    }
+}
+
+// Unfreeze part of the frozen balance. Votes are reduced if tron power is not enough.
+impl BuiltinContractExecutorExt for contract_pb::UnfreezeBalanceV2Contract {
+    fn validate(&self, manager: &Manager, _ctx: &mut TransactionContext) -> Result<(), String> {
+        if !manager.support_unfreeze_delay() {
+            return Err("Stake 2.0 is not enabled".into());
+        }
+
+        let owner_addr = Address::try_from(&self.owner_address).map_err(|_| "invalid owner_address")?;
+        let owner_acct = manager
+            .state_db
+            .get(&keys::Account(owner_addr))
+            .map_err(|_| "error while querying db")?
+            .ok_or_else(|| "owner account is not on chain")?;
+
+        let res_type = ResourceCode::from_i32(self.resource).ok_or("invalid resource type")?;
+
+        let frozen_amount = owner_acct.frozen_v2_amount(res_type);
+        if frozen_amount <= 0 {
+            return Err(format!("no frozen {:?} balance", res_type));
+        }
+        if self.unfreeze_balance <= 0 || self.unfreeze_balance > frozen_amount {
+            return Err(format!(
+                "invalid unfreeze balance, frozen={}, required={}",
+                frozen_amount, self.unfreeze_balance
+            ));
+        }
+
+        let now = manager.latest_block_timestamp();
+        let num_unfreezing = owner_acct
+            .unfreezing_v2
+            .iter()
+            .filter(|unfreezing| unfreezing.expiration_timestamp > now)
+            .count();
+        if num_unfreezing >= constants::MAX_NUM_OF_UNFREEZING_V2 {
+            return Err(format!(
+                "too many unfreezing balances, max={}",
+                constants::MAX_NUM_OF_UNFREEZING_V2
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn execute(&self, manager: &mut Manager, ctx: &mut TransactionContext) -> Result<TransactionResult, String> {
+        let owner_addr = Address::try_from(&self.owner_address).unwrap();
+        let res_type = ResourceCode::from_i32(self.resource).unwrap();
+
+        RewardController::new(manager).withdraw_reward(owner_addr)?;
+
+        let now = manager.latest_block_timestamp();
+        let mut owner_acct = manager.state_db.must_get(&keys::Account(owner_addr));
+
+        ctx.withdrawal_expire_amount = withdraw_expire_unfreezing(&mut owner_acct, now)?;
+
+        let old_weight = weight_of(&owner_acct, res_type);
+        *owner_acct.frozen_v2_amount_mut(res_type) -= self.unfreeze_balance;
+        owner_acct.unfreezing_v2.push(UnfreezingV2 {
+            resource: res_type as i32,
+            amount: self.unfreeze_balance,
+            expiration_timestamp: now + manager.unfreeze_delay_days() * DAY_IN_MS,
+        });
+        adjust_total_weight(manager, res_type, weight_of(&owner_acct, res_type) - old_weight)?;
+
+        let tron_power = owner_acct.tron_power();
+        manager
+            .state_db
+            .put_key(keys::Account(owner_addr), owner_acct)
+            .map_err(|_| "db insert error")?;
+
+        reduce_votes(manager, owner_addr, tron_power)?;
+
+        Ok(TransactionResult::success())
+    }
+}
+
+// Withdraw all expired unfreezing balances.
+impl BuiltinContractExecutorExt for contract_pb::WithdrawExpireUnfreezeContract {
+    fn validate(&self, manager: &Manager, _ctx: &mut TransactionContext) -> Result<(), String> {
+        if !manager.support_unfreeze_delay() {
+            return Err("Stake 2.0 is not enabled".into());
+        }
+
+        let owner_addr = Address::try_from(&self.owner_address).map_err(|_| "invalid owner_address")?;
+        let owner_acct = manager
+            .state_db
+            .get(&keys::Account(owner_addr))
+            .map_err(|_| "error while querying db")?
+            .ok_or_else(|| "owner account is not on chain")?;
+
+        let now = manager.latest_block_timestamp();
+        let amount = owner_acct
+            .unfreezing_v2
+            .iter()
+            .filter(|unfreezing| unfreezing.expiration_timestamp <= now)
+            .try_fold(0_i64, |acc, unfreezing| acc.checked_add(unfreezing.amount))
+            .ok_or("mathematical overflow")?;
+        if amount <= 0 {
+            return Err("no expired unfreezing balance to withdraw".into());
+        }
+        owner_acct.balance.checked_add(amount).ok_or("mathematical overflow")?;
+
+        Ok(())
+    }
+
+    fn execute(&self, manager: &mut Manager, ctx: &mut TransactionContext) -> Result<TransactionResult, String> {
+        let owner_addr = Address::try_from(&self.owner_address).unwrap();
+
+        let now = manager.latest_block_timestamp();
+        let mut owner_acct = manager.state_db.must_get(&keys::Account(owner_addr));
+        ctx.withdrawal_expire_amount = withdraw_expire_unfreezing(&mut owner_acct, now)?;
+
+        manager
+            .state_db
+            .put_key(keys::Account(owner_addr), owner_acct)
+            .map_err(|_| "db insert error")?;
+
+        Ok(TransactionResult::success())
+    }
+}
+
+impl BuiltinContractExecutorExt for contract_pb::DelegateResourceContract {
+    fn validate(&self, manager: &Manager, _ctx: &mut TransactionContext) -> Result<(), String> {
+        let state_db = &manager.state_db;
+
+        if !manager.support_unfreeze_delay() {
+            return Err("Stake 2.0 is not enabled".into());
+        }
+        if state_db.must_get(&keys::ChainParameter::AllowDelegateResource) == 0 {
+            return Err("delegate resource is not allowed".into());
+        }
+
+        let owner_addr = Address::try_from(&self.owner_address).map_err(|_| "invalid owner_address")?;
+        let owner_acct = state_db
+            .get(&keys::Account(owner_addr))
+            .map_err(|_| "error while querying db")?
+            .ok_or_else(|| "owner account is not on chain")?;
+
+        let res_type = ResourceCode::from_i32(self.resource).ok_or("invalid resource type")?;
+
+        if self.balance < 1_000_000 {
+            return Err("delegated balance must be greater than 1_TRX".into());
+        }
+        // Resource already used can not be delegated.
+        let frozen_amount = owner_acct.frozen_v2_amount(res_type);
+        let used_amount = frozen_v2_usage(manager, &owner_acct, res_type);
+        if self.balance > frozen_amount - used_amount {
+            return Err(format!(
+                "insufficient frozen {:?} balance, frozen={}, used={}, required={}",
+                res_type, frozen_amount, used_amount, self.balance
+            ));
+        }
+
+        if self.receiver_address == self.owner_address {
+            return Err("the owner and receiver address cannot be the same".into());
+        }
+        let recv_addr = Address::try_from(&self.receiver_address).map_err(|_| "invalid receiver_address")?;
+        let recv_acct = state_db
+            .get(&keys::Account(recv_addr))
+            .map_err(|_| "error while querying db")?
+            .ok_or_else(|| "receiver account is not on chain")?;
+        if recv_acct.r#type == AccountType::Contract as i32 {
+            return Err("delegate resource to contract address is not allowed".into());
+        }
+
+        if self.lock {
+            if self.lock_period < 0 || self.lock_period > constants::MAX_DELEGATE_LOCK_PERIOD {
+                return Err(format!(
+                    "lock period must be in range [0, {}]",
+                    constants::MAX_DELEGATE_LOCK_PERIOD
+                ));
+            }
+            let maybe_locked = state_db
+                .get(&keys::ResourceDelegationV2(owner_addr, recv_addr, true))
+                .map_err(|_| "error while querying db")?;
+            if let Some(locked) = maybe_locked {
+                let expiration = lock_expiration(manager, self.lock_period);
+                if locked.amount_for_resource(res_type) > 0 &&
+                    locked.expiration_timestamp_for_resource(res_type) > expiration
+                {
+                    return Err("lock period cannot be shorter than the remaining lock period".into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute(&self, manager: &mut Manager, _ctx: &mut TransactionContext) -> Result<TransactionResult, String> {
+        let owner_addr = Address::try_from(&self.owner_address).unwrap();
+        let recv_addr = Address::try_from(&self.receiver_address).unwrap();
+        let res_type = ResourceCode::from_i32(self.resource).unwrap();
+
+        let key = keys::ResourceDelegationV2(owner_addr, recv_addr, self.lock);
+        let mut del = manager
+            .state_db
+            .get(&key)
+            .map_err(|_| "db query error")?
+            .unwrap_or_else(|| ResourceDelegation {
+                from_address: owner_addr.as_bytes().to_vec(),
+                to_address: recv_addr.as_bytes().to_vec(),
+                ..Default::default()
+            });
+        let expiration = if self.lock {
+            lock_expiration(manager, self.lock_period)
+        } else {
+            0
+        };
+        match res_type {
+            ResourceCode::Bandwidth => {
+                del.amount_for_bandwidth += self.balance;
+                del.expiration_timestamp_for_bandwidth = expiration;
+            }
+            ResourceCode::Energy => {
+                del.amount_for_energy += self.balance;
+                del.expiration_timestamp_for_energy = expiration;
+            }
+        }
+        manager.state_db.put_key(key, del).map_err(|_| "db insert error")?;
+
+        // NOTE: Total weight is unchanged, frozen amount is moved to delegated out amount.
+        let mut owner_acct = manager.state_db.must_get(&keys::Account(owner_addr));
+        *owner_acct.frozen_v2_amount_mut(res_type) -= self.balance;
+        *owner_acct.delegated_v2_out_amount_mut(res_type) += self.balance;
+        manager
+            .state_db
+            .put_key(keys::Account(owner_addr), owner_acct)
+            .map_err(|_| "db insert error")?;
+
+        let mut recv_acct = manager.state_db.must_get(&keys::Account(recv_addr));
+        *recv_acct.delegated_v2_frozen_amount_mut(res_type) += self.balance;
+        manager
+            .state_db
+            .put_key(keys::Account(recv_addr), recv_acct)
+            .map_err(|_| "db insert error")?;
+
+        Ok(TransactionResult::success())
+    }
+}
+
+// Take back delegated resource. Locked delegation can only be taken back after its lock period.
+impl BuiltinContractExecutorExt for contract_pb::UnDelegateResourceContract {
+    fn validate(&self, manager: &Manager, _ctx: &mut TransactionContext) -> Result<(), String> {
+        let state_db = &manager.state_db;
+
+        if !manager.support_unfreeze_delay() {
+            return Err("Stake 2.0 is not enabled".into());
+        }
+
+        let owner_addr = Address::try_from(&self.owner_address).map_err(|_| "invalid owner_address")?;
+        if state_db
+            .get(&keys::Account(owner_addr))
+            .map_err(|_| "error while querying db")?
+            .is_none()
+        {
+            return Err("owner account is not on chain".into());
+        }
+
+        let res_type = ResourceCode::from_i32(self.resource).ok_or("invalid resource type")?;
+
+        if self.receiver_address == self.owner_address {
+            return Err("the owner and receiver address cannot be the same".into());
+        }
+        let recv_addr = Address::try_from(&self.receiver_address).map_err(|_| "invalid receiver_address")?;
+
+        if self.balance <= 0 {
+            return Err("undelegated balance must be greater than 0".into());
+        }
+
+        let now = manager.latest_block_timestamp();
+        let mut available = 0;
+        for &lock in &[false, true] {
+            let maybe_del = state_db
+                .get(&keys::ResourceDelegationV2(owner_addr, recv_addr, lock))
+                .map_err(|_| "error while querying db")?;
+            if let Some(del) = maybe_del {
+                if !lock || del.expiration_timestamp_for_resource(res_type) <= now {
+                    available += del.amount_for_resource(res_type);
+                }
+            }
+        }
+        if available <= 0 {
+            return Err(format!("delegated {:?} does not exist, or is locked", res_type));
+        }
+        if self.balance > available {
+            return Err(format!(
+                "insufficient delegated {:?} balance, available={}, required={}",
+                res_type, available, self.balance
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn execute(&self, manager: &mut Manager, _ctx: &mut TransactionContext) -> Result<TransactionResult, String> {
+        let owner_addr = Address::try_from(&self.owner_address).unwrap();
+        let recv_addr = Address::try_from(&self.receiver_address).unwrap();
+        let res_type = ResourceCode::from_i32(self.resource).unwrap();
+        let now = manager.latest_block_timestamp();
+
+        let unlocked_key = keys::ResourceDelegationV2(owner_addr, recv_addr, false);
+        let locked_key = keys::ResourceDelegationV2(owner_addr, recv_addr, true);
+        let mut unlocked = manager
+            .state_db
+            .get(&unlocked_key)
+            .map_err(|_| "db query error")?
+            .unwrap_or_else(|| ResourceDelegation {
+                from_address: owner_addr.as_bytes().to_vec(),
+                to_address: recv_addr.as_bytes().to_vec(),
+                ..Default::default()
+            });
+
+        // Expired locked delegation is merged into the unlocked one.
+        let maybe_locked = manager.state_db.get(&locked_key).map_err(|_| "db query error")?;
+        if let Some(mut locked) = maybe_locked {
+            if locked.expiration_timestamp_for_resource(res_type) <= now {
+                let amount = locked.amount_for_resource(res_type);
+                locked.reset_resource(res_type);
+                match res_type {
+                    ResourceCode::Bandwidth => unlocked.amount_for_bandwidth += amount,
+                    ResourceCode::Energy => unlocked.amount_for_energy += amount,
+                }
+                if locked.is_empty() {
+                    manager
+                        .state_db
+                        .delete_key(&locked_key)
+                        .map_err(|_| "db delete error")?;
+                } else {
+                    manager
+                        .state_db
+                        .put_key(locked_key, locked)
+                        .map_err(|_| "db insert error")?;
+                }
+            }
+        }
+
+        match res_type {
+            ResourceCode::Bandwidth => unlocked.amount_for_bandwidth -= self.balance,
+            ResourceCode::Energy => unlocked.amount_for_energy -= self.balance,
+        }
+        if unlocked.is_empty() {
+            manager
+                .state_db
+                .delete_key(&unlocked_key)
+                .map_err(|_| "db delete error")?;
+        } else {
+            manager
+                .state_db
+                .put_key(unlocked_key, unlocked)
+                .map_err(|_| "db insert error")?;
+        }
+
+        let mut owner_acct = manager.state_db.must_get(&keys::Account(owner_addr));
+        let maybe_recv_acct = manager
+            .state_db
+            .get(&keys::Account(recv_addr))
+            .map_err(|_| "db query error")?;
+        if let Some(mut recv_acct) = maybe_recv_acct {
+            // Resource usage of the undelegated amount is transferred back to the owner.
+            transfer_undelegated_usage(manager, &mut recv_acct, &mut owner_acct, self.balance, res_type);
+            let acquired = recv_acct.delegated_v2_frozen_amount_mut(res_type);
+            *acquired = (*acquired - self.balance).max(0);
+            manager
+                .state_db
+                .put_key(keys::Account(recv_addr), recv_acct)
+                .map_err(|_| "db insert error")?;
+        }
+
+        *owner_acct.delegated_v2_out_amount_mut(res_type) -= self.balance;
+        *owner_acct.frozen_v2_amount_mut(res_type) += self.balance;
+        manager
+            .state_db
+            .put_key(keys::Account(owner_addr), owner_acct)
+            .map_err(|_| "db insert error")?;
+
+        Ok(TransactionResult::success())
+    }
+}
+
+/// Total weight of a resource of the account, in TRX.
+fn weight_of(acct: &Account, res_type: ResourceCode) -> i64 {
+    match res_type {
+        ResourceCode::Bandwidth => {
+            (acct.frozen_v2_amount_for_bandwidth + acct.delegated_v2_out_amount_for_bandwidth) / 1_000_000
+        }
+        ResourceCode::Energy => {
+            (acct.frozen_v2_amount_for_energy + acct.delegated_v2_out_amount_for_energy) / 1_000_000
+        }
+    }
+}
+
+fn adjust_total_weight(manager: &mut Manager, res_type: ResourceCode, diff: i64) -> Result<(), String> {
+    let weight_key = match res_type {
+        ResourceCode::Bandwidth => keys::DynamicProperty::TotalBandwidthWeight,
+        ResourceCode::Energy => keys::DynamicProperty::TotalEnergyWeight,
+    };
+    let weight = manager.state_db.must_get(&weight_key);
+    manager
+        .state_db
+        .put_key(weight_key, weight + diff)
+        .map_err(|_| "db insert error")?;
+    Ok(())
+}
+
+/// Move expired unfreezing balances back to balance. Returns the withdrawn amount.
+fn withdraw_expire_unfreezing(acct: &mut Account, now: i64) -> Result<i64, String> {
+    let mut amount = 0;
+    acct.unfreezing_v2.retain(|unfreezing| {
+        if unfreezing.expiration_timestamp <= now {
+            amount += unfreezing.amount;
+            false
+        } else {
+            true
+        }
+    });
+    acct.adjust_balance(amount).map_err(|_| "mathematical overflow")?;
+    Ok(amount)
+}
+
+fn lock_expiration(manager: &Manager, lock_period: i64) -> i64 {
+    let lock_period = if lock_period == 0 {
+        constants::DEFAULT_DELEGATE_LOCK_PERIOD
+    } else {
+        lock_period
+    };
+    manager.latest_block_timestamp() + lock_period * constants::BLOCK_PRODUCING_INTERVAL
+}
+
+/// Reduce votes proportionally when they exceed the tron power.
+fn reduce_votes(manager: &mut Manager, owner_addr: Address, tron_power: i64) -> Result<(), String> {
+    let maybe_votes = manager
+        .state_db
+        .get(&keys::Votes(owner_addr))
+        .map_err(|_| "db query error")?;
+    let old_votes = match maybe_votes {
+        Some(votes) => votes,
+        None => return Ok(()),
+    };
+    let total_vote_count: i64 = old_votes.votes.iter().map(|vote| vote.vote_count).sum();
+    if total_vote_count <= tron_power {
+        return Ok(());
+    }
+
+    let mut new_votes = vec![];
+    for vote in old_votes.votes {
+        let vote_count = (vote.vote_count as i128 * tron_power as i128 / total_vote_count as i128) as i64;
+        if vote_count > 0 {
+            new_votes.push(proto::common::Vote { vote_count, ..vote });
+        }
+    }
+    record_vote_change(manager, owner_addr, new_votes.clone())?;
+
+    if new_votes.is_empty() {
+        manager
+            .state_db
+            .delete_key(&keys::Votes(owner_addr))
+            .map_err(|_| "db delete error")?;
+    } else {
+        let epoch = manager.state_db.must_get(&keys::DynamicProperty::CurrentEpoch);
+        manager
+            .state_db
+            .put_key(
+                keys::Votes(owner_addr),
+                Votes {
+                    epoch,
+                    votes: new_votes,
+                },
+            )
+            .map_err(|_| "db insert error")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{addr, new_test_manager, run_contract as run, GR1, GR2};
+    use proto::common::Vote;
+    use proto::state::AccountResource;
+
+    const OWNER: &str = "TALSWqjhNCaXi5YWPh9DvZgvtYRSfqVUwq";
+    const RECEIVER: &str = "TARkPnaSRKSg6ZAUbJGMGvBstELj7VS3Br";
+
+    fn new_stake_v2_manager() -> Manager {
+        let mut manager = new_test_manager();
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::UnfreezeDelayDays, 14)
+            .unwrap();
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowDelegateResource, 1)
+            .unwrap();
+        for &address in &[OWNER, RECEIVER] {
+            let acct = Account {
+                balance: 100_000_000,
+                ..Default::default()
+            };
+            manager.state_db.put_key(keys::Account(addr(address)), acct).unwrap();
+        }
+        manager
+    }
+
+    fn freeze(manager: &mut Manager, res_type: ResourceCode, frozen_balance: i64) -> Result<(), String> {
+        let cntr = contract_pb::FreezeBalanceV2Contract {
+            owner_address: addr(OWNER).as_bytes().to_vec(),
+            frozen_balance,
+            resource: res_type as i32,
+        };
+        run(manager, cntr)
+    }
+
+    fn delegate(manager: &mut Manager, res_type: ResourceCode, balance: i64, lock: bool) -> Result<(), String> {
+        let cntr = contract_pb::DelegateResourceContract {
+            owner_address: addr(OWNER).as_bytes().to_vec(),
+            resource: res_type as i32,
+            balance,
+            receiver_address: addr(RECEIVER).as_bytes().to_vec(),
+            lock,
+            lock_period: 0,
+        };
+        run(manager, cntr)
+    }
+
+    fn undelegate(manager: &mut Manager, res_type: ResourceCode, balance: i64) -> Result<(), String> {
+        let cntr = contract_pb::UnDelegateResourceContract {
+            owner_address: addr(OWNER).as_bytes().to_vec(),
+            resource: res_type as i32,
+            balance,
+            receiver_address: addr(RECEIVER).as_bytes().to_vec(),
+        };
+        run(manager, cntr)
+    }
+
+    fn account(manager: &Manager, address: &str) -> Account {
+        manager.state_db.must_get(&keys::Account(addr(address)))
+    }
+
+    fn vote(wit: &str, vote_count: i64) -> Vote {
+        Vote {
+            vote_address: addr(wit).as_bytes().to_vec(),
+            vote_count,
+        }
+    }
+
+    #[test]
+    fn freeze_and_unfreeze_update_weight_and_votes() {
+        let mut manager = new_stake_v2_manager();
+        let total_weight = manager.state_db.must_get(&keys::DynamicProperty::TotalEnergyWeight);
+
+        assert!(freeze(&mut manager, ResourceCode::Energy, 500_000).is_err());
+        assert!(freeze(&mut manager, ResourceCode::Energy, 200_000_000).is_err());
+        freeze(&mut manager, ResourceCode::Energy, 10_000_000).unwrap();
+        let owner_acct = account(&manager, OWNER);
+        assert_eq!(owner_acct.balance, 90_000_000);
+        assert_eq!(owner_acct.frozen_v2_amount_for_energy, 10_000_000);
+        assert_eq!(owner_acct.tron_power(), 10);
+        assert_eq!(
+            manager.state_db.must_get(&keys::DynamicProperty::TotalEnergyWeight),
+            total_weight + 10
+        );
+
+        let votes = Votes {
+            epoch: 0,
+            votes: vec![vote(GR1, 6), vote(GR2, 4)],
+        };
+        manager.state_db.put_key(keys::Votes(addr(OWNER)), votes).unwrap();
+
+        let cntr = contract_pb::UnfreezeBalanceV2Contract {
+            owner_address: addr(OWNER).as_bytes().to_vec(),
+            unfreeze_balance: 5_000_000,
+            resource: ResourceCode::Energy as i32,
+        };
+        run(&mut manager, cntr).unwrap();
+        let owner_acct = account(&manager, OWNER);
+        assert_eq!(owner_acct.frozen_v2_amount_for_energy, 5_000_000);
+        assert_eq!(
+            owner_acct.unfreezing_v2,
+            vec![UnfreezingV2 {
+                resource: ResourceCode::Energy as i32,
+                amount: 5_000_000,
+                expiration_timestamp: manager.latest_block_timestamp() + 14 * DAY_IN_MS,
+            }]
+        );
+        assert_eq!(
+            manager.state_db.must_get(&keys::DynamicProperty::TotalEnergyWeight),
+            total_weight + 5
+        );
+        // votes are reduced proportionally
+        assert_eq!(
+            manager.state_db.must_get(&keys::Votes(addr(OWNER))).votes,
+            vec![vote(GR1, 3), vote(GR2, 2)]
+        );
+        assert_eq!(
+            manager.state_db.must_get(&keys::VoteChange(addr(OWNER))).old_votes,
+            vec![vote(GR1, 6), vote(GR2, 4)]
+        );
+    }
+
+    #[test]
+    fn used_resource_can_not_be_delegated() {
+        let mut manager = new_stake_v2_manager();
+        freeze(&mut manager, ResourceCode::Bandwidth, 10_000_000).unwrap();
+        // 1 bandwidth per TRX
+        manager
+            .state_db
+            .put_key(keys::DynamicProperty::TotalBandwidthLimit, 1)
+            .unwrap();
+        manager
+            .state_db
+            .put_key(keys::DynamicProperty::TotalBandwidthWeight, 1)
+            .unwrap();
+
+        let mut owner_acct = account(&manager, OWNER);
+        owner_acct.resource = Some(AccountResource {
+            frozen_bandwidth_used: 3,
+            frozen_bandwidth_latest_slot: manager.get_head_slot(),
+            ..Default::default()
+        });
+        manager.state_db.put_key(keys::Account(addr(OWNER)), owner_acct).unwrap();
+
+        assert!(delegate(&mut manager, ResourceCode::Bandwidth, 8_000_000, false).is_err());
+        delegate(&mut manager, ResourceCode::Bandwidth, 7_000_000, false).unwrap();
+        assert_eq!(account(&manager, OWNER).frozen_v2_amount_for_bandwidth, 3_000_000);
+    }
+
+    #[test]
+    fn locked_delegation_is_undelegated_after_expiration() {
+        let mut manager = new_stake_v2_manager();
+        freeze(&mut manager, ResourceCode::Energy, 10_000_000).unwrap();
+        let total_weight = manager.state_db.must_get(&keys::DynamicProperty::TotalEnergyWeight);
+
+        delegate(&mut manager, ResourceCode::Energy, 4_000_000, false).unwrap();
+        delegate(&mut manager, ResourceCode::Energy, 5_000_000, true).unwrap();
+        let owner_acct = account(&manager, OWNER);
+        assert_eq!(owner_acct.frozen_v2_amount_for_energy, 1_000_000);
+        assert_eq!(owner_acct.delegated_v2_out_amount_for_energy, 9_000_000);
+        assert_eq!(owner_acct.tron_power(), 10);
+        assert_eq!(account(&manager, RECEIVER).delegated_v2_frozen_amount_for_energy, 9_000_000);
+        assert_eq!(
+            manager.state_db.must_get(&keys::DynamicProperty::TotalEnergyWeight),
+            total_weight
+        );
+        let expiration = lock_expiration(&manager, 0);
+        let locked = manager
+            .state_db
+            .must_get(&keys::ResourceDelegationV2(addr(OWNER), addr(RECEIVER), true));
+        assert_eq!(locked.expiration_timestamp_for_energy, expiration);
+
+        let set_receiver_energy_used = |manager: &mut Manager, energy_used| {
+            let mut recv_acct = account(manager, RECEIVER);
+            recv_acct.resource = Some(AccountResource {
+                energy_used,
+                energy_latest_slot: manager.get_head_slot(),
+                ..Default::default()
+            });
+            manager.state_db.put_key(keys::Account(addr(RECEIVER)), recv_acct).unwrap();
+        };
+        let energy_used = |manager: &Manager, address| account(manager, address).resource().energy_used;
+
+        // only unlocked delegation is available
+        set_receiver_energy_used(&mut manager, 900);
+        assert!(undelegate(&mut manager, ResourceCode::Energy, 5_000_000).is_err());
+        undelegate(&mut manager, ResourceCode::Energy, 4_000_000).unwrap();
+        assert!(undelegate(&mut manager, ResourceCode::Energy, 1_000_000).is_err());
+        // usage of 4 of 9 TRX is transferred back
+        assert_eq!(energy_used(&manager, RECEIVER), 500);
+        assert_eq!(energy_used(&manager, OWNER), 400);
+
+        manager
+            .state_db
+            .put_key(keys::DynamicProperty::LatestBlockTimestamp, expiration)
+            .unwrap();
+        // usage before the lock period has been recovered
+        set_receiver_energy_used(&mut manager, 300);
+        undelegate(&mut manager, ResourceCode::Energy, 5_000_000).unwrap();
+        assert_eq!(energy_used(&manager, RECEIVER), 0);
+        assert_eq!(energy_used(&manager, OWNER), 300);
+        for &lock in &[false, true] {
+            assert!(manager
+                .state_db
+                .get(&keys::ResourceDelegationV2(addr(OWNER), addr(RECEIVER), lock))
+                .unwrap()
+                .is_none());
+        }
+        let owner_acct = account(&manager, OWNER);
+        assert_eq!(owner_acct.frozen_v2_amount_for_energy, 10_000_000);
+        assert_eq!(owner_acct.delegated_v2_out_amount_for_energy, 0);
+        assert_eq!(account(&manager, RECEIVER).delegated_v2_frozen_amount_for_energy, 0);
+    }
+
+    #[test]
+    fn withdraw_expired_unfreezing_only() {
+        let unfreezing = |amount, expiration_timestamp| UnfreezingV2 {
+            resource: ResourceCode::Energy as i32,
+            amount,
+            expiration_timestamp,
+        };
+        let mut acct = Account {
+            balance: 1_000_000,
+            unfreezing_v2: vec![
+                unfreezing(2_000_000, 100),
+                unfreezing(3_000_000, 200),
+                unfreezing(5_000_000, 300),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(withdraw_expire_unfreezing(&mut acct, 200), Ok(5_000_000));
+        assert_eq!(acct.balance, 6_000_000);
+        assert_eq!(acct.unfreezing_v2, vec![unfreezing(5_000_000, 300)]);
+
+        assert_eq!(withdraw_expire_unfreezing(&mut acct, 299), Ok(0));
+        assert_eq!(acct.balance, 6_000_000);
+    }
+}
@@ -1,6 +1,5 @@
 //! Witness(SR, SRP, SRC) related builtin contracts.
 
-use std::collections::HashMap;
 use std::convert::TryFrom;
 
 use ::keys::Address;
@@ -10,6 +9,7 @@ use proto::contract as contract_pb;
 use proto::state::{Votes, Witness};
 use state::keys;
 
+use super::super::super::governance::maintenance::record_vote_change;
 use super::super::super::governance::reward::{RewardController, RewardUtil};
 use super::super::TransactionContext;
 use super::BuiltinContractExecutorExt;
@@ -55,7 +55,7 @@ impl BuiltinContractExecutorExt for contract_pb::WitnessCreateContract {
         let mut owner_acct = manager.state_db.must_get(&keys::Account(owner_address));
         // createWitness
 
-        let witness = Witness {
+        let mut witness = Witness {
             address: owner_address.as_bytes().to_vec(),
             url: unsafe { String::from_utf8_unchecked(self.url.clone()) },
             vote_count: 0,
@@ -64,14 +64,17 @@ impl BuiltinContractExecutorExt for contract_pb::WitnessCreateContract {
             is_active: false,
             ..Default::default()
         };
+        // NOTE: Being a witness is implied by the Witness store, so there's no `setIsWitness`.
+        // The default witness permission, signing blocks with the owner key.
+        if manager.state_db.must_get(&keys::ChainParameter::AllowMultisig) != 0 {
+            witness.signature_key = owner_address.as_bytes().to_vec();
+        }
 
         manager
             .state_db
             .put_key(keys::Witness(owner_address), witness)
             .map_err(|_| "db insert error")?;
 
-        // TODO: setIsWitness for account,  getAllowMultiSign for witness permission
-
         owner_acct.adjust_balance(-ctx.contract_fee).unwrap();
         manager.add_to_blackhole(ctx.contract_fee).unwrap();
         manager
@@ -89,8 +92,7 @@ impl BuiltinContractExecutorExt for contract_pb::WitnessCreateContract {
 
 // Vote for witnesses.
 //
-// NOTE: The implementation is different from java-tron.
-// The new votes will be directely counted and save to Witness store.
+// The vote change is recorded, and counted to Witness store at next maintenance, the same as java-tron.
 // The current effective vote count is saved in WitnessSchedule.
 impl BuiltinContractExecutorExt for contract_pb::VoteWitnessContract {
     fn validate(&self, manager: &Manager, _ctx: &mut TransactionContext) -> Result<(), String> {
@@ -150,39 +152,8 @@ impl BuiltinContractExecutorExt for contract_pb::VoteWitnessContract {
         // delegationService.withdrawReward(ownerAddress);
         RewardController::new(manager).withdraw_reward(owner_addr)?;
 
-        let mut votes_diff: HashMap<Address, i64> = HashMap::new();
-
-        let votes_key = keys::Votes(owner_addr);
-        let old_votes = manager
-            .state_db
-            .get(&votes_key)
-            .map_err(|_| "db query error")?
-            .unwrap_or_default();
-
-        if old_votes.votes != self.votes {
-            for vote in old_votes.votes {
-                // NOTE: This is a bug in java-tron, duplicated vote addresses is not checked.
-                // So `insert()` must not be used here.
-                *votes_diff.entry(*Address::from_bytes(&vote.vote_address)).or_default() -= vote.vote_count;
-            }
-
-            for vote in &self.votes {
-                *votes_diff.entry(*Address::from_bytes(&vote.vote_address)).or_default() += vote.vote_count;
-            }
-        }
-
-        // Save votes.
-        for (addr, count_diff) in votes_diff {
-            if count_diff != 0 {
-                let mut wit = manager.state_db.must_get(&keys::Witness(addr));
-                wit.vote_count += count_diff;
-
-                manager
-                    .state_db
-                    .put_key(keys::Witness(addr), wit)
-                    .map_err(|_| "db insert error")?;
-            }
-        }
+        // Counted at next maintenance.
+        record_vote_change(manager, owner_addr, self.votes.clone())?;
 
         let epoch = manager.state_db.must_get(&keys::DynamicProperty::CurrentEpoch);
         manager
@@ -195,10 +166,6 @@ impl BuiltinContractExecutorExt for contract_pb::VoteWitnessContract {
                 },
             )
             .map_err(|_| "db insert error")?;
-        manager
-            .state_db
-            .put_key(keys::DynamicProperty::HasNewVotesInCurrentEpoch, 1)
-            .map_err(|_| "db insert error")?;
 
         Ok(TransactionResult::success())
     }
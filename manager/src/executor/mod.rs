@@ -37,6 +37,7 @@ pub struct TransactionContext<'a> {
     pub new_account_created: bool,
     pub withdrawal_amount: i64,
     pub unfrozen_amount: i64,
+    pub withdrawal_expire_amount: i64,
     pub fee_limit: i64,
     pub energy: i64,
     pub energy_limit: i64,
@@ -63,6 +64,7 @@ impl<'a> TransactionContext<'a> {
             new_account_created: false,
             withdrawal_amount: 0,
             unfrozen_amount: 0,
+            withdrawal_expire_amount: 0,
             fee_limit: transaction.raw.raw_data.as_ref().unwrap().fee_limit,
             // will be filled while validating
             energy: 0,
@@ -87,6 +89,7 @@ impl<'a> TransactionContext<'a> {
             new_account_created: false,
             withdrawal_amount: 0,
             unfrozen_amount: 0,
+            withdrawal_expire_amount: 0,
             fee_limit: 1000_000_000,
             // will be filled while validating
             energy: 0,
@@ -134,6 +137,7 @@ impl From<TransactionContext<'_>> for TransactionReceipt {
         // misc
         receipt.withdrawal_amount = ctx.withdrawal_amount;
         receipt.unfrozen_amount = ctx.unfrozen_amount;
+        receipt.withdrawal_expire_amount = ctx.withdrawal_expire_amount;
 
         receipt
     }
@@ -149,6 +153,7 @@ impl ::std::fmt::Debug for TransactionContext<'_> {
             .field("multisig_fee", &self.multisig_fee)
             .field("withdrawal_amount", &self.withdrawal_amount)
             .field("unfrozen_amount", &self.unfrozen_amount)
+            .field("withdrawal_expire_amount", &self.withdrawal_expire_amount)
             .field("new_account_created", &self.new_account_created);
 
         // smart contract
@@ -337,6 +342,51 @@ impl<'m> TransactionExecutor<'m> {
                 cntr.validate(self.manager, ctx)?;
                 BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
             }
+            ContractType::FreezeBalanceV2Contract => {
+                let cntr = contract_pb::FreezeBalanceV2Contract::from_any(
+                    cntr.parameter.as_ref().ok_or_else(|| "empty inner Any pb".to_owned())?,
+                )
+                .ok_or_else(|| "invalid inner Any pb".to_owned())?;
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+            }
+            ContractType::UnfreezeBalanceV2Contract => {
+                let cntr = contract_pb::UnfreezeBalanceV2Contract::from_any(
+                    cntr.parameter.as_ref().ok_or_else(|| "empty inner Any pb".to_owned())?,
+                )
+                .ok_or_else(|| "invalid inner Any pb".to_owned())?;
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+            }
+            ContractType::WithdrawExpireUnfreezeContract => {
+                let cntr = contract_pb::WithdrawExpireUnfreezeContract::from_any(
+                    cntr.parameter.as_ref().ok_or_else(|| "empty inner Any pb".to_owned())?,
+                )
+                .ok_or_else(|| "invalid inner Any pb".to_owned())?;
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+            }
+            ContractType::DelegateResourceContract => {
+                let cntr = contract_pb::DelegateResourceContract::from_any(
+                    cntr.parameter.as_ref().ok_or_else(|| "empty inner Any pb".to_owned())?,
+                )
+                .ok_or_else(|| "invalid inner Any pb".to_owned())?;
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+            }
+            ContractType::UnDelegateResourceContract => {
+                let cntr = contract_pb::UnDelegateResourceContract::from_any(
+                    cntr.parameter.as_ref().ok_or_else(|| "empty inner Any pb".to_owned())?,
+                )
+                .ok_or_else(|| "invalid inner Any pb".to_owned())?;
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+            }
             ContractType::VoteWitnessContract => {
                 let cntr = contract_pb::VoteWitnessContract::from_any(
                     cntr.parameter.as_ref().ok_or_else(|| "empty inner Any pb".to_owned())?,
@@ -718,6 +768,80 @@ impl<'m> TransactionExecutor<'m> {
                 BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
                 cntr.execute(self.manager, ctx)
             }
+            ContractType::FreezeBalanceV2Contract => {
+                let cntr = contract_pb::FreezeBalanceV2Contract::from_any(cntr.parameter.as_ref().unwrap()).unwrap();
+
+                debug!(
+                    "=> Freeze Resource V2 {} amount={} resource={:?}",
+                    b58encode_check(cntr.owner_address()),
+                    cntr.frozen_balance,
+                    ResourceCode::from_i32(cntr.resource)
+                );
+
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+                cntr.execute(self.manager, ctx)
+            }
+            ContractType::UnfreezeBalanceV2Contract => {
+                let cntr = contract_pb::UnfreezeBalanceV2Contract::from_any(cntr.parameter.as_ref().unwrap()).unwrap();
+
+                debug!(
+                    "=> Unfreeze Resource V2 {} amount={} resource={:?}",
+                    b58encode_check(cntr.owner_address()),
+                    cntr.unfreeze_balance,
+                    ResourceCode::from_i32(cntr.resource)
+                );
+
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+                cntr.execute(self.manager, ctx)
+            }
+            ContractType::WithdrawExpireUnfreezeContract => {
+                let cntr =
+                    contract_pb::WithdrawExpireUnfreezeContract::from_any(cntr.parameter.as_ref().unwrap()).unwrap();
+
+                debug!("=> Withdraw Expire Unfreeze {}", b58encode_check(cntr.owner_address()));
+
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+                cntr.execute(self.manager, ctx)
+            }
+            ContractType::DelegateResourceContract => {
+                let cntr = contract_pb::DelegateResourceContract::from_any(cntr.parameter.as_ref().unwrap()).unwrap();
+
+                debug!(
+                    "=> Delegate Resource {} receiver={} amount={} resource={:?} lock={}",
+                    b58encode_check(cntr.owner_address()),
+                    b58encode_check(&cntr.receiver_address),
+                    cntr.balance,
+                    ResourceCode::from_i32(cntr.resource),
+                    cntr.lock
+                );
+
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+                cntr.execute(self.manager, ctx)
+            }
+            ContractType::UnDelegateResourceContract => {
+                let cntr = contract_pb::UnDelegateResourceContract::from_any(cntr.parameter.as_ref().unwrap()).unwrap();
+
+                debug!(
+                    "=> UnDelegate Resource {} receiver={} amount={} resource={:?}",
+                    b58encode_check(cntr.owner_address()),
+                    b58encode_check(&cntr.receiver_address),
+                    cntr.balance,
+                    ResourceCode::from_i32(cntr.resource)
+                );
+
+                cntr.validate_signature(permission_id, recover_addrs, self.manager, ctx)?;
+                cntr.validate(self.manager, ctx)?;
+                BandwidthProcessor::new(self.manager, txn, &cntr)?.consume(ctx)?;
+                cntr.execute(self.manager, ctx)
+            }
             ContractType::VoteWitnessContract => {
                 let cntr = contract_pb::VoteWitnessContract::from_any(cntr.parameter.as_ref().unwrap()).unwrap();
 
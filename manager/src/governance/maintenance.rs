@@ -4,7 +4,9 @@ use ::keys::Address;
 use chain::IndexedBlock;
 use chrono::Utc;
 use log::{debug, info};
-use proto::state::{Witness, WitnessVoterReward};
+use proto::common::Vote;
+use proto::state::{VoteChange, WitnessVoterReward};
+use state::db::StateDB;
 use state::keys;
 
 use crate::Manager;
@@ -87,6 +89,8 @@ impl MaintenanceManager<'_> {
             info!("power of GR gets removed");
         }
 
+        let votes = self.count_votes()?;
+        // NOTE: Set by older versions, which counted votes to Witness store directly.
         let has_new_votes = self
             .manager
             .state_db
@@ -97,8 +101,7 @@ impl MaintenanceManager<'_> {
 
         // NOTE: RemovePowerOfGr won't trigger an SR re-scheduling. This is a bad design flaw in java-tron.
         // Re-scheduling is only triggered iff new votes in current epoch.
-        if has_new_votes {
-            let votes = self.count_votes()?;
+        if !votes.is_empty() || has_new_votes {
             // reset vote status
             self.manager
                 .state_db
@@ -107,21 +110,20 @@ impl MaintenanceManager<'_> {
 
             let old_active_witnesses = self.manager.get_active_witnesses();
 
-            // FIXME: handle votes, unvotes
-            let mut witnesses: HashMap<Address, Witness> = HashMap::new();
-            for (&wit_addr, &vote_count) in votes.iter() {
-                let mut wit = witnesses
-                    .entry(wit_addr)
-                    .or_insert_with(|| self.manager.state_db.must_get(&keys::Witness(wit_addr)));
-                wit.vote_count = vote_count;
-            }
-
-            for (wit_addr, wit) in witnesses.into_iter() {
-                // debug!("witness {} vote = {}", wit_addr, wit.vote_count);
-                self.manager
+            for (wit_addr, vote_count) in votes.into_iter() {
+                let maybe_witness = self
+                    .manager
                     .state_db
-                    .put_key(keys::Witness(wit_addr), wit)
-                    .map_err(|_| "db insert error")?;
+                    .get(&keys::Witness(wit_addr))
+                    .map_err(|_| "db query error")?;
+                if let Some(mut wit) = maybe_witness {
+                    // debug!("witness {} vote = {} + {}", wit_addr, wit.vote_count, vote_count);
+                    wit.vote_count += vote_count;
+                    self.manager
+                        .state_db
+                        .put_key(keys::Witness(wit_addr), wit)
+                        .map_err(|_| "db insert error")?;
+                }
             }
 
             self.update_witness_schedule();
@@ -153,19 +155,29 @@ impl MaintenanceManager<'_> {
         Ok(())
     }
 
-    /// Executive vote counting.
+    /// Executive vote counting, returns vote count changes of witnesses.
     ///
-    /// NOTE: The implementation is different from java-tron.
-    /// The votes are already counted and saved in Witness store.
-    fn count_votes(&self) -> Result<HashMap<Address, i64>, String> {
-        let mut votes: HashMap<Address, i64> = HashMap::new();
+    /// Vote changes of current epoch are consumed, the same as `VotesStore` in java-tron.
+    fn count_votes(&mut self) -> Result<HashMap<Address, i64>, String> {
+        let mut voters: Vec<Address> = Vec::new();
+        let mut changes: Vec<VoteChange> = Vec::new();
         {
-            let votes = &mut votes;
-            self.manager.state_db.for_each(move |_key: &keys::Witness, wit| {
-                votes.insert(*Address::from_bytes(&wit.address), wit.vote_count);
-            });
+            let voters = &mut voters;
+            let changes = &mut changes;
+            self.manager
+                .state_db
+                .for_each(move |key: &keys::VoteChange, change: &VoteChange| {
+                    voters.push(key.0);
+                    changes.push(change.clone());
+                });
         }
-        Ok(votes)
+        for voter in voters {
+            self.manager
+                .state_db
+                .delete_key(&keys::VoteChange(voter))
+                .map_err(|_| "db delete error")?;
+        }
+        Ok(count_vote_changes(&changes))
     }
 
     // in DynamicPropertiesStore.java
@@ -222,28 +234,8 @@ impl MaintenanceManager<'_> {
     }
 
     // DposService.updateWitness
-    fn update_witness_schedule(&mut self) {
-        let mut wit_sched: Vec<(Address, i64, u8)> = Vec::new();
-        {
-            let wit_sched = &mut wit_sched;
-            self.manager.state_db.for_each(move |key: &keys::Witness, value| {
-                wit_sched.push((key.0, value.vote_count, value.brokerage as u8));
-            });
-        }
-
-        // NOTE: This is different from java-tron. In OpenTron, raw address is used as final fallback sorting key.
-        wit_sched.sort_by_cached_key(|&(addr, vote_count, _)| {
-            (
-                vote_count,
-                java_bytestring_hash_code(addr.as_bytes()),
-                addr.as_bytes().to_vec(),
-            )
-        });
-        wit_sched.reverse();
-        if wit_sched.len() > constants::MAX_NUM_OF_STANDBY_WITNESSES {
-            let _ = wit_sched.split_off(constants::MAX_NUM_OF_STANDBY_WITNESSES);
-        }
-
+    pub(crate) fn update_witness_schedule(&mut self) {
+        let wit_sched = sorted_witnesses(&self.manager.state_db);
         self.manager.state_db.put_key(keys::WitnessSchedule, wit_sched).unwrap();
     }
 
@@ -279,6 +271,54 @@ impl MaintenanceManager<'_> {
     }
 }
 
+/// Record vote change of an account, to be counted at next maintenance.
+///
+/// Must be called before the account's `Votes` are updated, which are kept as old votes at the first change of
+/// the epoch.
+pub(crate) fn record_vote_change(
+    manager: &mut Manager,
+    owner_addr: Address,
+    new_votes: Vec<Vote>,
+) -> Result<(), String> {
+    let maybe_change = manager
+        .state_db
+        .get(&keys::VoteChange(owner_addr))
+        .map_err(|_| "db query error")?;
+    let mut change = match maybe_change {
+        Some(change) => change,
+        None => VoteChange {
+            old_votes: manager
+                .state_db
+                .get(&keys::Votes(owner_addr))
+                .map_err(|_| "db query error")?
+                .map(|votes| votes.votes)
+                .unwrap_or_default(),
+            ..Default::default()
+        },
+    };
+    change.new_votes = new_votes;
+    manager
+        .state_db
+        .put_key(keys::VoteChange(owner_addr), change)
+        .map_err(|_| "db insert error")
+}
+
+/// `countVote` in java-tron. Old votes are withdrawn, and new votes are added.
+///
+/// NOTE: Duplicated vote addresses are counted multiple times, the same as java-tron.
+fn count_vote_changes(changes: &[VoteChange]) -> HashMap<Address, i64> {
+    let mut votes: HashMap<Address, i64> = HashMap::new();
+    for change in changes {
+        for vote in &change.old_votes {
+            *votes.entry(*Address::from_bytes(&vote.vote_address)).or_default() -= vote.vote_count;
+        }
+        for vote in &change.new_votes {
+            *votes.entry(*Address::from_bytes(&vote.vote_address)).or_default() += vote.vote_count;
+        }
+    }
+    votes
+}
+
 /// `hashCode()` for `com.google.protobuf.ByteString`.
 ///
 /// NOTE: This is a really bad design flaw in java-tron, and is still vulnerable.
@@ -317,9 +357,267 @@ fn java_bytestring_hash_code(bs: &[u8]) -> i32 {
     }
 }
 
+/// All witnesses sorted by vote count, at most `MAX_NUM_OF_STANDBY_WITNESSES` of them.
+pub(crate) fn sorted_witnesses(state_db: &StateDB) -> Vec<(Address, i64, u8)> {
+    let mut wit_sched: Vec<(Address, i64, u8)> = Vec::new();
+    {
+        let wit_sched = &mut wit_sched;
+        state_db.for_each(move |key: &keys::Witness, value| {
+            wit_sched.push((key.0, value.vote_count, value.brokerage as u8));
+        });
+    }
+
+    // NOTE: This is different from java-tron. In OpenTron, raw address is used as final fallback sorting key.
+    wit_sched.sort_by_cached_key(|&(addr, vote_count, _)| {
+        (
+            vote_count,
+            java_bytestring_hash_code(addr.as_bytes()),
+            addr.as_bytes().to_vec(),
+        )
+    });
+    wit_sched.reverse();
+    if wit_sched.len() > constants::MAX_NUM_OF_STANDBY_WITNESSES {
+        let _ = wit_sched.split_off(constants::MAX_NUM_OF_STANDBY_WITNESSES);
+    }
+    wit_sched
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{addr, new_test_manager, new_test_manager_with_genesis, run_contract, GR1, GR2, GR3};
+    use config::genesis::GenesisConfig;
+    use proto::common::ResourceCode;
+    use proto::contract as contract_pb;
+    use proto::state::{Account, Votes};
+
+    const VOTER1: &str = "TALSWqjhNCaXi5YWPh9DvZgvtYRSfqVUwq";
+    const VOTER2: &str = "TARkPnaSRKSg6ZAUbJGMGvBstELj7VS3Br";
+
+    fn vote(wit: &str, vote_count: i64) -> Vote {
+        Vote {
+            vote_address: addr(wit).as_bytes().to_vec(),
+            vote_count,
+        }
+    }
+
+    // VoteWitnessContract, without reward withdrawal.
+    fn vote_witness(manager: &mut Manager, voter: &str, votes: Vec<Vote>) {
+        record_vote_change(manager, addr(voter), votes.clone()).unwrap();
+        manager.state_db.put_key(keys::Votes(addr(voter)), Votes { epoch: 0, votes }).unwrap();
+    }
+
+    // Votes cleared by UnfreezeBalanceContract.
+    fn clear_votes(manager: &mut Manager, voter: &str) {
+        record_vote_change(manager, addr(voter), vec![]).unwrap();
+        manager.state_db.delete_key(&keys::Votes(addr(voter))).unwrap();
+    }
+
+    fn vote_count(manager: &Manager, wit: &str) -> i64 {
+        manager.state_db.must_get(&keys::Witness(addr(wit))).vote_count
+    }
+
+    fn maintain(manager: &mut Manager) {
+        MaintenanceManager::new(manager).do_maintenance().unwrap();
+    }
+
+    #[test]
+    fn votes_are_counted_at_maintenance() {
+        let mut manager = new_test_manager();
+        assert_eq!(manager.get_active_witnesses(), vec![addr(GR1), addr(GR2), addr(GR3)]);
+
+        vote_witness(&mut manager, VOTER1, vec![vote(GR3, 30_000_000)]);
+        vote_witness(&mut manager, VOTER2, vec![vote(GR2, 5_000_000), vote(GR3, 1_000_000)]);
+        assert_eq!(vote_count(&manager, GR3), 80_000_000);
+
+        maintain(&mut manager);
+        assert_eq!(vote_count(&manager, GR1), 100_000_000);
+        assert_eq!(vote_count(&manager, GR2), 95_000_000);
+        assert_eq!(vote_count(&manager, GR3), 111_000_000);
+        assert_eq!(manager.get_active_witnesses(), vec![addr(GR3), addr(GR1), addr(GR2)]);
+        assert!(manager.state_db.get(&keys::VoteChange(addr(VOTER1))).unwrap().is_none());
+
+        // nothing changed in next epoch
+        maintain(&mut manager);
+        assert_eq!(vote_count(&manager, GR3), 111_000_000);
+    }
+
+    #[test]
+    fn only_last_votes_of_epoch_are_counted() {
+        let mut manager = new_test_manager();
+
+        vote_witness(&mut manager, VOTER1, vec![vote(GR2, 10_000_000)]);
+        maintain(&mut manager);
+        assert_eq!(vote_count(&manager, GR2), 100_000_000);
+
+        vote_witness(&mut manager, VOTER1, vec![vote(GR3, 5_000_000)]);
+        vote_witness(&mut manager, VOTER1, vec![vote(GR1, 3_000_000)]);
+        clear_votes(&mut manager, VOTER1);
+        vote_witness(&mut manager, VOTER1, vec![vote(GR3, 7_000_000)]);
+        assert_eq!(
+            manager.state_db.must_get(&keys::VoteChange(addr(VOTER1))).old_votes,
+            vec![vote(GR2, 10_000_000)]
+        );
+
+        maintain(&mut manager);
+        assert_eq!(vote_count(&manager, GR1), 100_000_000);
+        assert_eq!(vote_count(&manager, GR2), 90_000_000);
+        assert_eq!(vote_count(&manager, GR3), 87_000_000);
+    }
+
+    #[test]
+    fn duplicated_vote_addresses_are_counted() {
+        let mut manager = new_test_manager();
+
+        vote_witness(&mut manager, VOTER1, vec![vote(GR3, 5), vote(GR3, 5)]);
+        maintain(&mut manager);
+        assert_eq!(vote_count(&manager, GR3), 80_000_010);
+
+        vote_witness(&mut manager, VOTER1, vec![vote(GR3, 1)]);
+        maintain(&mut manager);
+        assert_eq!(vote_count(&manager, GR3), 80_000_001);
+    }
+
+    #[test]
+    fn power_of_gr_is_removed_before_counting() {
+        let mut manager = new_test_manager();
+        manager.state_db.put_key(keys::ChainParameter::RemovePowerOfGr, 1).unwrap();
+
+        vote_witness(&mut manager, VOTER1, vec![vote(GR1, 2_000_000), vote(GR3, 1_000_000)]);
+        maintain(&mut manager);
+        assert_eq!(manager.state_db.must_get(&keys::ChainParameter::RemovePowerOfGr), -1);
+        assert_eq!(vote_count(&manager, GR1), 2_000_000);
+        assert_eq!(vote_count(&manager, GR2), 0);
+        assert_eq!(vote_count(&manager, GR3), 1_000_000);
+        assert_eq!(manager.get_active_witnesses(), vec![addr(GR1), addr(GR3), addr(GR2)]);
+    }
+
+    /// A transaction or chain event of a scripted vote sequence.
+    enum Op {
+        /// Freeze bandwidth for 3 days, in TRX.
+        Freeze(&'static str, i64),
+        Vote(&'static str, &'static [(&'static str, i64)]),
+        Unfreeze(&'static str),
+        /// Move the latest block timestamp forward, in days.
+        Wait(i64),
+        Maintain,
+    }
+
+    /// Replay operations through the actuators, as they are applied in blocks.
+    fn replay(manager: &mut Manager, ops: &[Op]) {
+        const DAY_IN_MS: i64 = 86_400_000;
+
+        for op in ops {
+            match *op {
+                Op::Freeze(owner, amount) => {
+                    let cntr = contract_pb::FreezeBalanceContract {
+                        owner_address: addr(owner).as_bytes().to_vec(),
+                        frozen_balance: amount * 1_000_000,
+                        frozen_duration: 3,
+                        resource: ResourceCode::Bandwidth as i32,
+                        ..Default::default()
+                    };
+                    run_contract(manager, cntr).unwrap();
+                }
+                Op::Vote(owner, votes) => {
+                    let cntr = contract_pb::VoteWitnessContract {
+                        owner_address: addr(owner).as_bytes().to_vec(),
+                        votes: votes.iter().map(|&(wit, count)| vote(wit, count)).collect(),
+                        ..Default::default()
+                    };
+                    run_contract(manager, cntr).unwrap();
+                }
+                Op::Unfreeze(owner) => {
+                    let cntr = contract_pb::UnfreezeBalanceContract {
+                        owner_address: addr(owner).as_bytes().to_vec(),
+                        resource: ResourceCode::Bandwidth as i32,
+                        ..Default::default()
+                    };
+                    run_contract(manager, cntr).unwrap();
+                }
+                Op::Wait(days) => {
+                    let now = manager.latest_block_timestamp();
+                    manager
+                        .state_db
+                        .put_key(keys::DynamicProperty::LatestBlockTimestamp, now + days * DAY_IN_MS)
+                        .unwrap();
+                }
+                Op::Maintain => maintain(manager),
+            }
+        }
+    }
+
+    // Witnesses of the mainnet genesis, ranked by genesis votes.
+    const MAINNET_GR1: &str = "THKJYuUmMKKARNf7s2VT51g5uPY6KEqnat";
+    const MAINNET_GR2: &str = "TVDmPWGYxgi5DNeW8hXrzrhY8Y6zgxPNg4";
+    const MAINNET_GR3: &str = "TWKZN1JJPFydd5rMgMCV5aZTSiwmoksSZv";
+    const MAINNET_GR4: &str = "TDarXEG2rAD57oa7JTK785Yb2Et32UzY32";
+    const MAINNET_GR25: &str = "TRMP6SKeFUt5NtMLzJv8kdpYuHRnEGjGfe";
+    const MAINNET_GR26: &str = "TDbNE1VajxjpgM5p7FyGNDASt3UVoFbiD3";
+    const MAINNET_GR27: &str = "TLTDZBcPoJ8tZ6TTEeEqEvwYFk2wgotSfD";
+    const VOTER3: &str = "TN21Wx2yoNYiZ7znuQonmZMJnH5Vdfxu78";
+    // Allocated in the mainnet genesis.
+    const VOTER4: &str = "TLLM21wteSPs4hKjbxgmH1L6poyMjeTbHm";
+
+    /// A synthetic vote sequence over the mainnet genesis: votes, re-votes and unfreezes of the same epoch, then
+    /// unfreezing in the next epoch.
+    ///
+    /// NOTE: Only the witness set and genesis votes are taken from mainnet. The voters, amounts and ordering are
+    /// made up to cover vote changes within an epoch, they are not recorded mainnet transactions.
+    const SYNTHETIC_EPOCHS: &[Op] = &[
+        Op::Freeze(VOTER4, 100),
+        Op::Vote(VOTER4, &[(MAINNET_GR27, 30)]),
+        Op::Freeze(VOTER3, 50),
+        Op::Vote(VOTER3, &[(MAINNET_GR26, 20)]),
+        Op::Vote(VOTER4, &[(MAINNET_GR25, 60), (MAINNET_GR27, 40)]),
+        Op::Freeze(VOTER1, 10),
+        Op::Vote(VOTER1, &[(MAINNET_GR1, 10)]),
+        Op::Freeze(VOTER2, 10),
+        Op::Vote(VOTER2, &[(MAINNET_GR2, 3), (MAINNET_GR3, 7)]),
+        Op::Wait(3),
+        Op::Unfreeze(VOTER1),
+        Op::Unfreeze(VOTER3),
+        Op::Vote(VOTER2, &[(MAINNET_GR2, 10)]),
+        Op::Maintain,
+    ];
+
+    #[test]
+    fn synthetic_votes_over_mainnet_genesis() {
+        let genesis = GenesisConfig::load_from_str(include_str!("../../../etc/genesis.json")).unwrap();
+        let mut manager = new_test_manager_with_genesis(&genesis);
+        for &voter in &[VOTER1, VOTER2, VOTER3] {
+            let acct = Account {
+                balance: 100_000_000,
+                ..Default::default()
+            };
+            manager.state_db.put_key(keys::Account(addr(voter)), acct).unwrap();
+        }
+        assert_eq!(manager.get_active_witnesses()[0], addr(MAINNET_GR1));
+
+        replay(&mut manager, SYNTHETIC_EPOCHS);
+        assert_eq!(vote_count(&manager, MAINNET_GR1), 100_000_026);
+        assert_eq!(vote_count(&manager, MAINNET_GR2), 100_000_035);
+        assert_eq!(vote_count(&manager, MAINNET_GR3), 100_000_024);
+        assert_eq!(vote_count(&manager, MAINNET_GR25), 100_000_062);
+        assert_eq!(vote_count(&manager, MAINNET_GR26), 100_000_001);
+        assert_eq!(vote_count(&manager, MAINNET_GR27), 100_000_040);
+        assert_eq!(
+            manager.get_active_witnesses()[..6],
+            [
+                addr(MAINNET_GR25),
+                addr(MAINNET_GR27),
+                addr(MAINNET_GR2),
+                addr(MAINNET_GR1),
+                addr(MAINNET_GR3),
+                addr(MAINNET_GR4)
+            ]
+        );
+
+        replay(&mut manager, &[Op::Unfreeze(VOTER2), Op::Maintain]);
+        assert_eq!(vote_count(&manager, MAINNET_GR2), 100_000_025);
+        assert_eq!(vote_count(&manager, MAINNET_GR25), 100_000_062);
+        assert_eq!(manager.get_active_witnesses().len(), 27);
+    }
 
     #[test]
     fn test_java_bytestring_hash_code() {
@@ -1,5 +1,7 @@
 //! Proposal controller and validator.
 
+use std::collections::BTreeMap;
+
 use ::keys::Address;
 use constants::block_version::BlockVersion;
 use log::{debug, info};
@@ -61,18 +63,12 @@ impl ProposalController<'_> {
     }
 
     fn process_proposal(&mut self, mut proposal: Proposal) -> Result<(), String> {
-        let active_witnesses = self.manager.get_active_witnesses();
-        if active_witnesses.len() != constants::MAX_NUM_OF_ACTIVE_WITNESSES {
-            info!("current number of active witnesses: {}", active_witnesses.len());
+        let num_active_witnesses = self.manager.get_active_witnesses().len();
+        if num_active_witnesses != constants::MAX_NUM_OF_ACTIVE_WITNESSES {
+            info!("current number of active witnesses: {}", num_active_witnesses);
         }
-        let approval_count = proposal
-            .approver_addresses
-            .iter()
-            .filter(|addr| active_witnesses.contains(Address::from_bytes(addr)))
-            .count();
 
-        // 70% approvals
-        if approval_count >= active_witnesses.len() * constants::SOLID_THRESHOLD_PERCENT / 100 {
+        if ProposalUtil::new(self.manager).is_approved(&proposal) {
             info!(
                 "proposal #{} passed, parameters: {:?}",
                 proposal.proposal_id, proposal.parameters
@@ -115,6 +111,62 @@ impl ProposalUtil<'_> {
         ProposalUtil { manager }
     }
 
+    /// Number of approvals from current active witnesses.
+    pub fn approval_count(&self, proposal: &Proposal) -> usize {
+        let active_witnesses = self.manager.get_active_witnesses();
+        proposal
+            .approver_addresses
+            .iter()
+            .filter(|addr| active_witnesses.contains(Address::from_bytes(addr)))
+            .count()
+    }
+
+    /// Number of approvals required to pass a proposal, 70% of active witnesses.
+    pub fn required_approval_count(&self) -> usize {
+        self.manager.get_active_witnesses().len() * constants::SOLID_THRESHOLD_PERCENT / 100
+    }
+
+    /// Would the proposal be approved if counted now?
+    pub fn is_approved(&self, proposal: &Proposal) -> bool {
+        self.approval_count(proposal) >= self.required_approval_count()
+    }
+
+    /// Parameters to be changed at next maintenance, by pending proposals that expire before it and have enough
+    /// approvals for now.
+    ///
+    /// Returns `(parameter, value, proposal_id)`, sorted by parameter. When more than one proposal sets the same
+    /// parameter, the one applied last in `ProposalController::process_proposals` wins.
+    pub fn pending_parameters(&self) -> Vec<(ChainParameter, i64, i64)> {
+        let latest_proposal_id = self.manager.state_db.must_get(&keys::DynamicProperty::LatestProposalId);
+        let next_maintenance_time = self
+            .manager
+            .state_db
+            .must_get(&keys::DynamicProperty::NextMaintenanceTime);
+
+        let mut params = BTreeMap::new();
+        // NOTE: the same order as `process_proposals`
+        for proposal_id in (1..=latest_proposal_id).rev() {
+            let proposal = self.manager.state_db.must_get(&keys::Proposal(proposal_id));
+            if proposal.is_processed() {
+                break;
+            }
+            if proposal.is_cancelled() || proposal.expiration_time > next_maintenance_time {
+                continue;
+            }
+            if self.is_approved(&proposal) {
+                for (&param, &value) in proposal.parameters.iter() {
+                    if let Some(param) = ChainParameter::from_i32(param as i32) {
+                        params.insert(param, (value, proposal_id));
+                    }
+                }
+            }
+        }
+        params
+            .into_iter()
+            .map(|(param, (value, proposal_id))| (param, value, proposal_id))
+            .collect()
+    }
+
     pub fn validate(self, key: i64, value: i64) -> Result<(), String> {
         use ChainParameter::*;
 
@@ -235,6 +287,10 @@ impl ProposalUtil<'_> {
                 self.require_version(BlockVersion::GreatVoyage4_1_2)?;
                 self.accept_bool(value)
             }
+            UnfreezeDelayDays => {
+                self.require_version(BlockVersion::GreatVoyage4_7_0)?;
+                self.accept_range_value(value, 1, 365)
+            }
             // NOTE: In nile's branch, this is wrongly marked as 4.0.
             //
             // See-also: https://github.com/tronprotocol/java-tron/pull/3372
@@ -292,3 +348,90 @@ impl ProposalUtil<'_> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{new_test_manager, GR1, GR2, GR3};
+
+    fn create_proposal(
+        manager: &mut Manager,
+        params: &[(ChainParameter, i64)],
+        expiration_time: i64,
+        approvers: &[&str],
+    ) {
+        let proposal_id = manager.state_db.must_get(&keys::DynamicProperty::LatestProposalId) + 1;
+        let proposal = Proposal {
+            proposal_id,
+            proposer_address: GR1.parse::<Address>().unwrap().as_bytes().to_vec(),
+            parameters: params.iter().map(|&(param, value)| (param as i64, value)).collect(),
+            expiration_time,
+            approver_addresses: approvers
+                .iter()
+                .map(|addr| addr.parse::<Address>().unwrap().as_bytes().to_vec())
+                .collect(),
+            ..Default::default()
+        };
+        manager.state_db.put_key(keys::Proposal(proposal_id), proposal).unwrap();
+        manager
+            .state_db
+            .put_key(keys::DynamicProperty::LatestProposalId, proposal_id)
+            .unwrap();
+    }
+
+    #[test]
+    fn pending_parameters_are_applied_at_maintenance() {
+        let mut manager = new_test_manager();
+        let expiration = manager.state_db.must_get(&keys::DynamicProperty::NextMaintenanceTime);
+
+        // not enough approvals
+        create_proposal(&mut manager, &[(ChainParameter::EnergyPrice, 1)], expiration, &[GR1]);
+        create_proposal(
+            &mut manager,
+            &[(ChainParameter::EnergyPrice, 140), (ChainParameter::AllowTvm, 1)],
+            expiration,
+            &[GR1, GR2, GR3],
+        );
+        // applied before #2, overwritten
+        create_proposal(
+            &mut manager,
+            &[(ChainParameter::EnergyPrice, 120)],
+            expiration,
+            &[GR1, GR3],
+        );
+        // not expired yet
+        create_proposal(
+            &mut manager,
+            &[(ChainParameter::BandwidthPrice, 40)],
+            expiration + 1,
+            &[GR1, GR2, GR3],
+        );
+
+        let util = ProposalUtil::new(&manager);
+        assert_eq!(util.required_approval_count(), 2);
+        let pending = util.pending_parameters();
+        assert_eq!(
+            pending,
+            vec![(ChainParameter::AllowTvm, 1, 2), (ChainParameter::EnergyPrice, 140, 2)]
+        );
+
+        ProposalController::new(&mut manager).process_proposals().unwrap();
+        for (param, value, _) in pending {
+            assert_eq!(manager.state_db.must_get(&param), value);
+        }
+        assert_ne!(manager.state_db.must_get(&ChainParameter::BandwidthPrice), 40);
+        let states: Vec<_> = (1..=4)
+            .map(|id| manager.state_db.must_get(&keys::Proposal(id)).state)
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ProposalState::Disapproved as i32,
+                ProposalState::Approved as i32,
+                ProposalState::Approved as i32,
+                ProposalState::Pending as i32,
+            ]
+        );
+        assert!(ProposalUtil::new(&manager).pending_parameters().is_empty());
+    }
+}
@@ -49,6 +49,13 @@ impl RewardController<'_> {
             0.0
         };
 
+        // payTransactionFeeReward, the whole pool is paid to the block producer.
+        let fee_reward = if self.manager.allow_transaction_fee_pool() {
+            self.manager.transaction_fee_pool()
+        } else {
+            0
+        };
+
         let epoch = self.manager.state_db.must_get(&keys::DynamicProperty::CurrentEpoch);
         // payReward
         for ((wit_addr, vote_count, brokerage), mut wit_acct) in wit_sched.into_iter().zip(wit_accts.into_iter()) {
@@ -72,6 +79,15 @@ impl RewardController<'_> {
                 if wit_acct.adjust_allowance(brokerage_amount).is_err() {
                     return Err("math overflow while adding brokerage amount".into());
                 }
+
+                // NOTE: Brokerage of fee reward is calculated separately, the same as java-tron.
+                if fee_reward > 0 {
+                    let brokerage_amount = (fee_reward as f64 * brokerage_rate) as i64;
+                    voters_reward += fee_reward - brokerage_amount;
+                    if wit_acct.adjust_allowance(brokerage_amount).is_err() {
+                        return Err("math overflow while adding brokerage amount".into());
+                    }
+                }
             }
             // save reward of voters
             // delegationStore.addReward(cycle, witnessAddress, value);
@@ -82,6 +98,13 @@ impl RewardController<'_> {
                 .map_err(|_| "db insert error")?;
         }
 
+        if fee_reward > 0 {
+            self.manager
+                .state_db
+                .put_key(keys::DynamicProperty::TransactionFeePool, 0)
+                .map_err(|_| "db insert error")?;
+        }
+
         Ok(())
     }
 
@@ -180,8 +203,119 @@ impl RewardUtil<'_> {
         }
     }
 
+    /// Unwithdrawn voting reward of each epoch and each vote, in `(epoch, witness, amount)`.
+    pub fn query_reward_details(&self, addr: Address) -> Result<Vec<(i64, Address, i64)>, String> {
+        let allow_change_delegation = self
+            .manager
+            .state_db
+            .must_get(&keys::ChainParameter::AllowChangeDelegation) !=
+            0;
+        if !allow_change_delegation {
+            return Ok(vec![]);
+        }
+
+        let mut details = vec![];
+        if let Some(votes) = self
+            .manager
+            .state_db
+            .get(&keys::Votes(addr))
+            .map_err(|_| "db query error")?
+        {
+            let curr_epoch = self.manager.state_db.must_get(&keys::DynamicProperty::CurrentEpoch);
+            for epoch in votes.epoch..curr_epoch {
+                for (wit_addr, amount) in self.compute_reward_of_votes(epoch, &votes)? {
+                    details.push((epoch, wit_addr, amount));
+                }
+            }
+        }
+        Ok(details)
+    }
+
+    /// Estimate voting reward of one epoch, for votes newly added to witnesses. Returns the reward of each vote.
+    ///
+    /// Witnesses are ranked by current vote count plus new votes, and are paid with current brokerage.
+    /// Transaction fee reward is not included.
+    pub fn estimate_reward(&self, votes: &[(Address, i64)]) -> Result<Vec<i64>, String> {
+        let allow_change_delegation = self
+            .manager
+            .state_db
+            .must_get(&keys::ChainParameter::AllowChangeDelegation) !=
+            0;
+        if !allow_change_delegation {
+            return Ok(vec![0; votes.len()]);
+        }
+
+        // (address, vote_count, brokerage)
+        let mut witnesses: Vec<(Address, i64, i32)> = vec![];
+        {
+            let witnesses = &mut witnesses;
+            self.manager.state_db.for_each(move |key: &keys::Witness, wit| {
+                witnesses.push((key.0, wit.vote_count, wit.brokerage));
+            });
+        }
+        for &(wit_addr, vote_count) in votes {
+            let wit = witnesses
+                .iter_mut()
+                .find(|wit| wit.0 == wit_addr)
+                .ok_or_else(|| format!("witness {} not found", wit_addr))?;
+            wit.1 = wit
+                .1
+                .checked_add(vote_count)
+                .ok_or("math overflow while adding vote count")?;
+        }
+        witnesses.sort_by(|a, b| b.1.cmp(&a.1));
+        witnesses.truncate(constants::MAX_NUM_OF_STANDBY_WITNESSES);
+
+        let total_votes = witnesses
+            .iter()
+            .try_fold(0_i64, |acc, wit| acc.checked_add(wit.1))
+            .ok_or("math overflow while summing vote count")?;
+        let num_active_witnesses = witnesses.len().min(constants::MAX_NUM_OF_ACTIVE_WITNESSES);
+        let num_blocks = self
+            .manager
+            .state_db
+            .must_get(&keys::ChainParameter::MaintenanceInterval) /
+            constants::BLOCK_PRODUCING_INTERVAL -
+            constants::NUM_OF_SKIPPED_SLOTS_IN_MAINTENANCE as i64;
+        let block_reward = self
+            .manager
+            .state_db
+            .must_get(&keys::ChainParameter::WitnessPayPerBlock);
+        let standby_reward = self
+            .manager
+            .state_db
+            .must_get(&keys::ChainParameter::StandbyWitnessPayPerBlock);
+
+        let rewards = votes
+            .iter()
+            .map(|&(wit_addr, vote_count)| {
+                // Not a standby witness, no reward at all.
+                let rank = match witnesses.iter().position(|wit| wit.0 == wit_addr) {
+                    Some(rank) => rank,
+                    None => return 0,
+                };
+                let (_, wit_vote_count, brokerage) = witnesses[rank];
+                let mut reward = standby_reward as f64 * num_blocks as f64 * vote_count as f64 / total_votes as f64;
+                if rank < num_active_witnesses {
+                    let num_produced_blocks = num_blocks as f64 / num_active_witnesses as f64;
+                    reward += block_reward as f64 * num_produced_blocks * vote_count as f64 / wit_vote_count as f64;
+                }
+                (reward * (1.0 - brokerage as f64 / 100.0)) as i64
+            })
+            .collect();
+        Ok(rewards)
+    }
+
     fn compute_reward(&self, epoch: i64, votes: &Votes) -> Result<i64, String> {
-        let mut reward_amount = 0_i64;
+        Ok(self
+            .compute_reward_of_votes(epoch, votes)?
+            .into_iter()
+            .map(|(_, amount)| amount)
+            .sum())
+    }
+
+    fn compute_reward_of_votes(&self, epoch: i64, votes: &Votes) -> Result<Vec<(Address, i64)>, String> {
+        let mut rewards = vec![];
         for vote in &votes.votes {
             let wit_addr = *Address::from_bytes(&vote.vote_address);
             if let Some(total_reward) = self
@@ -194,9 +328,144 @@ impl RewardUtil<'_> {
                     continue;
                 }
                 let vote_rate = vote.vote_count as f64 / total_reward.vote_count as f64;
-                reward_amount += (vote_rate * total_reward.reward_amount as f64) as i64;
+                rewards.push((wit_addr, (vote_rate * total_reward.reward_amount as f64) as i64));
             }
         }
-        Ok(reward_amount)
+        Ok(rewards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{addr, new_test_manager, GR1, GR2, GR3};
+    use proto::common::Vote;
+    use proto::state::{Account, Witness, WitnessVoterReward};
+
+    const VOTER: &str = "TALSWqjhNCaXi5YWPh9DvZgvtYRSfqVUwq";
+
+    fn allow_change_delegation(manager: &mut Manager) {
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowChangeDelegation, 1)
+            .unwrap();
+    }
+
+    fn set_brokerage(manager: &mut Manager, wit: &str, brokerage: i32) {
+        let mut witness = manager.state_db.must_get(&keys::Witness(addr(wit)));
+        witness.brokerage = brokerage;
+        manager.state_db.put_key(keys::Witness(addr(wit)), witness).unwrap();
+    }
+
+    fn estimate(manager: &Manager, votes: &[(&str, i64)]) -> Result<Vec<i64>, String> {
+        let votes: Vec<_> = votes.iter().map(|&(wit, count)| (addr(wit), count)).collect();
+        RewardUtil::new(manager).estimate_reward(&votes)
+    }
+
+    #[test]
+    fn estimate_reward_of_active_witness() {
+        let mut manager = new_test_manager();
+        assert_eq!(estimate(&manager, &[(GR3, 30_000_000)]), Ok(vec![0]));
+
+        allow_change_delegation(&mut manager);
+        // GR3 becomes the top one of 3 active witnesses, 110_000_000 of 300_000_000 votes.
+        // An epoch of the test config is 198 blocks, 66 of them produced by GR3.
+        // standby: 16_000_000 * 198 * 30_000_000 / 300_000_000 = 316_800_000
+        // block:   32_000_000 * 66 * 30_000_000 / 110_000_000 = 576_000_000
+        set_brokerage(&mut manager, GR3, 0);
+        assert_eq!(estimate(&manager, &[(GR3, 30_000_000)]), Ok(vec![892_800_000]));
+
+        set_brokerage(&mut manager, GR3, 50);
+        assert_eq!(estimate(&manager, &[(GR3, 30_000_000)]), Ok(vec![446_400_000]));
+
+        set_brokerage(&mut manager, GR3, constants::DEFAULT_BROKERAGE_RATE);
+        assert_eq!(estimate(&manager, &[(GR3, 30_000_000)]), Ok(vec![714_240_000]));
+
+        assert!(estimate(&manager, &[(VOTER, 1)]).is_err());
+        assert!(estimate(&manager, &[(GR1, i64::max_value())]).is_err());
+    }
+
+    #[test]
+    fn estimate_reward_of_standby_witness() {
+        let mut manager = new_test_manager();
+        allow_change_delegation(&mut manager);
+
+        // 126 witnesses ahead of genesis witnesses, leaving only one standby seat to GR1.
+        for i in 0..126 {
+            let wit_addr = Address::from_tvm_bytes(&[i as u8 + 1; 20]);
+            let wit = Witness {
+                address: wit_addr.as_bytes().to_vec(),
+                vote_count: 200_000_000,
+                ..Default::default()
+            };
+            manager.state_db.put_key(keys::Witness(wit_addr), wit).unwrap();
+        }
+
+        // GR1 is ranked 127th with 144_000_000 votes, no block reward.
+        // standby: 16_000_000 * 198 * 44_000_000 / (126 * 200_000_000 + 144_000_000) = 5_500_000
+        // GR3 is still behind GR2, out of the standby witnesses.
+        assert_eq!(
+            estimate(&manager, &[(GR1, 44_000_000), (GR3, 1_000_000)]),
+            Ok(vec![4_400_000, 0])
+        );
+        assert_eq!(estimate(&manager, &[(GR2, 1_000_000)]).unwrap()[0], 0);
+    }
+
+    #[test]
+    fn query_reward_of_unwithdrawn_epochs() {
+        let mut manager = new_test_manager();
+        let votes = Votes {
+            epoch: 1,
+            votes: vec![
+                Vote {
+                    vote_address: addr(GR1).as_bytes().to_vec(),
+                    vote_count: 10,
+                },
+                Vote {
+                    vote_address: addr(GR2).as_bytes().to_vec(),
+                    vote_count: 30,
+                },
+            ],
+        };
+        manager.state_db.put_key(keys::Votes(addr(VOTER)), votes).unwrap();
+        manager
+            .state_db
+            .put_key(keys::Account(addr(VOTER)), Account::default())
+            .unwrap();
+        manager
+            .state_db
+            .put_key(keys::DynamicProperty::CurrentEpoch, 3)
+            .unwrap();
+        for &(epoch, wit, vote_count, reward_amount) in &[
+            (0, GR1, 100, 5_000),
+            (1, GR1, 100, 1_000),
+            (2, GR1, 100, 2_000),
+            (2, GR2, 60, 600),
+            (3, GR2, 60, 5_000),
+        ] {
+            let reward = WitnessVoterReward {
+                vote_count,
+                reward_amount,
+            };
+            manager
+                .state_db
+                .put_key(keys::VoterReward(epoch, addr(wit)), reward)
+                .unwrap();
+        }
+        assert_eq!(RewardUtil::new(&manager).query_reward_details(addr(VOTER)), Ok(vec![]));
+
+        allow_change_delegation(&mut manager);
+        assert_eq!(
+            RewardUtil::new(&manager).query_reward_details(addr(VOTER)),
+            Ok(vec![(1, addr(GR1), 100), (2, addr(GR1), 200), (2, addr(GR2), 300)])
+        );
+        assert_eq!(RewardUtil::new(&manager).query_reward(addr(VOTER)), Ok(600));
+        assert_eq!(RewardUtil::new(&manager).query_reward_details(addr(GR3)), Ok(vec![]));
+
+        RewardController::new(&mut manager)
+            .withdraw_reward(addr(VOTER))
+            .unwrap();
+        assert_eq!(manager.state_db.must_get(&keys::Account(addr(VOTER))).allowance, 600);
+        assert_eq!(RewardUtil::new(&manager).query_reward_details(addr(VOTER)), Ok(vec![]));
     }
 }
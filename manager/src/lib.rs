@@ -4,7 +4,7 @@ use ::keys::{b58encode_check, Address, KeyPair};
 use chain::BlockBuilder;
 use chain::{IndexedBlock, IndexedBlockHeader, IndexedTransaction};
 use chrono::Utc;
-use config::{Config, GenesisConfig};
+use config::{Config, GenesisConfig, StorageEngine};
 use log::{debug, info, trace, warn};
 use prost::Message;
 use proto::chain::transaction::Result as TransactionResult;
@@ -16,7 +16,7 @@ use std::convert::{TryFrom, TryInto};
 use types::H256;
 
 use self::executor::TransactionExecutor;
-use self::governance::maintenance::MaintenanceManager;
+use self::governance::maintenance::{sorted_witnesses, MaintenanceManager};
 use self::governance::proposal::ProposalController;
 use self::governance::reward::RewardController;
 use self::resource::EnergyProcessor;
@@ -24,6 +24,8 @@ use self::resource::EnergyProcessor;
 pub mod executor;
 pub mod governance;
 pub mod resource;
+#[cfg(test)]
+mod test_utils;
 pub mod version_fork;
 pub mod vm;
 
@@ -49,13 +51,18 @@ pub struct Manager {
     config: Config,
     genesis_config: GenesisConfig,
     maintenance_started_at: i64,
+    /// Time warp of the instant-seal dev mode, in ms.
+    time_offset: i64,
 
     layers: usize,
 }
 
 impl Manager {
     pub fn new(config: &Config, genesis_config: &GenesisConfig) -> Self {
-        let mut state_db = StateDB::new(&config.storage.state_data_dir);
+        let mut state_db = match config.storage.engine {
+            StorageEngine::RocksDB => StateDB::new(&config.storage.state_data_dir, &config.rocksdb),
+            StorageEngine::Memory => StateDB::new_in_memory(),
+        };
 
         state_db.init_genesis(&genesis_config, &config.chain).unwrap();
         let genesis_block_timestamp = genesis_config.timestamp;
@@ -78,6 +85,7 @@ impl Manager {
             config: config.clone(),
             genesis_config: genesis_config.clone(),
             maintenance_started_at: 0,
+            time_offset: 0,
             layers: 0,
         }
     }
@@ -113,7 +121,11 @@ impl Manager {
         Ok(())
     }
 
+    /// Burn the fee, or credit it to the Blackhole account before `AllowBlackholeOptimization`.
     pub fn add_to_blackhole(&mut self, fee: i64) -> Result<()> {
+        if self.allow_blackhole_optimization() {
+            return self.burn_trx(fee);
+        }
         let key = keys::Account(self.blackhole);
         let mut blackhole_acct = self.state_db.must_get(&key);
         blackhole_acct.balance += fee;
@@ -121,6 +133,102 @@ impl Manager {
         Ok(())
     }
 
+    /// Collect a transaction fee of bandwidth or energy. With `AllowTransactionFeePool`, fees of a block are paid to
+    /// its producer, see `RewardController::pay_reward`.
+    pub fn add_to_fee_pool(&mut self, fee: i64) -> Result<()> {
+        if !self.allow_transaction_fee_pool() {
+            return self.add_to_blackhole(fee);
+        }
+        let pool = self.transaction_fee_pool();
+        self.state_db
+            .put_key(keys::DynamicProperty::TransactionFeePool, pool + fee)
+            .map_err(|_| new_error("db insert error"))
+    }
+
+    /// Burn TRX, deducting it from the total supply.
+    fn burn_trx(&mut self, amount: i64) -> Result<()> {
+        let burned = self
+            .state_db
+            .get(&keys::DynamicProperty::BurnedTrx)
+            .unwrap()
+            .unwrap_or_default();
+        self.state_db
+            .put_key(keys::DynamicProperty::BurnedTrx, burned + amount)
+            .map_err(|_| new_error("db insert error"))
+    }
+
+    pub fn allow_account_state_root(&self) -> bool {
+        self.state_db.must_get(&keys::ChainParameter::AllowAccountStateRoot) != 0
+    }
+
+    /// Account state root in the header of latest block, zero if empty.
+    pub fn account_state_root(&self) -> H256 {
+        self.state_db.get(&keys::AccountStateRoot).unwrap().unwrap_or_default()
+    }
+
+    /// Apply accounts updated by transactions of current block to the account state trie of
+    /// the latest block, return the new root.
+    fn update_account_state_root(&mut self) -> Result<H256> {
+        let parent_root = self.account_state_root();
+        self.state_db.update_account_state_trie(&parent_root)
+    }
+
+    // NOTE: The following keys are missing in state-db inited by older versions, until the proposals are approved.
+
+    pub fn allow_transaction_fee_pool(&self) -> bool {
+        self.state_db
+            .get(&keys::ChainParameter::AllowTransactionFeePool)
+            .unwrap()
+            .unwrap_or_default() !=
+            0
+    }
+
+    pub fn allow_blackhole_optimization(&self) -> bool {
+        self.state_db
+            .get(&keys::ChainParameter::AllowBlackholeOptimization)
+            .unwrap()
+            .unwrap_or_default() !=
+            0
+    }
+
+    /// Stake 2.0, days to wait before unfrozen balance can be withdrawn. 0 if Stake 2.0 is not enabled.
+    pub fn unfreeze_delay_days(&self) -> i64 {
+        self.state_db
+            .get(&keys::ChainParameter::UnfreezeDelayDays)
+            .unwrap()
+            .unwrap_or_default()
+    }
+
+    pub fn support_unfreeze_delay(&self) -> bool {
+        self.unfreeze_delay_days() > 0
+    }
+
+    /// Fees collected but not yet paid to block producers.
+    pub fn transaction_fee_pool(&self) -> i64 {
+        self.state_db
+            .get(&keys::DynamicProperty::TransactionFeePool)
+            .unwrap()
+            .unwrap_or_default()
+    }
+
+    /// Discard uncommitted layers of a half-processed block, then flush state-db.
+    ///
+    /// The caller must hold the manager exclusively, so that no block is being processed.
+    /// Discard state-db layers left uncommitted by a failed block.
+    pub fn discard_pending_layers(&mut self) {
+        if self.layers > 0 {
+            warn!("discard {} uncommitted state-db layers", self.layers);
+            self.rollback_layers(self.layers);
+        }
+    }
+
+    pub fn prepare_close(&mut self) {
+        self.discard_pending_layers();
+        unsafe {
+            self.state_db.prepare_close();
+        }
+    }
+
     fn new_layer(&mut self) {
         self.layers += 1;
         self.state_db.new_layer();
@@ -144,10 +252,8 @@ impl Manager {
     pub fn push_incoming_block(&mut self, block: &IndexedBlock) -> Result<bool> {
         // . verify witness signature
         let recovered = block.recover_witness()?;
-        if self.state_db.must_get(&keys::ChainParameter::AllowMultisig) == 1 {
-            // warn!("TODO: handle multisig witness");
-        }
-        if recovered.as_bytes() != block.witness() {
+        let witness = Address::try_from(block.witness()).map_err(|_| new_error("invalid block witness"))?;
+        if recovered != self.get_witness_signature_key(&witness) {
             return Err(new_error("verifying block witness signature failed"));
         }
         self.push_block(block)
@@ -255,15 +361,41 @@ impl Manager {
         };
 
         // 3. Execute Transaction, TransactionRet / TransactionReceipt
-        // TODO: handle accountState - AccountStateCallBack
-        for (txn, recovered_addrs) in block.transactions.iter().zip(recovered_owners.into_iter()) {
-            debug!(
-                "transaction => {:?} at block #{} v{}",
-                txn.hash,
-                block.number(),
-                block.version()
-            );
-            self.process_transaction(&txn, recovered_addrs, &block.header)?;
+        // Accounts updated by transactions are applied to the account state trie - AccountStateCallBack
+        let allow_account_state_root = self.allow_account_state_root();
+        self.state_db.track_account_states(allow_account_state_root);
+        let ret = block
+            .transactions
+            .iter()
+            .zip(recovered_owners.into_iter())
+            .try_for_each(|(txn, recovered_addrs)| {
+                debug!(
+                    "transaction => {:?} at block #{} v{}",
+                    txn.hash,
+                    block.number(),
+                    block.version()
+                );
+                self.process_transaction(&txn, recovered_addrs, &block.header)
+            });
+        self.state_db.track_account_states(false);
+        ret?;
+
+        // . verify account state root
+        if allow_account_state_root {
+            let root = self.update_account_state_root()?;
+            // NOTE: An empty root is accepted, and the next block starts from an empty trie,
+            // the same as java-tron.
+            if block.account_state_root().is_empty() {
+                self.state_db.put_key(keys::AccountStateRoot, H256::zero())?;
+            } else if block.account_state_root() != root.as_bytes() {
+                return Err(new_error(&format!(
+                    "verify account state root failed, block={}, expected={:?}",
+                    block.number(),
+                    root
+                )));
+            } else {
+                self.state_db.put_key(keys::AccountStateRoot, root)?;
+            }
         }
 
         self.post_process_block(block)
@@ -560,6 +692,13 @@ impl Manager {
             let mut wit_acct = self.state_db.must_get(&wit_key);
             let reward_per_block = self.state_db.must_get(&keys::ChainParameter::WitnessPayPerBlock);
             wit_acct.allowance += reward_per_block;
+            // payTransactionFeeReward, the whole pool is paid to the block producer.
+            if self.allow_transaction_fee_pool() {
+                wit_acct.allowance += self.transaction_fee_pool();
+                self.state_db
+                    .put_key(keys::DynamicProperty::TransactionFeePool, 0)
+                    .unwrap();
+            }
             self.state_db.put_key(wit_key, wit_acct).unwrap();
         }
     }
@@ -615,18 +754,33 @@ impl Manager {
         let old_layers = self.layers;
         self.new_layer();
 
+        let allow_account_state_root = self.allow_account_state_root();
+        self.state_db.track_account_states(allow_account_state_root);
         for txn in transactions {
             if Utc::now().timestamp_millis() >= deadline {
                 info!("deadline, stop pushing transactions");
                 break;
             }
             debug!("transaction => {:?} at block #{}", txn.hash, block_header.number());
-            let result = self.run_transaction(&txn, &block_header)?;
+            let result = match self.run_transaction(&txn, &block_header) {
+                Ok(result) => result,
+                Err(e) => {
+                    self.state_db.track_account_states(false);
+                    return Err(e);
+                }
+            };
             let mut txn = txn.raw.clone();
             txn.result = vec![result];
 
             builder.push_transaction(txn);
         }
+        self.state_db.track_account_states(false);
+
+        if allow_account_state_root {
+            let root = self.update_account_state_root()?;
+            builder.set_account_state_root(&root);
+            self.state_db.put_key(keys::AccountStateRoot, root)?;
+        }
 
         // sign and post-process
         let ret = builder
@@ -661,6 +815,17 @@ impl Manager {
     }
 
     // * DposSlot
+    /// Current timestamp, with time warp applied.
+    pub fn current_timestamp(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.time_offset
+    }
+
+    /// Move the clock forward, for the instant-seal dev mode. Returns the total time warp.
+    pub fn increase_time(&mut self, millis: i64) -> i64 {
+        self.time_offset += millis;
+        self.time_offset
+    }
+
     // getAbSlot
     fn get_absolute_slot(&self, timestamp: i64) -> i64 {
         (timestamp - self.genesis_block_timestamp) / constants::BLOCK_PRODUCING_INTERVAL
@@ -685,7 +850,7 @@ impl Manager {
         assert!(slot >= 0, "unreachable");
 
         if slot == 0 {
-            return Utc::now().timestamp_millis();
+            return self.current_timestamp();
         }
 
         if self.state_db.must_get(&keys::DynamicProperty::LatestBlockNumber) == 0 {
@@ -702,7 +867,7 @@ impl Manager {
         ts + constants::BLOCK_PRODUCING_INTERVAL * slot
     }
 
-    fn get_active_witnesses(&self) -> Vec<Address> {
+    pub fn get_active_witnesses(&self) -> Vec<Address> {
         let mut witnesses = self.state_db.get(&keys::WitnessSchedule).unwrap().unwrap();
         if witnesses.is_empty() {
             panic!("no witness found");
@@ -713,6 +878,21 @@ impl Manager {
         witnesses.into_iter().map(|wit| wit.0).collect()
     }
 
+    /// Address of the key signing blocks for the witness. When multisig is allowed, this is the key of the witness
+    /// permission, otherwise the witness itself.
+    pub fn get_witness_signature_key(&self, witness: &Address) -> Address {
+        if self.state_db.must_get(&keys::ChainParameter::AllowMultisig) == 0 {
+            return *witness;
+        }
+        self.state_db
+            .get(&keys::Witness(*witness))
+            .ok()
+            .flatten()
+            .filter(|wit| !wit.signature_key.is_empty())
+            .and_then(|wit| Address::try_from(&wit.signature_key).ok())
+            .unwrap_or(*witness)
+    }
+
     fn get_standby_witnesses(&self) -> Vec<Address> {
         let mut witnesses = self.state_db.get(&keys::WitnessSchedule).unwrap().unwrap();
         if witnesses.is_empty() {
@@ -726,6 +906,10 @@ impl Manager {
 
     pub fn get_scheduled_witness(&self, slot: i64) -> Address {
         let mut witnesses = self.state_db.get(&keys::WitnessSchedule).unwrap().unwrap_or_default();
+        if witnesses.is_empty() && self.latest_block_number() == 0 {
+            // WitnessSchedule is inited in first maintenance cycle, block #1 follows the genesis witnesses.
+            witnesses = sorted_witnesses(&self.state_db);
+        }
         if witnesses.is_empty() {
             panic!("no witness schedule found");
         }
@@ -756,6 +940,41 @@ impl Manager {
         self.state_db.must_get(&keys::DynamicProperty::LatestSolidBlockNumber)
     }
 
+    /// Latest block number finalized by PBFT.
+    #[inline]
+    pub fn pbft_block_number(&self) -> i64 {
+        // NOTE: The key is missing in state-db inited by older versions.
+        self.state_db
+            .get(&keys::DynamicProperty::LatestPbftBlockNumber)
+            .unwrap()
+            .unwrap_or_default()
+    }
+
+    /// Move the PBFT-finalized block number forward. Returns false if the block is already finalized.
+    pub fn update_pbft_block_number(&mut self, block_number: i64) -> Result<bool> {
+        if block_number <= self.pbft_block_number() {
+            return Ok(false);
+        }
+        self.new_layer();
+        self.state_db
+            .put_key(keys::DynamicProperty::LatestPbftBlockNumber, block_number)
+            .map_err(|_| new_error("db insert error"))?;
+        self.commit_current_layers();
+        Ok(true)
+    }
+
+    /// Percentage of filled slots in the latest `NUM_OF_BLOCK_FILLED_SLOTS` slots.
+    pub fn block_filled_slots_rate(&self) -> i64 {
+        let filled_slots = self.state_db.must_get(&keys::BlockFilledSlots);
+        let num_filled = filled_slots.iter().filter(|&&filled| filled != 0).count();
+        (num_filled * 100 / constants::NUM_OF_BLOCK_FILLED_SLOTS) as i64
+    }
+
+    #[inline]
+    pub fn current_epoch(&self) -> i64 {
+        self.state_db.must_get(&keys::DynamicProperty::CurrentEpoch)
+    }
+
     #[inline]
     pub fn latest_block_number(&self) -> i64 {
         self.state_db.must_get(&keys::DynamicProperty::LatestBlockNumber)
@@ -799,6 +1018,9 @@ impl WitnessStatisticManager<'_> {
 
         self.manager.state_db.put_key(keys::Witness(wit_addr), wit).unwrap();
 
+        let epoch = self.manager.current_epoch();
+        self.update_epoch_statistic(epoch, wit_addr, 1, 0);
+
         let slot = if block.number() != 1 {
             self.manager.get_slot(block.timestamp())
         } else {
@@ -829,6 +1051,7 @@ impl WitnessStatisticManager<'_> {
                 missed_count[&wit_addr],
             );
             self.manager.state_db.put_key(keys::Witness(wit_addr), wit).unwrap();
+            self.update_epoch_statistic(epoch, wit_addr, 0, missed_count[&wit_addr]);
         }
 
         // current block is filled
@@ -846,4 +1069,322 @@ impl WitnessStatisticManager<'_> {
 
         Ok(())
     }
+
+    fn update_epoch_statistic(&mut self, epoch: i64, wit_addr: Address, produced: i64, missed: i64) {
+        let key = keys::WitnessStatistic(epoch, wit_addr);
+        let mut stat = self.manager.state_db.get(&key).unwrap().unwrap_or_default();
+        stat.produced += produced;
+        stat.missed += missed;
+        self.manager.state_db.put_key(key, stat).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proto::common::permission::{Key as PermissionKey, PermissionType};
+    use proto::common::Permission;
+    use proto::contract::{AccountPermissionUpdateContract, TransferContract, WitnessCreateContract};
+    use proto::state::Account;
+
+    use super::*;
+    use crate::executor::TransactionContext;
+    use crate::resource::BandwidthProcessor;
+    use crate::test_utils::{addr, new_test_manager, run_contract, GR1, GR2, GR3};
+
+    const FEES: i64 = 1_000_000;
+
+    fn pay_block_reward(manager: &mut Manager, fees: i64) -> i64 {
+        manager
+            .state_db
+            .put_key(keys::DynamicProperty::TransactionFeePool, fees)
+            .unwrap();
+        let block = manager
+            .generate_empty_block(0, &addr(GR1), &KeyPair::generate())
+            .unwrap();
+        manager.pay_reward(&block);
+        manager.state_db.must_get(&keys::Account(addr(GR1))).allowance
+    }
+
+    /// Transfer to a new account, paying the account creation fee. Returns the fee.
+    fn create_account_by_fee(manager: &mut Manager) -> i64 {
+        let owner = KeyPair::generate().address();
+        let mut owner_acct = Account::new(manager.latest_block_timestamp());
+        owner_acct.balance = 1_000_000_000;
+        manager.state_db.put_key(keys::Account(owner), owner_acct).unwrap();
+
+        let cntr = TransferContract {
+            owner_address: owner.as_bytes().to_vec(),
+            to_address: KeyPair::generate().address().as_bytes().to_vec(),
+            amount: 1,
+        };
+        let header = IndexedBlockHeader::dummy(manager.latest_block_number() + 1, manager.latest_block_timestamp());
+        let txn = IndexedTransaction::new(H256::zero(), Default::default());
+        let mut ctx = TransactionContext::new(&header, &txn);
+        ctx.new_account_created = true;
+        BandwidthProcessor::new(manager, &txn, &cntr)
+            .unwrap()
+            .consume(&mut ctx)
+            .unwrap();
+        ctx.bandwidth_fee
+    }
+
+    #[test]
+    fn account_creation_fee_is_not_paid_to_fee_pool() {
+        let mut manager = new_test_manager();
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowTransactionFeePool, 1)
+            .unwrap();
+        let blackhole_balance = |manager: &Manager| {
+            manager
+                .state_db
+                .must_get(&keys::Account(manager.blackhole))
+                .balance
+        };
+        let burned_trx = |manager: &Manager| {
+            manager
+                .state_db
+                .get(&keys::DynamicProperty::BurnedTrx)
+                .unwrap()
+                .unwrap_or_default()
+        };
+        let balance = blackhole_balance(&manager);
+        let burned = burned_trx(&manager);
+
+        let fee = create_account_by_fee(&mut manager);
+        assert_eq!(fee, manager.state_db.must_get(&keys::ChainParameter::AccountCreateFee));
+        assert_eq!(manager.transaction_fee_pool(), 0);
+        assert_eq!(blackhole_balance(&manager), balance + fee);
+
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowBlackholeOptimization, 1)
+            .unwrap();
+        let fee = create_account_by_fee(&mut manager);
+        assert_eq!(manager.transaction_fee_pool(), 0);
+        assert_eq!(blackhole_balance(&manager), balance + fee);
+        assert_eq!(burned_trx(&manager), burned + fee);
+    }
+
+    #[test]
+    fn fee_pool_is_paid_to_producer_in_legacy_reward_scheme() {
+        let mut manager = new_test_manager();
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowTransactionFeePool, 1)
+            .unwrap();
+        let reward_per_block = manager.state_db.must_get(&keys::ChainParameter::WitnessPayPerBlock);
+
+        assert_eq!(pay_block_reward(&mut manager, FEES), reward_per_block + FEES);
+        assert_eq!(manager.transaction_fee_pool(), 0);
+        assert_eq!(pay_block_reward(&mut manager, 0), 2 * reward_per_block + FEES);
+    }
+
+    #[test]
+    fn fee_pool_is_paid_to_producer_and_voters_in_new_reward_scheme() {
+        let mut manager = new_test_manager();
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowTransactionFeePool, 1)
+            .unwrap();
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowChangeDelegation, 1)
+            .unwrap();
+        let brokerage = manager.state_db.must_get(&keys::Witness(addr(GR1))).brokerage;
+        let epoch = manager.state_db.must_get(&keys::DynamicProperty::CurrentEpoch);
+        let voter_reward = |manager: &Manager| {
+            manager
+                .state_db
+                .must_get(&keys::VoterReward(epoch, addr(GR1)))
+                .reward_amount
+        };
+
+        let allowance_without_fees = pay_block_reward(&mut manager, 0);
+        let voter_reward_without_fees = voter_reward(&manager);
+        let allowance = pay_block_reward(&mut manager, FEES) - allowance_without_fees;
+        let fee_brokerage = (FEES as f64 * (brokerage as f64 / 100.0)) as i64;
+        assert_eq!(allowance, allowance_without_fees + fee_brokerage);
+        assert_eq!(
+            voter_reward(&manager),
+            2 * voter_reward_without_fees + FEES - fee_brokerage
+        );
+        assert_eq!(manager.transaction_fee_pool(), 0);
+    }
+
+    /// Push an empty block of the scheduled witness, at `slot` after the latest block. Returns the witness.
+    fn produce_block_at_slot(manager: &mut Manager, slot: i64) -> Address {
+        let timestamp = manager.get_slot_timestamp(slot);
+        let witness = manager.get_scheduled_witness(slot);
+        let block = manager
+            .generate_empty_block(timestamp, &witness, &KeyPair::generate())
+            .unwrap();
+        assert!(manager.push_generated_block(&block).unwrap());
+        witness
+    }
+
+    /// `(produced, missed)` of a witness in an epoch.
+    fn epoch_statistic(manager: &Manager, epoch: i64, wit: &str) -> (i64, i64) {
+        let stat = manager
+            .state_db
+            .get(&keys::WitnessStatistic(epoch, addr(wit)))
+            .unwrap()
+            .unwrap_or_default();
+        (stat.produced, stat.missed)
+    }
+
+    #[test]
+    fn witness_statistic_across_maintenance() {
+        let mut manager = new_test_manager();
+        assert_eq!(manager.block_filled_slots_rate(), 100);
+
+        // Block #1 inits the schedule of epoch 1, GR1 > GR2 > GR3.
+        assert_eq!(produce_block_at_slot(&mut manager, 1), addr(GR2));
+        assert_eq!(manager.current_epoch(), 1);
+        // Shorten epoch 1 to end at the 10th slot.
+        manager.new_layer();
+        manager
+            .state_db
+            .put_key(keys::DynamicProperty::NextMaintenanceTime, 30_000)
+            .unwrap();
+
+        // Maintenance slots after block #1 are skipped, not missed.
+        assert_eq!(produce_block_at_slot(&mut manager, 2), addr(GR1));
+        assert_eq!(produce_block_at_slot(&mut manager, 1), addr(GR1));
+        // The maintenance block, after 3 missed slots of GR2, GR3 and GR1.
+        assert_eq!(produce_block_at_slot(&mut manager, 4), addr(GR2));
+        assert_eq!(manager.latest_block_timestamp(), 30_000);
+        assert_eq!(manager.current_epoch(), 2);
+        assert_eq!(produce_block_at_slot(&mut manager, 2), addr(GR1));
+
+        assert_eq!(epoch_statistic(&manager, 0, GR1), (0, 0));
+        assert_eq!(epoch_statistic(&manager, 0, GR2), (1, 0));
+        assert_eq!(epoch_statistic(&manager, 1, GR1), (2, 1));
+        assert_eq!(epoch_statistic(&manager, 1, GR2), (1, 1));
+        assert_eq!(epoch_statistic(&manager, 1, GR3), (0, 2));
+        assert_eq!(epoch_statistic(&manager, 2, GR1), (1, 0));
+        assert_eq!(epoch_statistic(&manager, 2, GR2), (0, 0));
+        assert_eq!(epoch_statistic(&manager, 2, GR3), (0, 1));
+        assert_eq!(manager.state_db.must_get(&keys::Witness(addr(GR3))).total_missed, 3);
+
+        // 5 of the latest 128 slots are missed.
+        assert_eq!(manager.block_filled_slots_rate(), 96);
+    }
+
+    fn allow_multisig(manager: &mut Manager, allow: bool) {
+        manager
+            .state_db
+            .put_key(keys::ChainParameter::AllowMultisig, allow as i64)
+            .unwrap();
+    }
+
+    /// Create a witness of a new account by WitnessCreateContract.
+    fn create_witness(manager: &mut Manager) -> KeyPair {
+        let kp = KeyPair::generate();
+        let mut acct = Account::new(manager.latest_block_timestamp());
+        acct.balance = 20_000_000_000;
+        manager.state_db.put_key(keys::Account(kp.address()), acct).unwrap();
+
+        let cntr = WitnessCreateContract {
+            owner_address: kp.address().as_bytes().to_vec(),
+            url: b"http://witness.example.com".to_vec(),
+        };
+        run_contract(manager, cntr).unwrap();
+        kp
+    }
+
+    /// Set the witness permission of a witness to a single key by AccountPermissionUpdateContract.
+    fn set_witness_permission(manager: &mut Manager, witness: &Address, key: &Address) -> Result<(), String> {
+        let perm = |perm_type: PermissionType, key: &Address| Permission {
+            r#type: perm_type as i32,
+            threshold: 1,
+            keys: vec![PermissionKey {
+                address: key.as_bytes().to_vec(),
+                weight: 1,
+            }],
+            ..Default::default()
+        };
+        // Only allows TransferContract.
+        let mut operations = vec![0; 32];
+        operations[0] = 0b10;
+
+        let cntr = AccountPermissionUpdateContract {
+            owner_address: witness.as_bytes().to_vec(),
+            owner: Some(perm(PermissionType::Owner, witness)),
+            witness: Some(perm(PermissionType::Witness, key)),
+            actives: vec![Permission {
+                operations,
+                ..perm(PermissionType::Active, witness)
+            }],
+        };
+        run_contract(manager, cntr)
+    }
+
+    /// Push block #1 of the witness, signed by `signer`.
+    fn push_block_signed_by(manager: &mut Manager, witness: &Address, signer: &KeyPair) -> Result<bool> {
+        let timestamp = manager.get_slot_timestamp(1);
+        let block = manager.generate_empty_block(timestamp, witness, signer).unwrap();
+        manager.push_incoming_block(&block)
+    }
+
+    #[test]
+    fn witness_create_sets_default_signature_key() {
+        for &multisig in &[false, true] {
+            let mut manager = new_test_manager();
+            allow_multisig(&mut manager, multisig);
+            let kp = create_witness(&mut manager);
+
+            let wit = manager.state_db.must_get(&keys::Witness(kp.address()));
+            if multisig {
+                assert_eq!(wit.signature_key, kp.address().as_bytes());
+            } else {
+                assert!(wit.signature_key.is_empty());
+            }
+            assert_eq!(manager.get_witness_signature_key(&kp.address()), kp.address());
+        }
+    }
+
+    #[test]
+    fn block_signed_by_owner_without_witness_permission() {
+        for &multisig in &[false, true] {
+            let mut manager = new_test_manager();
+            allow_multisig(&mut manager, multisig);
+            // A genesis witness, without witness permission.
+            let genesis_wit = manager.state_db.must_get(&keys::Witness(addr(GR1)));
+            assert!(genesis_wit.signature_key.is_empty());
+            assert_eq!(manager.get_witness_signature_key(&addr(GR1)), addr(GR1));
+
+            let kp = create_witness(&mut manager);
+            assert!(push_block_signed_by(&mut manager, &kp.address(), &KeyPair::generate()).is_err());
+            assert!(push_block_signed_by(&mut manager, &kp.address(), &kp).unwrap());
+            assert_eq!(manager.latest_block_number(), 1);
+        }
+    }
+
+    #[test]
+    fn block_signed_by_witness_permission_key() {
+        for &multisig in &[false, true] {
+            let mut manager = new_test_manager();
+            allow_multisig(&mut manager, false);
+            let kp = create_witness(&mut manager);
+            let signer = KeyPair::generate();
+            assert_eq!(
+                set_witness_permission(&mut manager, &kp.address(), &signer.address()),
+                Err("multisig is disabled on chain".into())
+            );
+
+            allow_multisig(&mut manager, true);
+            set_witness_permission(&mut manager, &kp.address(), &signer.address()).unwrap();
+            assert_eq!(manager.get_witness_signature_key(&kp.address()), signer.address());
+
+            // The permission key is ignored when multisig is disabled, and the owner key must be rejected otherwise.
+            allow_multisig(&mut manager, multisig);
+            let (rejected, accepted) = if multisig { (&kp, &signer) } else { (&signer, &kp) };
+            assert_eq!(manager.get_witness_signature_key(&kp.address()), accepted.address());
+            assert!(push_block_signed_by(&mut manager, &kp.address(), rejected).is_err());
+            assert_eq!(manager.latest_block_number(), 0);
+            assert!(push_block_signed_by(&mut manager, &kp.address(), accepted).unwrap());
+            assert_eq!(manager.latest_block_number(), 1);
+        }
+    }
 }
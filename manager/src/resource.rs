@@ -6,6 +6,7 @@ use constants::block_version::BlockVersion;
 use log::debug;
 use prost::Message;
 use proto::chain::ContractType;
+use proto::common::ResourceCode;
 use proto::contract::TransferAssetContract;
 use proto::state::Account;
 use proto::ContractExt;
@@ -127,9 +128,17 @@ impl<C: ContractExt> BandwidthProcessor<'_, C> {
         }
 
         ctx.bandwidth_fee = bw_fee;
+        self.pay_bandwidth_fee(bw_fee);
         true
     }
 
+    fn pay_bandwidth_fee(&mut self, fee: i64) {
+        // NOTE: Legacy bandwidth fee is only deducted from the owner, not credited to the Blackhole account.
+        if self.manager.allow_transaction_fee_pool() || self.manager.allow_blackhole_optimization() {
+            self.manager.add_to_fee_pool(fee).unwrap();
+        }
+    }
+
     // Renamed: useAccountNet
     fn consume_frozen_bandwidth(&mut self, nbytes: i64, now: i64, _ctx: &mut TransactionContext) -> bool {
         let bw_usage = self.acct.resource().frozen_bandwidth_used;
@@ -362,6 +371,8 @@ impl<C: ContractExt> BandwidthProcessor<'_, C> {
             assert!(self.acct.adjust_balance(-creation_fee).is_ok());
             ctx.bandwidth_fee = creation_fee;
             ctx.bandwidth_usage = 0;
+            // NOTE: Unlike bandwidth fee, account creation fee is burnt, or credited to the Blackhole account.
+            self.manager.add_to_blackhole(creation_fee).unwrap();
             true
         } else {
             false
@@ -569,7 +580,7 @@ impl EnergyProcessor<'_> {
                 return Err("insufficient balance to burn for energy".into());
             }
             ctx.energy_fee = energy_fee;
-            self.manager.add_to_blackhole(energy_fee).unwrap();
+            self.manager.add_to_fee_pool(energy_fee).unwrap();
 
             debug!(
                 "E usage: total={} frozen={} burnt={} fee={}",
@@ -754,3 +765,83 @@ impl EnergyUtil<'_> {
         (energy_weight as f64 * (total_energy_limit as f64 / total_energy_weight as f64)) as i64
     }
 }
+
+/// Stake 2.0, move resource usage of the undelegated `amount` from the receiver back to the owner, proportional to
+/// all frozen amount of the receiver. `unDelegateIncrease`.
+pub fn transfer_undelegated_usage(
+    manager: &Manager,
+    recv_acct: &mut Account,
+    owner_acct: &mut Account,
+    amount: i64,
+    res_type: ResourceCode,
+) {
+    let now = manager.get_head_slot();
+    let recv_amount = match res_type {
+        ResourceCode::Bandwidth => recv_acct.amount_for_bandwidth(),
+        ResourceCode::Energy => recv_acct.amount_for_energy(),
+    };
+
+    let (used, latest_slot) = usage_mut(recv_acct, res_type);
+    let recv_usage = adjust_usage(*used, 0, *latest_slot, now);
+    let transferred = if recv_amount > 0 {
+        (recv_usage as f64 * (amount as f64 / recv_amount as f64)) as i64
+    } else {
+        0
+    };
+    *used = recv_usage - transferred;
+    *latest_slot = now;
+
+    let (used, latest_slot) = usage_mut(owner_acct, res_type);
+    *used = adjust_usage(*used, transferred, *latest_slot, now);
+    *latest_slot = now;
+}
+
+/// Usage and its latest slot of frozen bandwidth or energy.
+fn usage_mut(acct: &mut Account, res_type: ResourceCode) -> (&mut i64, &mut i64) {
+    let res = acct.resource_mut();
+    match res_type {
+        ResourceCode::Bandwidth => (&mut res.frozen_bandwidth_used, &mut res.frozen_bandwidth_latest_slot),
+        ResourceCode::Energy => (&mut res.energy_used, &mut res.energy_latest_slot),
+    }
+}
+
+/// Stake 2.0, resource usage of the account, counted in SUN of its own Stake 2.0 frozen amount.
+///
+/// Usage is covered by legacy frozen amount and acquired delegated amounts first. `getV2NetUsage`,
+/// `getV2EnergyUsage`.
+pub fn frozen_v2_usage(manager: &Manager, acct: &Account, res_type: ResourceCode) -> i64 {
+    let res = match acct.resource {
+        Some(ref res) => res,
+        None => return 0,
+    };
+    let now = manager.get_head_slot();
+    let (usage, total_limit, total_weight, covered) = match res_type {
+        ResourceCode::Bandwidth => (
+            adjust_usage(res.frozen_bandwidth_used, 0, res.frozen_bandwidth_latest_slot, now),
+            manager
+                .state_db
+                .must_get(&keys::DynamicProperty::TotalBandwidthLimit),
+            manager
+                .state_db
+                .must_get(&keys::DynamicProperty::TotalBandwidthWeight),
+            acct.frozen_amount_for_bandwidth +
+                acct.delegated_frozen_amount_for_bandwidth +
+                acct.delegated_v2_frozen_amount_for_bandwidth,
+        ),
+        ResourceCode::Energy => (
+            adjust_usage(res.energy_used, 0, res.energy_latest_slot, now),
+            manager
+                .state_db
+                .must_get(&keys::ChainParameter::TotalEnergyCurrentLimit),
+            manager.state_db.must_get(&keys::DynamicProperty::TotalEnergyWeight),
+            acct.frozen_amount_for_energy +
+                acct.delegated_frozen_amount_for_energy +
+                acct.delegated_v2_frozen_amount_for_energy,
+        ),
+    };
+    if total_limit <= 0 {
+        return 0;
+    }
+    let usage_amount = (usage as f64 * 1_000_000.0 * (total_weight as f64 / total_limit as f64)) as i64;
+    (usage_amount - covered).max(0)
+}
@@ -0,0 +1,53 @@
+//! Shared fixtures of manager tests.
+
+use ::keys::Address;
+use chain::{IndexedBlockHeader, IndexedTransaction};
+use config::genesis::{GenesisConfig, Witness as GenesisWitness};
+use config::{Config, StorageEngine};
+use proto::chain::Transaction;
+use types::H256;
+
+use crate::executor::actuators::BuiltinContractExecutorExt;
+use crate::executor::TransactionContext;
+use crate::governance::maintenance::MaintenanceManager;
+use crate::Manager;
+
+pub const GR1: &str = "TA4Wt1DUCqz6YegbnsmqsWC5uUfbdBqPxm";
+pub const GR2: &str = "TA9pkx4DFxrEw8JZzUtyDrh2uAat1LDuJL";
+pub const GR3: &str = "TAF8dttxK5iPKbvYC626aDBytrWANpLRXp";
+
+pub fn addr(s: &str) -> Address {
+    s.parse().unwrap()
+}
+
+/// An in-memory manager of 3 genesis witnesses, GR1 > GR2 > GR3 in votes, with a layer opened.
+pub fn new_test_manager() -> Manager {
+    let witnesses = [(GR1, 100_000_000), (GR2, 90_000_000), (GR3, 80_000_000)]
+        .iter()
+        .map(|&(address, votes)| GenesisWitness {
+            address: address.into(),
+            url: format!("http://{}.com", address),
+            votes,
+        })
+        .collect();
+    new_test_manager_with_genesis(&GenesisConfig::new(0, witnesses, vec![]))
+}
+
+pub fn new_test_manager_with_genesis(genesis: &GenesisConfig) -> Manager {
+    let mut config = Config::load_from_str(include_str!("../../etc/conf.local-test.toml")).unwrap();
+    config.storage.engine = StorageEngine::Memory;
+
+    let mut manager = Manager::new(&config, genesis);
+    manager.new_layer();
+    MaintenanceManager::new(&mut manager).update_witness_schedule();
+    manager
+}
+
+/// Validate and execute a builtin contract in a dummy block of current timestamp.
+pub fn run_contract<C: BuiltinContractExecutorExt>(manager: &mut Manager, cntr: C) -> Result<(), String> {
+    let header = IndexedBlockHeader::dummy(manager.latest_block_number() + 1, manager.latest_block_timestamp());
+    let txn = IndexedTransaction::new(H256::zero(), Transaction::default());
+    let mut ctx = TransactionContext::new(&header, &txn);
+    cntr.validate(manager, &mut ctx)?;
+    cntr.execute(manager, &mut ctx).map(|_| ())
+}
@@ -0,0 +1,307 @@
+//! Local multi-node devnet, every node as a child process on localhost.
+//!
+//! Layout of the devnet directory:
+//!
+//! - `genesis.json`, `keys.json`: shared genesis and generated keys
+//! - `node{i}/conf.toml`: config of the i-th node, producing with the i-th genesis witness
+//! - `node{i}/node.log`: output of the i-th node
+//!
+//! Node i listens p2p on `base-port + 10 * i` and GraphQL on `base-port + 10 * i + 1`.
+//!
+//! With `--until <N>`, the devnet is stopped once all nodes agree on the same block at #N or above, and fails if they
+//! don't in `--timeout` seconds. This checks that blocks produced by different nodes converge.
+
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ArgMatches;
+use keys::KeyPair;
+use serde_json::{json, Value};
+
+use super::genesis::{load_config_template, save_config, set_config_value, set_producer_keys, PrivateNet};
+
+type Error = Box<dyn std::error::Error>;
+
+const PORTS_PER_NODE: usize = 10;
+
+struct Node {
+    name: String,
+    dir: PathBuf,
+    p2p_endpoint: String,
+    graphql_endpoint: String,
+}
+
+impl Node {
+    fn new(base_dir: &Path, base_port: u16, index: usize) -> Result<Self, Error> {
+        let port = base_port as usize + index * PORTS_PER_NODE;
+        if port + 1 > u16::MAX as usize {
+            return Err("port out of range, use a lower --base-port".into());
+        }
+        Ok(Node {
+            name: format!("node{}", index),
+            dir: base_dir.join(format!("node{}", index)),
+            p2p_endpoint: format!("127.0.0.1:{}", port),
+            graphql_endpoint: format!("127.0.0.1:{}", port + 1),
+        })
+    }
+
+    fn config_file(&self) -> PathBuf {
+        self.dir.join("conf.toml")
+    }
+}
+
+pub fn main(matches: &ArgMatches<'_>) -> Result<(), Error> {
+    let num_nodes: usize = matches.value_of("nodes").expect("has default; qed").parse()?;
+    let base_port: u16 = matches.value_of("base-port").expect("has default; qed").parse()?;
+    let interval: u64 = matches.value_of("status-interval").expect("has default; qed").parse()?;
+    let template = matches.value_of("template").expect("has default; qed");
+    let dir = Path::new(matches.value_of("dir").expect("has default; qed"));
+    let until: Option<i64> = matches.value_of("until").map(str::parse).transpose()?;
+    let timeout = Duration::from_secs(matches.value_of("timeout").expect("has default; qed").parse()?);
+
+    if num_nodes == 0 {
+        return Err("at least 1 node is required".into());
+    }
+    let nodes = (0..num_nodes)
+        .map(|i| Node::new(dir, base_port, i))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if dir.join("genesis.json").exists() {
+        println!("Reuse devnet => {}", dir.display());
+        for node in &nodes {
+            if !node.config_file().exists() {
+                return Err(format!(
+                    "{} not found, remove {} to regenerate",
+                    node.config_file().display(),
+                    dir.display()
+                )
+                .into());
+            }
+        }
+    } else {
+        init_devnet(matches, template, dir, &nodes)?;
+    }
+
+    let running = Arc::new(AtomicBool::new(true));
+    let interrupted = Arc::new(AtomicBool::new(false));
+    {
+        let running = running.clone();
+        let interrupted = interrupted.clone();
+        ctrlc::set_handler(move || {
+            interrupted.store(true, Ordering::SeqCst);
+            running.store(false, Ordering::SeqCst);
+        })?;
+    }
+
+    let mut children = Vec::with_capacity(nodes.len());
+    for node in &nodes {
+        match spawn_node(node) {
+            Ok(child) => {
+                println!(
+                    "{:<8} pid={} p2p={} graphql=http://{}",
+                    node.name,
+                    child.id(),
+                    node.p2p_endpoint,
+                    node.graphql_endpoint
+                );
+                children.push(child);
+            }
+            Err(e) => {
+                shutdown(&mut children, false);
+                return Err(e);
+            }
+        }
+    }
+    println!("Press Ctrl-C to stop the devnet.");
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()?;
+    let started_at = Instant::now();
+    let mut last_report = started_at - Duration::from_secs(interval);
+    let mut converged = false;
+    while running.load(Ordering::SeqCst) {
+        for (node, child) in nodes.iter().zip(children.iter_mut()) {
+            if let Some(status) = child.try_wait()? {
+                eprintln!(
+                    "{} exited with {}, see {}",
+                    node.name,
+                    status,
+                    node.dir.join("node.log").display()
+                );
+                running.store(false, Ordering::SeqCst);
+            }
+        }
+        if running.load(Ordering::SeqCst) && last_report.elapsed() >= Duration::from_secs(interval) {
+            let in_sync_at = report_status(&client, &nodes);
+            last_report = Instant::now();
+            if let Some(target) = until {
+                if in_sync_at.map(|number| number >= target).unwrap_or(false) {
+                    println!("All nodes are in sync at #{} or above", target);
+                    converged = true;
+                    running.store(false, Ordering::SeqCst);
+                } else if started_at.elapsed() >= timeout {
+                    eprintln!("Nodes are not in sync at #{} after {}s", target, timeout.as_secs());
+                    running.store(false, Ordering::SeqCst);
+                }
+            }
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+
+    shutdown(&mut children, interrupted.load(Ordering::SeqCst));
+    match until {
+        Some(target) if !converged => Err(format!("devnet did not converge at #{}", target).into()),
+        _ => Ok(()),
+    }
+}
+
+fn init_devnet(matches: &ArgMatches<'_>, template: &str, dir: &Path, nodes: &[Node]) -> Result<(), Error> {
+    let votes: i64 = matches.value_of("votes").expect("has default; qed").parse()?;
+    let balance: i64 = matches.value_of("balance").expect("has default; qed").parse()?;
+
+    println!("Init devnet => {}", dir.display());
+    let net = PrivateNet::generate(nodes.len(), votes, vec![KeyPair::generate()], balance, 0)?;
+    net.save(dir)?;
+
+    let template = load_config_template(template)?;
+    for (i, node) in nodes.iter().enumerate() {
+        let others: Vec<toml::Value> = nodes
+            .iter()
+            .filter(|other| other.name != node.name)
+            .map(|other| other.p2p_endpoint.clone().into())
+            .collect();
+
+        let mut conf = template.clone();
+        // NOTE: genesis is related to the config file, data dirs are related to run path, the node dir.
+        set_config_value(&mut conf, &["chain", "genesis"], "../genesis.json".into())?;
+        set_config_value(&mut conf, &["storage", "data-dir"], "./data/chaindb".into())?;
+        set_config_value(&mut conf, &["storage", "state-data-dir"], "./data/statedb".into())?;
+        set_config_value(&mut conf, &["storage", "state-cache-dir"], "./data/cache".into())?;
+        set_config_value(&mut conf, &["protocol", "seed-nodes"], others.clone().into())?;
+        set_config_value(
+            &mut conf,
+            &["protocol", "discovery", "endpoint"],
+            node.p2p_endpoint.clone().into(),
+        )?;
+        set_config_value(
+            &mut conf,
+            &["protocol", "discovery", "peers-file"],
+            "./peers.json".into(),
+        )?;
+        set_config_value(
+            &mut conf,
+            &["protocol", "channel", "endpoint"],
+            node.p2p_endpoint.clone().into(),
+        )?;
+        set_config_value(
+            &mut conf,
+            &["protocol", "channel", "advertised-endpoint"],
+            node.p2p_endpoint.clone().into(),
+        )?;
+        set_config_value(&mut conf, &["protocol", "channel", "active-nodes"], others.into())?;
+        set_config_value(
+            &mut conf,
+            &["protocol", "channel", "max-active-connections"],
+            (nodes.len() as i64 - 1).max(1).into(),
+        )?;
+        set_config_value(&mut conf, &["graphql", "enable"], true.into())?;
+        set_config_value(
+            &mut conf,
+            &["graphql", "endpoint"],
+            node.graphql_endpoint.clone().into(),
+        )?;
+        set_producer_keys(&mut conf, &net.witness_kps[i..=i])?;
+
+        fs::create_dir_all(&node.dir)?;
+        save_config(&conf, &node.config_file())?;
+    }
+    Ok(())
+}
+
+fn spawn_node(node: &Node) -> Result<Child, Error> {
+    let log = File::create(node.dir.join("node.log"))?;
+    let child = Command::new(env::current_exe()?)
+        .arg("--config")
+        .arg("conf.toml")
+        .arg("run")
+        .current_dir(&node.dir)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .spawn()?;
+    Ok(child)
+}
+
+/// Latest block of a node, as `(number, hash)`.
+fn latest_block(client: &reqwest::blocking::Client, node: &Node) -> Result<(i64, String), Error> {
+    let resp: Value = client
+        .post(format!("http://{}", node.graphql_endpoint))
+        .json(&json!({ "query": "{ block { number hash } }" }))
+        .send()?
+        .json()?;
+    let block = &resp["data"]["block"];
+    // `Long` is serialized as a JSON string
+    let number = block["number"]
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .ok_or("malformed response")?;
+    let hash = block["hash"].as_str().ok_or("malformed response")?;
+    Ok((number, hash.to_owned()))
+}
+
+/// Print latest blocks of all nodes. Returns the block number if all nodes are in sync.
+fn report_status(client: &reqwest::blocking::Client, nodes: &[Node]) -> Option<i64> {
+    let blocks: Vec<_> = nodes.iter().map(|node| latest_block(client, node).ok()).collect();
+    for (node, block) in nodes.iter().zip(&blocks) {
+        match block {
+            Some((number, hash)) => println!("{:<8} #{:<8} {}", node.name, number, hash),
+            None => println!("{:<8} not ready", node.name),
+        }
+    }
+
+    let blocks: Vec<_> = blocks.into_iter().flatten().collect();
+    if blocks.len() < nodes.len() {
+        println!("Consensus => waiting for {} node(s)", nodes.len() - blocks.len());
+        return None;
+    }
+    let min = blocks.iter().map(|(number, _)| *number).min().unwrap_or_default();
+    let max = blocks.iter().map(|(number, _)| *number).max().unwrap_or_default();
+    if blocks.iter().all(|block| block.1 == blocks[0].1) {
+        println!("Consensus => in sync at #{}", min);
+        return Some(min);
+    } else if min == max {
+        println!("Consensus => forked at #{}", min);
+    } else {
+        println!("Consensus => syncing, heights #{} ~ #{}", min, max);
+    }
+    None
+}
+
+fn shutdown(children: &mut [Child], interrupted: bool) {
+    eprintln!("Shutting down the devnet...");
+    // NOTE: Ctrl-C is also sent to children in the same process group, give them time to close db gracefully.
+    let started_at = Instant::now();
+    while interrupted && started_at.elapsed() < Duration::from_secs(10) {
+        if children
+            .iter_mut()
+            .all(|child| child.try_wait().map(|s| s.is_some()).unwrap_or(true))
+        {
+            return;
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+    for child in children.iter_mut() {
+        if let Ok(None) = child.try_wait() {
+            eprintln!("kill pid={}", child.id());
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::Path;
+
+use clap::ArgMatches;
+use config::genesis::{Alloc, GenesisConfig, Witness};
+use keys::{ExtendedPrivate, KeyPair};
+use serde::Serialize;
+
+type Error = Box<dyn std::error::Error>;
+
+pub fn main(matches: &ArgMatches<'_>) -> Result<(), Error> {
+    match matches.subcommand() {
+        ("new", Some(arg_matches)) => new_genesis(arg_matches),
+        _ => {
+            eprintln!("{}", matches.usage());
+            Ok(())
+        }
+    }
+}
+
+/// Generated accounts, saved as `keys.json`.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct GeneratedKey {
+    name: String,
+    address: String,
+    private_key: String,
+}
+
+impl From<(&str, &KeyPair)> for GeneratedKey {
+    fn from((name, kp): (&str, &KeyPair)) -> Self {
+        GeneratedKey {
+            name: name.into(),
+            address: kp.address().to_string(),
+            private_key: kp.private().to_string(),
+        }
+    }
+}
+
+/// A private network of generated witnesses and pre-funded accounts.
+pub struct PrivateNet {
+    pub genesis: GenesisConfig,
+    pub witness_kps: Vec<KeyPair>,
+    pub account_kps: Vec<KeyPair>,
+}
+
+impl PrivateNet {
+    /// Generate witnesses, each with `votes`, and fund every account with `balance`.
+    pub fn generate(
+        num_witnesses: usize,
+        votes: i64,
+        account_kps: Vec<KeyPair>,
+        balance: i64,
+        timestamp: i64,
+    ) -> Result<Self, Error> {
+        if num_witnesses == 0 {
+            return Err("at least 1 witness is required".into());
+        }
+
+        let witness_kps: Vec<_> = (0..num_witnesses).map(|_| KeyPair::generate()).collect();
+
+        let witnesses = witness_kps
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| Witness {
+                address: kp.address().to_string(),
+                url: format!("http://witness{}.local", i + 1),
+                votes,
+            })
+            .collect();
+        let allocs = account_kps
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| Alloc {
+                address: kp.address().to_string(),
+                name: format!("InitialAccount{}", i + 1),
+                balance,
+            })
+            .collect();
+
+        Ok(PrivateNet {
+            genesis: GenesisConfig::new(timestamp, witnesses, allocs),
+            witness_kps,
+            account_kps,
+        })
+    }
+
+    /// Save `genesis.json` and `keys.json` to the directory.
+    pub fn save(&self, dir: &Path) -> Result<(), Error> {
+        fs::create_dir_all(dir)?;
+        self.genesis.save_to_file(dir.join("genesis.json"))?;
+
+        let mut keys: Vec<GeneratedKey> = self
+            .witness_kps
+            .iter()
+            .enumerate()
+            .map(|(i, kp)| (&*format!("Witness{}", i + 1), kp).into())
+            .collect();
+        keys.extend(
+            self.account_kps
+                .iter()
+                .enumerate()
+                .map(|(i, kp)| GeneratedKey::from((&*format!("InitialAccount{}", i + 1), kp))),
+        );
+        fs::write(dir.join("keys.json"), serde_json::to_string_pretty(&keys)?)?;
+
+        for key in &keys {
+            println!("{:<16} {}", key.name, key.address);
+        }
+        Ok(())
+    }
+}
+
+fn new_genesis(matches: &ArgMatches<'_>) -> Result<(), Error> {
+    let num_witnesses: usize = matches.value_of("witnesses").expect("has default; qed").parse()?;
+    let timestamp: i64 = matches.value_of("timestamp").expect("has default; qed").parse()?;
+    let votes: i64 = matches.value_of("votes").expect("has default; qed").parse()?;
+    let balance: i64 = matches.value_of("balance").expect("has default; qed").parse()?;
+    let output = Path::new(matches.value_of("output").expect("has default; qed"));
+    let template = matches.value_of("template").expect("has default; qed");
+    let num_accounts: usize = matches.value_of("accounts").expect("has default; qed").parse()?;
+    let account_kps = match matches.value_of("mnemonic") {
+        Some(phrase) => derive_accounts(phrase, num_accounts)?,
+        None => (0..num_accounts).map(|_| KeyPair::generate()).collect(),
+    };
+
+    let net = PrivateNet::generate(num_witnesses, votes, account_kps, balance, timestamp)?;
+    net.save(output)?;
+
+    let mut conf = load_config_template(template)?;
+    set_config_value(&mut conf, &["chain", "genesis"], "genesis.json".into())?;
+    set_producer_keys(&mut conf, &net.witness_kps)?;
+    if matches.is_present("instant-seal") {
+        set_config_value(&mut conf, &["producer", "instant-seal"], true.into())?;
+    }
+    save_config(&conf, &output.join("conf.toml"))?;
+
+    println!("Genesis => {}", output.join("genesis.json").display());
+    println!("Config  => {}", output.join("conf.toml").display());
+    println!("Keys    => {}", output.join("keys.json").display());
+    Ok(())
+}
+
+/// Accounts derived from a mnemonic, along `m/44'/195'/0'/0/i`, so that dev accounts are reproducible.
+fn derive_accounts(phrase: &str, num_accounts: usize) -> Result<Vec<KeyPair>, Error> {
+    let master = ExtendedPrivate::from_mnemonic(phrase, "")?;
+    (0..num_accounts as u32)
+        .map(|i| Ok(master.derive_path(&keys::tron_derivation_path(0, i))?.to_keypair()?))
+        .collect()
+}
+
+pub fn load_config_template(path: &str) -> Result<toml::Value, Error> {
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Set a config value by path of keys, like `["protocol", "channel", "endpoint"]`. Missing tables are created.
+pub fn set_config_value(conf: &mut toml::Value, path: &[&str], value: toml::Value) -> Result<(), Error> {
+    let (key, tables) = path.split_last().ok_or("empty config path")?;
+    let mut table = conf.as_table_mut().ok_or("malformed config")?;
+    for name in tables {
+        table = table
+            .entry(*name)
+            .or_insert_with(|| toml::Value::Table(Default::default()))
+            .as_table_mut()
+            .ok_or_else(|| format!("malformed [{}] of config", name))?;
+    }
+    table.insert((*key).into(), value);
+    Ok(())
+}
+
+/// Enable block producing with the witness keys.
+pub fn set_producer_keys(conf: &mut toml::Value, witness_kps: &[KeyPair]) -> Result<(), Error> {
+    let keypairs = witness_kps
+        .iter()
+        .map(|kp| {
+            let mut table = toml::value::Table::new();
+            table.insert("address".into(), kp.address().to_string().into());
+            table.insert("private-key".into(), kp.private().to_string().into());
+            toml::Value::Table(table)
+        })
+        .collect::<Vec<_>>();
+    let mut producer = toml::value::Table::new();
+    producer.insert("enable".into(), true.into());
+    producer.insert("keypair".into(), keypairs.into());
+    set_config_value(conf, &["producer"], producer.into())
+}
+
+/// Validate and save the config file.
+pub fn save_config(conf: &toml::Value, path: &Path) -> Result<(), Error> {
+    let content = toml::to_string_pretty(conf)?;
+    config::Config::load_from_str(&content)?;
+    fs::write(path, content)?;
+    Ok(())
+}
@@ -1,5 +1,7 @@
+use std::io::{self, BufRead};
+
 use clap::ArgMatches;
-use keys::{Address, KeyPair};
+use keys::{Address, ExtendedPrivate, KeyPair};
 
 type Error = Box<dyn std::error::Error>;
 
@@ -7,7 +9,8 @@ pub fn main(matches: &ArgMatches<'_>) -> Result<(), Error> {
     match matches.subcommand() {
         ("generate", _) => generate_key(),
         ("inspect", Some(arg_matches)) => inspect_key(arg_matches),
-        // ("generate-genesis-key", _) => unimplemented!(),
+        ("mnemonic", Some(arg_matches)) => generate_mnemonic(arg_matches),
+        ("derive", Some(arg_matches)) => derive_keys(arg_matches),
         _ => {
             eprintln!("{}", matches.usage());
             Ok(())
@@ -50,6 +53,56 @@ fn inspect_key(matches: &ArgMatches<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+fn generate_mnemonic(matches: &ArgMatches<'_>) -> Result<(), Error> {
+    let words = matches.value_of("words").expect("has default; qed").parse()?;
+    let phrase = keys::generate_mnemonic(words)?;
+    let path = keys::tron_derivation_path(0, 0);
+    let kp = ExtendedPrivate::from_mnemonic(&phrase, "")?
+        .derive_path(&path)?
+        .to_keypair()?;
+
+    println!("Mnemonic: {}", phrase);
+    println!("Path:     {}", path);
+    pprint_address(&kp.address());
+    Ok(())
+}
+
+fn derive_keys(matches: &ArgMatches<'_>) -> Result<(), Error> {
+    let phrase = match matches.value_of("mnemonic") {
+        Some(phrase) => phrase.to_owned(),
+        None => {
+            eprintln!("Mnemonic:");
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line)?;
+            line
+        }
+    };
+    let passphrase = matches.value_of("passphrase").unwrap_or_default();
+    let master = ExtendedPrivate::from_mnemonic(phrase.trim(), passphrase)?;
+
+    let paths: Vec<String> = match matches.value_of("path") {
+        Some(path) => vec![path.to_owned()],
+        None => {
+            let account = matches.value_of("account").expect("has default; qed").parse()?;
+            let index: u32 = matches.value_of("index").expect("has default; qed").parse()?;
+            let count: u32 = matches.value_of("count").expect("has default; qed").parse()?;
+            (index..index + count)
+                .map(|i| keys::tron_derivation_path(account, i))
+                .collect()
+        }
+    };
+
+    for path in paths {
+        let kp = master.derive_path(&path)?.to_keypair()?;
+        if matches.is_present("show-private") {
+            println!("{} {} {}", path, kp.address(), kp.private());
+        } else {
+            println!("{} {}", path, kp.address());
+        }
+    }
+    Ok(())
+}
+
 fn pprint_address(address: &Address) {
     println!("Address:");
     println!(" - Base58: {:}", address);
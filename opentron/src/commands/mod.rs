@@ -1,4 +1,6 @@
 pub mod check;
 pub mod dev;
+pub mod devnet;
 pub mod fix;
+pub mod genesis;
 pub mod key;
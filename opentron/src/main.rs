@@ -1,19 +1,22 @@
 // NOTE: Embedding slog macros and select! requires increasing recursion_limit.
 #![recursion_limit = "1024"]
 use std::error::Error;
+use std::net::SocketAddr;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use futures::channel::oneshot;
 use futures::join;
-use log::info;
+use log::{info, warn};
 use slog::{o, slog_debug, slog_info, Drain};
 use slog_scope_futures::FutureExt as SlogFutureExt;
 
 use channel_service::server::channel_server;
 use context::AppContext;
 use discovery_service::server::discovery_server;
-use graphql_service::server::graphql_server;
+use graphql_service::server::{admin_graphql_server, graphql_server};
 use opentron::util::get_my_ip;
 use producer_service::producer_task;
 
@@ -27,6 +30,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             opentron::commands::key::main(arg_matches)?;
             return Ok(());
         }
+        ("genesis", Some(arg_matches)) => {
+            opentron::commands::genesis::main(arg_matches)?;
+            return Ok(());
+        }
+        ("devnet", Some(arg_matches)) => {
+            opentron::commands::devnet::main(arg_matches)?;
+            return Ok(());
+        }
         _ => (),
     }
 
@@ -57,7 +68,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     slog_info!(slog_scope::logger(), "use config file"; "path" => config_file);
     let mut ctx = AppContext::from_config(config_file)?;
-    let outbound_ip = get_my_ip().unwrap_or("127.0.0.1".into());
+    let outbound_ip = match ctx.config.protocol.channel.advertised_endpoint.parse::<SocketAddr>() {
+        Ok(addr) => addr.ip().to_string(),
+        Err(_) => get_my_ip().unwrap_or("127.0.0.1".into()),
+    };
     info!("outbound ip address: {}", outbound_ip);
     ctx.outbound_ip = outbound_ip;
 
@@ -91,9 +105,16 @@ async fn run(ctx: AppContext) -> Result<(), Box<dyn Error>> {
     let termination_handler = {
         let ctx = ctx.clone();
         move || {
+            // stop block producing and syncing
             ctx.running.store(false, Ordering::SeqCst);
-            ctx.chain_db.report_status();
             let _ = ctx.termination_signal.send(());
+            wait_for_connections_closed(&ctx, Duration::from_secs(5));
+
+            // wait for the block being processed, and keep the lock until closed
+            let mut manager = ctx.manager.write().unwrap_or_else(|e| e.into_inner());
+            manager.prepare_close();
+            info!("state-db closed at block #{}", manager.latest_block_number());
+            ctx.chain_db.report_status();
             unsafe {
                 ctx.chain_db.prepare_close();
             }
@@ -121,6 +142,13 @@ async fn run(ctx: AppContext) -> Result<(), Box<dyn Error>> {
         graphql_server(ctx, done_signal).with_logger(logger)
     };
 
+    let admin_service = {
+        let ctx = ctx.clone();
+        let done_signal = ctx.termination_signal.subscribe();
+        let logger = slog_scope::logger().new(o!("service" => "admin"));
+        admin_graphql_server(ctx, done_signal).with_logger(logger)
+    };
+
     let channel_service = {
         let ctx = ctx.clone();
         let done_signal = ctx.termination_signal.subscribe();
@@ -140,7 +168,29 @@ async fn run(ctx: AppContext) -> Result<(), Box<dyn Error>> {
         producer_task(ctx, done_signal)
     };
 
-    let _ = join!(graphql_service, channel_service, discovery_service, producer_task);
+    let _ = join!(
+        graphql_service,
+        admin_service,
+        channel_service,
+        discovery_service,
+        producer_task
+    );
 
     Ok(termination_done.await?)
 }
+
+fn wait_for_connections_closed(ctx: &AppContext, timeout: Duration) {
+    let started_at = Instant::now();
+    loop {
+        let num_connections = ctx.num_active_connections.load(Ordering::SeqCst) +
+            ctx.num_passive_connections.load(Ordering::SeqCst);
+        if num_connections == 0 {
+            break;
+        }
+        if started_at.elapsed() > timeout {
+            warn!("timeout, {} connections are still open", num_connections);
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
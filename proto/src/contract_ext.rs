@@ -113,6 +113,11 @@ impl_contract_ext_for!(UpdateEnergyLimitContract);
 impl_contract_ext_for!(ClearAbiContract, "ClearABIContract");
 impl_contract_ext_for!(FreezeBalanceContract);
 impl_contract_ext_for!(UnfreezeBalanceContract);
+impl_contract_ext_for!(FreezeBalanceV2Contract);
+impl_contract_ext_for!(UnfreezeBalanceV2Contract);
+impl_contract_ext_for!(WithdrawExpireUnfreezeContract);
+impl_contract_ext_for!(DelegateResourceContract);
+impl_contract_ext_for!(UnDelegateResourceContract);
 impl_contract_ext_for!(ProposalCreateContract);
 impl_contract_ext_for!(ProposalApproveContract);
 impl_contract_ext_for!(ProposalDeleteContract);
@@ -120,6 +125,8 @@ impl_contract_ext_for!(ExchangeCreateContract);
 impl_contract_ext_for!(ExchangeInjectContract);
 impl_contract_ext_for!(ExchangeWithdrawContract);
 impl_contract_ext_for!(ExchangeTransactionContract);
+impl_contract_ext_for!(MarketSellAssetContract);
+impl_contract_ext_for!(MarketCancelOrderContract);
 
 #[cfg(feature = "nile")]
 impl ContractExt for ::proto::contract::ShieldedTransferContract {
@@ -130,3 +137,62 @@ impl ContractExt for ::proto::contract::ShieldedTransferContract {
         ContractType::ShieldedTransferContract
     }
 }
+
+impl Contract {
+    /// Owner address of the builtin contract, None if the contract is unknown or malformed.
+    pub fn owner_address(&self) -> Option<Vec<u8>> {
+        use crate::contract::*;
+
+        macro_rules! owner_address_of {
+            ($($contract_ty:ident),*) => {
+                match ContractType::from_i32(self.r#type)? {
+                    $(
+                        ContractType::$contract_ty => {
+                            $contract_ty::from_any(self.parameter.as_ref()?).map(|cntr| cntr.owner_address().to_vec())
+                        }
+                    )*
+                    _ => None,
+                }
+            };
+        }
+
+        owner_address_of!(
+            AccountCreateContract,
+            AccountUpdateContract,
+            SetAccountIdContract,
+            AccountPermissionUpdateContract,
+            TransferContract,
+            TransferAssetContract,
+            AssetIssueContract,
+            ParticipateAssetIssueContract,
+            UpdateAssetContract,
+            UnfreezeAssetContract,
+            WitnessCreateContract,
+            WitnessUpdateContract,
+            UpdateBrokerageContract,
+            VoteWitnessContract,
+            WithdrawBalanceContract,
+            CreateSmartContract,
+            TriggerSmartContract,
+            UpdateSettingContract,
+            UpdateEnergyLimitContract,
+            ClearAbiContract,
+            FreezeBalanceContract,
+            UnfreezeBalanceContract,
+            FreezeBalanceV2Contract,
+            UnfreezeBalanceV2Contract,
+            WithdrawExpireUnfreezeContract,
+            DelegateResourceContract,
+            UnDelegateResourceContract,
+            ProposalCreateContract,
+            ProposalApproveContract,
+            ProposalDeleteContract,
+            ExchangeCreateContract,
+            ExchangeInjectContract,
+            ExchangeWithdrawContract,
+            ExchangeTransactionContract,
+            MarketSellAssetContract,
+            MarketCancelOrderContract
+        )
+    }
+}
@@ -161,16 +161,33 @@ pub mod state {
             Err(())
         }
 
+        /// Total frozen amount of the account, including delegated out, both legacy and Stake 2.0.
+        pub fn frozen_amount(&self) -> i64 {
+            self.frozen_amount_for_bandwidth +
+                self.frozen_amount_for_energy +
+                self.delegated_out_amount +
+                self.frozen_v2_amount_for_bandwidth +
+                self.frozen_v2_amount_for_energy +
+                self.delegated_v2_out_amount_for_bandwidth +
+                self.delegated_v2_out_amount_for_energy
+        }
+
         pub fn tron_power(&self) -> i64 {
-            (self.frozen_amount_for_bandwidth + self.frozen_amount_for_energy + self.delegated_out_amount) / 1_000_000
+            self.frozen_amount() / 1_000_000
         }
 
         pub fn amount_for_bandwidth(&self) -> i64 {
-            self.frozen_amount_for_bandwidth + self.delegated_frozen_amount_for_bandwidth
+            self.frozen_amount_for_bandwidth +
+                self.delegated_frozen_amount_for_bandwidth +
+                self.frozen_v2_amount_for_bandwidth +
+                self.delegated_v2_frozen_amount_for_bandwidth
         }
 
         pub fn amount_for_energy(&self) -> i64 {
-            self.frozen_amount_for_energy + self.delegated_frozen_amount_for_energy
+            self.frozen_amount_for_energy +
+                self.delegated_frozen_amount_for_energy +
+                self.frozen_v2_amount_for_energy +
+                self.delegated_v2_frozen_amount_for_energy
         }
 
         pub fn resource(&self) -> &AccountResource {
@@ -190,6 +207,37 @@ pub mod state {
                 ResourceCode::Energy => self.delegated_frozen_amount_for_energy,
             }
         }
+
+        /// Stake 2.0, frozen amount of oneself.
+        pub fn frozen_v2_amount(&self, res: ResourceCode) -> i64 {
+            match res {
+                ResourceCode::Bandwidth => self.frozen_v2_amount_for_bandwidth,
+                ResourceCode::Energy => self.frozen_v2_amount_for_energy,
+            }
+        }
+
+        pub fn frozen_v2_amount_mut(&mut self, res: ResourceCode) -> &mut i64 {
+            match res {
+                ResourceCode::Bandwidth => &mut self.frozen_v2_amount_for_bandwidth,
+                ResourceCode::Energy => &mut self.frozen_v2_amount_for_energy,
+            }
+        }
+
+        /// Stake 2.0, frozen amount delegated from others.
+        pub fn delegated_v2_frozen_amount_mut(&mut self, res: ResourceCode) -> &mut i64 {
+            match res {
+                ResourceCode::Bandwidth => &mut self.delegated_v2_frozen_amount_for_bandwidth,
+                ResourceCode::Energy => &mut self.delegated_v2_frozen_amount_for_energy,
+            }
+        }
+
+        /// Stake 2.0, frozen amount delegated to others.
+        pub fn delegated_v2_out_amount_mut(&mut self, res: ResourceCode) -> &mut i64 {
+            match res {
+                ResourceCode::Bandwidth => &mut self.delegated_v2_out_amount_for_bandwidth,
+                ResourceCode::Energy => &mut self.delegated_v2_out_amount_for_energy,
+            }
+        }
     }
 
     impl Proposal {
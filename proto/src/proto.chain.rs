@@ -285,4 +285,10 @@ pub enum ContractType {
     /// NOTE: Introduced in 4.1
     MarketSellAssetContract = 52,
     MarketCancelOrderContract = 53,
+    /// NOTE: Introduced in 4.7, Stake 2.0
+    FreezeBalanceV2Contract = 54,
+    UnfreezeBalanceV2Contract = 55,
+    WithdrawExpireUnfreezeContract = 56,
+    DelegateResourceContract = 57,
+    UnDelegateResourceContract = 58,
 }
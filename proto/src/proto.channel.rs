@@ -70,6 +70,72 @@ pub struct Transactions {
     #[prost(message, repeated, tag="1")]
     pub transactions: ::prost::alloc::vec::Vec<super::chain::Transaction>,
 }
+//
+//message Items {
+//enum ItemType {
+//ERR = 0;
+//TRX = 1;
+//BLOCK = 2;
+//BLOCKHEADER = 3;
+//}
+//
+//ItemType type = 1;
+//repeated Block blocks = 2;
+//repeated BlockHeader block_headers = 3;
+//repeated Transaction transactions = 4;
+//}
+
+/// PBFT consensus message, signed by an SR.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PbftMessage {
+    #[prost(message, optional, tag="1")]
+    pub raw_data: ::core::option::Option<pbft_message::Raw>,
+    /// signature of sha256(raw_data)
+    #[prost(bytes="vec", tag="2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+/// Nested message and enum types in `PbftMessage`.
+pub mod pbft_message {
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Raw {
+        #[prost(enumeration="Type", tag="1")]
+        pub msg_type: i32,
+        #[prost(enumeration="DataType", tag="2")]
+        pub data_type: i32,
+        /// block number, when data type is BLOCK
+        #[prost(int64, tag="3")]
+        pub view_n: i64,
+        #[prost(int64, tag="4")]
+        pub epoch: i64,
+        /// block hash, when data type is BLOCK
+        #[prost(bytes="vec", tag="5")]
+        pub data: ::prost::alloc::vec::Vec<u8>,
+    }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Type {
+        ViewChange = 0,
+        Request = 1,
+        Preprepare = 2,
+        Prepare = 3,
+        Commit = 4,
+    }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum DataType {
+        Block = 0,
+        Srl = 1,
+    }
+}
+/// Signatures of a PBFT commit, from 2/3+1 SRs.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PbftCommitResult {
+    /// encoded PbftMessage.Raw of the commit
+    #[prost(bytes="vec", tag="1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes="vec", repeated, tag="2")]
+    pub signature: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum ReasonCode {
@@ -286,6 +286,59 @@ pub struct UnfreezeBalanceContract {
     #[prost(bytes="vec", tag="15")]
     pub receiver_address: ::prost::alloc::vec::Vec<u8>,
 }
+/// Stake 2.0, freeze for oneself, without a frozen duration.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FreezeBalanceV2Contract {
+    #[prost(bytes="vec", tag="1")]
+    pub owner_address: ::prost::alloc::vec::Vec<u8>,
+    #[prost(int64, tag="2")]
+    pub frozen_balance: i64,
+    #[prost(enumeration="super::common::ResourceCode", tag="3")]
+    pub resource: i32,
+}
+/// Stake 2.0, unfrozen balance can be withdrawn after `UnfreezeDelayDays`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnfreezeBalanceV2Contract {
+    #[prost(bytes="vec", tag="1")]
+    pub owner_address: ::prost::alloc::vec::Vec<u8>,
+    #[prost(int64, tag="2")]
+    pub unfreeze_balance: i64,
+    #[prost(enumeration="super::common::ResourceCode", tag="3")]
+    pub resource: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WithdrawExpireUnfreezeContract {
+    #[prost(bytes="vec", tag="1")]
+    pub owner_address: ::prost::alloc::vec::Vec<u8>,
+}
+/// Stake 2.0, delegate frozen resource to others.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DelegateResourceContract {
+    #[prost(bytes="vec", tag="1")]
+    pub owner_address: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration="super::common::ResourceCode", tag="2")]
+    pub resource: i32,
+    #[prost(int64, tag="3")]
+    pub balance: i64,
+    #[prost(bytes="vec", tag="4")]
+    pub receiver_address: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag="5")]
+    pub lock: bool,
+    /// In blocks.
+    #[prost(int64, tag="6")]
+    pub lock_period: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnDelegateResourceContract {
+    #[prost(bytes="vec", tag="1")]
+    pub owner_address: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration="super::common::ResourceCode", tag="2")]
+    pub resource: i32,
+    #[prost(int64, tag="3")]
+    pub balance: i64,
+    #[prost(bytes="vec", tag="4")]
+    pub receiver_address: ::prost::alloc::vec::Vec<u8>,
+}
 // # Proposal
 
 #[derive(Clone, PartialEq, ::prost::Message)]
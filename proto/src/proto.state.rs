@@ -88,6 +88,33 @@ pub struct Account {
     /// deprecated, but still exists
     #[prost(bytes="vec", tag="20")]
     pub account_id: ::prost::alloc::vec::Vec<u8>,
+    /// Stake 2.0, frozen resource of oneself
+    #[prost(int64, tag="21")]
+    pub frozen_v2_amount_for_bandwidth: i64,
+    #[prost(int64, tag="22")]
+    pub frozen_v2_amount_for_energy: i64,
+    /// Stake 2.0, delegated in
+    #[prost(int64, tag="23")]
+    pub delegated_v2_frozen_amount_for_bandwidth: i64,
+    #[prost(int64, tag="24")]
+    pub delegated_v2_frozen_amount_for_energy: i64,
+    /// Stake 2.0, delegated out
+    #[prost(int64, tag="25")]
+    pub delegated_v2_out_amount_for_bandwidth: i64,
+    #[prost(int64, tag="26")]
+    pub delegated_v2_out_amount_for_energy: i64,
+    /// Stake 2.0, unfreezing balances, waiting for withdrawal
+    #[prost(message, repeated, tag="27")]
+    pub unfreezing_v2: ::prost::alloc::vec::Vec<UnfreezingV2>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnfreezingV2 {
+    #[prost(enumeration="super::common::ResourceCode", tag="1")]
+    pub resource: i32,
+    #[prost(int64, tag="2")]
+    pub amount: i64,
+    #[prost(int64, tag="3")]
+    pub expiration_timestamp: i64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Witness {
@@ -117,6 +144,14 @@ pub struct Witness {
     #[prost(bytes="vec", tag="11")]
     pub signature_key: ::prost::alloc::vec::Vec<u8>,
 }
+/// Block producing statistic of a witness in one epoch.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WitnessEpochStatistic {
+    #[prost(int64, tag="1")]
+    pub produced: i64,
+    #[prost(int64, tag="2")]
+    pub missed: i64,
+}
 /// Witness reward info of one epoch.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WitnessVoterReward {
@@ -148,6 +183,15 @@ pub struct Votes {
     #[prost(int64, tag="2")]
     pub epoch: i64,
 }
+/// Vote change of an account in current epoch, applied to witnesses at maintenance.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VoteChange {
+    /// account votes at the first change of the epoch
+    #[prost(message, repeated, tag="1")]
+    pub old_votes: ::prost::alloc::vec::Vec<super::common::Vote>,
+    #[prost(message, repeated, tag="2")]
+    pub new_votes: ::prost::alloc::vec::Vec<super::common::Vote>,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Proposal {
     #[prost(int64, tag="1")]
@@ -355,6 +399,9 @@ pub struct TransactionReceipt {
     pub exchange_injected_amount: i64,
     #[prost(int64, tag="19")]
     pub exchange_withdrawal_amount: i64,
+    /// Stake 2.0, withdrawn unfreezing balance
+    #[prost(int64, tag="20")]
+    pub withdrawal_expire_amount: i64,
 }
 /// Chain parameters, known as proposals, can be changed via proposal.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -454,6 +501,17 @@ pub enum ChainParameter {
     ///
     /// Default: 0
     AllowBlackholeOptimization = 49,
+    /// Stake 2.0. Enables `FreezeBalanceV2`, `UnfreezeBalanceV2`, `DelegateResource`, `UnDelegateResource` and
+    /// `WithdrawExpireUnfreeze`. Legacy freezing is forbidden then.
+    ///
+    /// Enabled: 4.7
+    ///
+    /// Note: non-zero to enable
+    ///
+    /// Default: 0
+    ///
+    /// Range: [1, 365]
+    UnfreezeDelayDays = 70,
     /// # Fees
     ///
     /// Fees, in SUN.
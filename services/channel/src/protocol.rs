@@ -5,7 +5,7 @@ use prost::Message;
 use proto::chain::Block;
 use proto::channel::{
     inventory::Type as InventoryType, BlockInventory, ChainInventory, HandshakeDisconnect, HandshakeHello, Inventory,
-    ReasonCode as DisconnectReasonCode, Transactions,
+    PbftCommitResult, PbftMessage, ReasonCode as DisconnectReasonCode, Transactions,
 };
 use std::convert::TryFrom;
 use std::io::{self, Cursor};
@@ -37,6 +37,9 @@ pub enum ChannelMessage {
 
     Ping,
     Pong,
+
+    PbftCommit(PbftCommitResult),
+    PbftMessage(PbftMessage),
 }
 
 impl ChannelMessage {
@@ -58,6 +61,9 @@ impl ChannelMessage {
 
             Ping => 0x22,
             Pong => 0x23,
+
+            PbftCommit(_) => 0x40,
+            PbftMessage(_) => 0x41,
         }
     }
 
@@ -84,6 +90,8 @@ impl ChannelMessage {
             BlockchainInventory(ref chain_inv) => chain_inv.encode(dst),
             HandshakeHello(ref hello) => hello.encode(dst),
             HandshakeDisconnect(ref disconnect) => disconnect.encode(dst),
+            PbftCommit(ref commit) => commit.encode(dst),
+            PbftMessage(ref msg) => msg.encode(dst),
         };
         ret.map_err(From::from)
     }
@@ -103,6 +111,8 @@ impl ChannelMessage {
             BlockchainInventory(ref chain_inv) => chain_inv.encoded_len(),
             HandshakeHello(ref hello) => hello.encoded_len(),
             HandshakeDisconnect(ref disconnect) => disconnect.encoded_len(),
+            PbftCommit(ref commit) => commit.encoded_len(),
+            PbftMessage(ref msg) => msg.encoded_len(),
         };
         pb_len + 1
     }
@@ -157,6 +167,15 @@ impl ::std::fmt::Debug for ChannelMessage {
                 hello.timestamp,
             ),
             HandshakeDisconnect(ref disconnect) => write!(f, "HandshakeDisconnect(reason={})", disconnect.reason),
+            PbftCommit(ref commit) => write!(f, "PbftCommit(|sigs|={})", commit.signature.len()),
+            PbftMessage(ref msg) => match msg.raw_data {
+                Some(ref raw) => write!(
+                    f,
+                    "PbftMessage(type={}, view_n={}, epoch={})",
+                    raw.msg_type, raw.view_n, raw.epoch
+                ),
+                None => write!(f, "PbftMessage(malformed)"),
+            },
         }
     }
 }
@@ -201,6 +220,9 @@ impl TryFrom<&[u8]> for ChannelMessage {
                 assert!(buf[1] == 0xC0);
                 Ok(ChannelMessage::Pong)
             }
+
+            0x40 => Ok(ChannelMessage::PbftCommit(Message::decode(&buf[1..])?)),
+            0x41 => Ok(ChannelMessage::PbftMessage(Message::decode(&buf[1..])?)),
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, "invalid data")),
         }
     }
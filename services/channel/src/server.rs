@@ -7,7 +7,8 @@ use std::sync::Arc;
 use byteorder::{ByteOrder, BE};
 use chain::IndexedBlock;
 use chrono::Utc;
-use context::AppContext;
+use context::peer::{PeerDirection, PeerInfo};
+use context::{pbft, AppContext};
 use futures::future::FutureExt;
 use futures::join;
 use futures::sink::{Sink, SinkExt};
@@ -24,6 +25,7 @@ use slog_scope_futures::FutureExt as SlogFutureExt;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
+use tokio::sync::oneshot;
 use tokio::time::Duration;
 use tokio::time::{sleep, timeout};
 use tokio_stream::StreamExt;
@@ -78,12 +80,18 @@ async fn passive_channel_service(
                     loop {
                         let ctx = ctx.clone();
                         let (sock, peer_addr) = listener.accept().await?;
+                        if ctx.peers.is_banned(&peer_addr.ip()) {
+                            debug!("reject banned peer {}", peer_addr);
+                            continue;
+                        }
                         ctx.num_passive_connections.fetch_add(1, Ordering::SeqCst);
                         let logger = slog_scope::logger().new(o!(
                             "peer_addr" => peer_addr,
                         ));
                         tokio::spawn(async move {
-                            let _ = handshake_handler(ctx.clone(), sock).with_logger(logger).await;
+                            let _ = handshake_handler(ctx.clone(), sock, PeerDirection::Passive)
+                                .with_logger(logger)
+                                .await;
                             ctx.num_passive_connections.fetch_sub(1, Ordering::SeqCst);
                         });
                     }
@@ -112,35 +120,54 @@ async fn active_channel_service(ctx: Arc<AppContext>) -> Result<(), Box<dyn Erro
 
     let active_service = {
         let ctx = ctx.clone();
-        let active_nodes = ctx.config.protocol.channel.active_nodes.clone();
         tokio::spawn(async move {
-            for peer_addr in active_nodes.into_iter().cycle() {
-                while ctx.num_active_connections.load(Ordering::SeqCst) >= max_active_connections {
+            // NOTE: Active nodes can be changed by admin API, so the list is reloaded for each round.
+            'outer: loop {
+                let active_nodes = ctx.peers.active_nodes();
+                if active_nodes.is_empty() {
+                    if !ctx.running.load(Ordering::Relaxed) {
+                        warn!("active connection service closed");
+                        break;
+                    }
                     sleep(Duration::from_secs(2)).await;
+                    continue;
                 }
-                if !ctx.running.load(Ordering::Relaxed) {
-                    warn!("active connection service closed");
-                    break;
-                }
-                ctx.chain_db.await_background_jobs();
-                if !ctx.running.load(Ordering::Relaxed) {
-                    warn!("active connection service closed");
-                    break;
-                }
-                info!("active connection to {}", peer_addr);
-                let logger = slog_scope::logger().new(o!(
-                    "peer_addr" => peer_addr.clone(),
-                ));
-                match timeout(Duration::from_secs(10), TcpStream::connect(&peer_addr)).await {
-                    Err(_) => slog_warn!(logger, "connect timeout"),
-                    Ok(Err(e)) => slog_warn!(logger, "connect failed: {}", e),
-                    Ok(Ok(sock)) => {
-                        ctx.num_active_connections.fetch_add(1, Ordering::SeqCst);
-                        let ctx = ctx.clone();
-                        tokio::spawn(async move {
-                            let _ = handshake_handler(ctx.clone(), sock).with_logger(logger).await;
-                            ctx.num_active_connections.fetch_sub(1, Ordering::SeqCst);
-                        });
+                for peer_addr in active_nodes {
+                    while ctx.num_active_connections.load(Ordering::SeqCst) >= max_active_connections {
+                        sleep(Duration::from_secs(2)).await;
+                    }
+                    if !ctx.running.load(Ordering::Relaxed) {
+                        warn!("active connection service closed");
+                        break 'outer;
+                    }
+                    ctx.chain_db.await_background_jobs();
+                    if !ctx.running.load(Ordering::Relaxed) {
+                        warn!("active connection service closed");
+                        break 'outer;
+                    }
+                    if let Ok(addr) = peer_addr.parse::<SocketAddr>() {
+                        if ctx.peers.is_banned(&addr.ip()) || ctx.peers.is_connected(&addr) {
+                            sleep(Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    }
+                    info!("active connection to {}", peer_addr);
+                    let logger = slog_scope::logger().new(o!(
+                        "peer_addr" => peer_addr.clone(),
+                    ));
+                    match timeout(Duration::from_secs(10), TcpStream::connect(&peer_addr)).await {
+                        Err(_) => slog_warn!(logger, "connect timeout"),
+                        Ok(Err(e)) => slog_warn!(logger, "connect failed: {}", e),
+                        Ok(Ok(sock)) => {
+                            ctx.num_active_connections.fetch_add(1, Ordering::SeqCst);
+                            let ctx = ctx.clone();
+                            tokio::spawn(async move {
+                                let _ = handshake_handler(ctx.clone(), sock, PeerDirection::Active)
+                                    .with_logger(logger)
+                                    .await;
+                                ctx.num_active_connections.fetch_sub(1, Ordering::SeqCst);
+                            });
+                        }
                     }
                 }
             }
@@ -150,7 +177,12 @@ async fn active_channel_service(ctx: Arc<AppContext>) -> Result<(), Box<dyn Erro
     Ok(())
 }
 
-async fn handshake_handler(ctx: Arc<AppContext>, mut sock: TcpStream) -> Result<(), Box<dyn Error>> {
+async fn handshake_handler(
+    ctx: Arc<AppContext>,
+    mut sock: TcpStream,
+    direction: PeerDirection,
+) -> Result<(), Box<dyn Error>> {
+    let peer_addr = sock.peer_addr()?;
     let (reader, writer) = sock.split();
 
     let mut reader = ChannelMessageCodec::new_read(reader);
@@ -215,6 +247,7 @@ async fn handshake_handler(ctx: Arc<AppContext>, mut sock: TcpStream) -> Result<
 
         match payload.unwrap() {
             Ok(ChannelMessage::HandshakeHello(HandshakeHello {
+                from: peer_endpoint,
                 version,
                 genesis_block_id: peer_genesis_block_id,
                 head_block_id: peer_head_block_id,
@@ -251,7 +284,17 @@ async fn handshake_handler(ctx: Arc<AppContext>, mut sock: TcpStream) -> Result<
                     peer_head_block_id.as_ref().unwrap().number >= head_block_id.as_ref().unwrap().number;
 
                 info!("handshake finished, need sync = {}", need_syncing);
-                let ret = sync_channel_handler(ctx, need_syncing, reader, writer).await;
+                let disconnect_signal = ctx.peers.register(PeerInfo {
+                    endpoint: peer_addr,
+                    node_id: peer_endpoint.map(|ep| ep.node_id).unwrap_or_default(),
+                    direction,
+                    head_block_number: peer_head_block_id.as_ref().unwrap().number,
+                    latency: None,
+                    connected_at: Utc::now().timestamp_millis(),
+                });
+                let ret =
+                    sync_channel_handler(ctx.clone(), peer_addr, need_syncing, disconnect_signal, reader, writer).await;
+                ctx.peers.unregister(&peer_addr);
                 match ret {
                     Ok(_) => info!("channel finished"),
                     Err(e) => warn!("channel finished with error={:?}", e),
@@ -283,7 +326,9 @@ async fn handshake_handler(ctx: Arc<AppContext>, mut sock: TcpStream) -> Result<
 
 async fn sync_channel_handler(
     ctx: Arc<AppContext>,
+    peer_addr: SocketAddr,
     mut syncing: bool,
+    mut disconnect_signal: oneshot::Receiver<()>,
     mut reader: impl Stream<Item = Result<ChannelMessage, io::Error>> + Unpin,
     mut writer: impl Sink<ChannelMessage, Error = io::Error> + Unpin,
 ) -> Result<(), Box<dyn Error>> {
@@ -313,9 +358,13 @@ async fn sync_channel_handler(
 
     let mut syncing_block_ids: Vec<Vec<u8>> = vec![];
     let mut pinged = false;
+    let mut pinged_at = 0_i64;
     let (tx, mut rx) = mpsc::channel::<ChannelMessage>(1000);
 
     let mut done = ctx.termination_signal.subscribe();
+    let mut pbft_rx = ctx.advertising_pbft_tx.subscribe();
+    let mut pbft_commit_rx = ctx.advertising_pbft_commit_tx.subscribe();
+    let mut block_rx = ctx.advertising_block_tx.subscribe();
 
     const READING_TIMEOUT: u64 = 18;
     loop {
@@ -325,10 +374,34 @@ async fn sync_channel_handler(
                     writer.send(msg).await?;
                 }
             }
+            Ok(msg) = pbft_rx.recv() => {
+                if !syncing {
+                    writer.send(ChannelMessage::PbftMessage(msg)).await?;
+                }
+            }
+            Ok(result) = pbft_commit_rx.recv() => {
+                if !syncing {
+                    writer.send(ChannelMessage::PbftCommit(result)).await?;
+                }
+            }
+            Ok(block) = block_rx.recv() => {
+                if !syncing {
+                    let inv = Inventory {
+                        r#type: 1, // BLOCK
+                        ids: vec![block.hash().as_bytes().to_vec()],
+                    };
+                    writer.send(ChannelMessage::BlockInventory(inv)).await?;
+                }
+            }
             _ = done.recv() => {
                 debug!("termination, close channel connection");
                 return Ok(());
             }
+            _ = &mut disconnect_signal => {
+                warn!("peer removed, close channel connection");
+                writer.send(ChannelMessage::disconnect_with_reason(DisconnectReasonCode::Requested)).await?;
+                return Ok(());
+            }
             task = timeout(Duration::from_secs(READING_TIMEOUT), reader.next().fuse()) => {
                 let payload = match task {
                     Err(_) if pinged => {
@@ -339,6 +412,7 @@ async fn sync_channel_handler(
                         debug!("timeout, try pinging remote");
                         writer.send(ChannelMessage::Ping).await?;
                         pinged = true;
+                        pinged_at = Utc::now().timestamp_millis();
                         continue;
                     },
                     Ok(None) => {
@@ -369,6 +443,10 @@ async fn sync_channel_handler(
                     },
                     Ok(ChannelMessage::Pong) => {
                         debug!("pong");
+                        if pinged {
+                            ctx.peers.update_latency(&peer_addr, Utc::now().timestamp_millis() - pinged_at);
+                            pinged = false;
+                        }
                     },
                     Ok(ChannelMessage::TransactionInventory(inv)) => {
                         let Inventory { ids, r#type: _ } = inv;
@@ -394,6 +472,9 @@ async fn sync_channel_handler(
                         }
                     }
                     Ok(ChannelMessage::BlockInventory(inv)) => {
+                        if let Some(num) = inv.ids.iter().map(|blk_id| block_hash_to_number(blk_id)).max() {
+                            ctx.peers.update_head_block(&peer_addr, num);
+                        }
                         if syncing {
                             continue;
                         }
@@ -428,6 +509,7 @@ async fn sync_channel_handler(
                             chain_inv.remain_num);
 
                         last_block_number = last_block_id.number;
+                        ctx.peers.update_head_block(&peer_addr, last_block_id.number + chain_inv.remain_num);
 
                         let tail = if syncing_block_ids.len() >= batch_size {
                             syncing_block_ids.split_off(batch_size)
@@ -449,7 +531,12 @@ async fn sync_channel_handler(
                         writer.send(ChannelMessage::FetchBlockInventory(block_inv)).await?;
                     }
                     Ok(ChannelMessage::Block(block)) => {
+                        if !ctx.running.load(Ordering::SeqCst) {
+                            warn!("node is shutting down, stop syncing");
+                            return Ok(());
+                        }
                         let block = IndexedBlock::from_raw(block).unwrap();
+                        ctx.peers.update_head_block(&peer_addr, block.number());
                         if !ctx.recent_block_ids.read().unwrap().contains(&block.header.hash) {
                             if syncing {
                                 if block.number() % 100 == 0 {
@@ -471,11 +558,16 @@ async fn sync_channel_handler(
                             }
 
                             ctx.recent_block_ids.write().unwrap().insert(block.header.hash);
-                            if !ctx.chain_db.has_block(&block)  {
+                            if ctx.chain_db.has_block(&block) {
+                                warn!("block exists in db");
+                            } else if apply_block(&ctx, &block) {
                                 ctx.chain_db.insert_block(&block)?;
                                 ctx.chain_db.update_block_height(block.number());
-                            } else {
-                                warn!("block exists in db");
+                                if !syncing {
+                                    pbft::on_block(&ctx, &block);
+                                    // relay to other peers
+                                    let _ = ctx.advertising_block_tx.send(block.clone());
+                                }
                             }
                         }
                         if syncing {
@@ -533,7 +625,7 @@ async fn sync_channel_handler(
                                 let max_block_num = block_height.min(unfork_id.number + SYNC_FETCH_BATCH_NUM);
                                 let reply_ids:Vec<BlockId> =
                                     ctx.chain_db.block_hashes_from(
-                                        &unfork_id.hash, (max_block_num - unfork_id.number) as usize + 1)
+                                        &unfork_id.hash, (max_block_num - unfork_id.number) as usize + 1)?
                                     .into_iter()
                                     .map(|block_hash| BlockId::from(block_hash))
                                     .collect();
@@ -571,6 +663,16 @@ async fn sync_channel_handler(
                         }
                         info!("sent {} blocks", ids.len());
                     }
+                    Ok(ChannelMessage::PbftMessage(msg)) => {
+                        if !syncing {
+                            pbft::on_message(&ctx, msg);
+                        }
+                    }
+                    Ok(ChannelMessage::PbftCommit(result)) => {
+                        if !syncing {
+                            pbft::on_commit_result(&ctx, result);
+                        }
+                    }
                     Ok(msg) => {
                         error!("unhandled message {:?}", msg);
                         return Ok(());
@@ -581,6 +683,38 @@ async fn sync_channel_handler(
     }
 }
 
+/// Apply a received block to state-db. Returns false if the block is rejected and should not be saved.
+///
+/// When state-db lags behind chain-db, the block is only saved to chain-db, and replayed by recovery at next startup.
+fn apply_block(ctx: &AppContext, block: &IndexedBlock) -> bool {
+    let mut manager = ctx.manager.write().unwrap();
+    // the node is shutting down, state-db might be flushed already
+    if !ctx.running.load(Ordering::SeqCst) {
+        return false;
+    }
+    let latest_block_number = manager.latest_block_number();
+    if latest_block_number < ctx.chain_db.get_block_height() {
+        return true;
+    }
+    if block.number() != latest_block_number + 1 {
+        warn!(
+            "reject block #{} {:?}, state-db is at #{}",
+            block.number(),
+            block.hash(),
+            latest_block_number
+        );
+        return false;
+    }
+    match manager.push_incoming_block(block) {
+        Ok(applied) => applied,
+        Err(e) => {
+            manager.discard_pending_layers();
+            warn!("reject block #{} {:?}: {}", block.number(), block.hash(), e);
+            false
+        }
+    }
+}
+
 #[inline]
 pub fn block_hash_to_number(hash: &[u8]) -> i64 {
     BE::read_u64(&hash[..8]) as _
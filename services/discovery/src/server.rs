@@ -22,8 +22,6 @@ use proto::discovery::{FindPeers, Peers, Ping, Pong};
 use crate::peer::Peer;
 use crate::protocol::{DiscoveryMessage, DiscoveryMessageTransport};
 
-const PEERS_FILE: &'static str = "./peers.json";
-
 fn common_prefix_bits(a: &[u8], b: &[u8]) -> u32 {
     let mut acc = 0;
     for (&lhs, &rhs) in a.iter().zip(b.iter()) {
@@ -53,7 +51,7 @@ pub async fn discovery_server(ctx: Arc<AppContext>, signal: broadcast::Receiver<
     let socket = UdpSocket::bind(endpoint).await?;
     info!("bind to udp socket {}", socket.local_addr()?);
 
-    let peers_data = std::fs::read_to_string(PEERS_FILE).unwrap_or("[]".to_string());
+    let peers_data = std::fs::read_to_string(&config.peers_file).unwrap_or("[]".to_string());
     let mut peers_db: HashSet<Peer> = serde_json::from_str(&peers_data)?;
 
     let my_endpoint = channel_config
@@ -99,6 +97,10 @@ pub async fn discovery_server(ctx: Arc<AppContext>, signal: broadcast::Receiver<
     loop {
         select! {
             _ = signal.recv().fuse() => {
+                    if config.persist {
+                        std::fs::write(&config.peers_file, serde_json::to_string_pretty(&peers_db)?.as_bytes())?;
+                        info!("saved {} peers to {}", peers_db.len(), config.peers_file);
+                    }
                     warn!("discovery service closed");
                     break;
             }
@@ -192,7 +194,9 @@ pub async fn discovery_server(ctx: Arc<AppContext>, signal: broadcast::Receiver<
                         };
                         if !peers_db.contains(&peer) {
                             peers_db.insert(peer);
-                            std::fs::write(PEERS_FILE, serde_json::to_string_pretty(&peers_db)?.as_bytes())?;
+                            if config.persist {
+                                std::fs::write(&config.peers_file, serde_json::to_string_pretty(&peers_db)?.as_bytes())?;
+                            }
                         }
                     }
                     Err(e) => {
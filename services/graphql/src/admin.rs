@@ -0,0 +1,174 @@
+//! Node admin API, served on a separate endpoint.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use async_graphql::{Context, Enum, Object, Result, SimpleObject};
+use chrono::{DateTime, TimeZone, Utc};
+
+use context::peer::{PeerDirection, PeerInfo};
+use context::AppContext;
+
+use super::scalar::{Bytes, Long};
+
+/// Direction of a peer connection.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Direction {
+    /// Outgoing connection.
+    Active,
+    /// Incoming connection.
+    Passive,
+}
+
+impl From<PeerDirection> for Direction {
+    fn from(direction: PeerDirection) -> Self {
+        match direction {
+            PeerDirection::Active => Direction::Active,
+            PeerDirection::Passive => Direction::Passive,
+        }
+    }
+}
+
+/// A connected peer of the channel protocol.
+pub struct Peer(PeerInfo);
+
+#[Object]
+impl Peer {
+    /// Remote endpoint, in `ip:port`.
+    async fn endpoint(&self) -> String {
+        self.0.endpoint.to_string()
+    }
+
+    /// Node id of the peer.
+    async fn node_id(&self) -> Bytes {
+        Bytes(self.0.node_id.clone())
+    }
+
+    /// Highest block number known to be held by the peer.
+    async fn head_block(&self) -> Long {
+        self.0.head_block_number.into()
+    }
+
+    /// Latest measured ping-pong round trip, in ms.
+    async fn latency(&self) -> Option<i64> {
+        self.0.latency
+    }
+
+    /// Direction of the connection.
+    async fn direction(&self) -> Direction {
+        self.0.direction.into()
+    }
+
+    /// Time when handshake finished.
+    async fn connected_at(&self) -> DateTime<Utc> {
+        let ts = self.0.connected_at;
+        Utc.timestamp(ts / 1_000, ts as u32 % 1_000 * 1_000_000)
+    }
+}
+
+/// Running node info, for admins.
+#[derive(SimpleObject)]
+pub struct AdminNodeInfo {
+    /// Node id used in handshake.
+    node_id: Bytes,
+    /// P2P version, aka. network id.
+    p2p_version: i32,
+    /// Outbound ip address.
+    outbound_ip: String,
+    /// Listening endpoint of channel protocol.
+    channel_endpoint: String,
+    /// Block height of chain-db.
+    block_height: Long,
+    /// Block number of state-db.
+    state_block: Long,
+    /// Highest block number among connected peers.
+    highest_peer_block: Option<Long>,
+    /// Is node syncing.
+    syncing: bool,
+    /// Number of active(outgoing) connections.
+    num_active_connections: u32,
+    /// Number of passive(incoming) connections.
+    num_passive_connections: u32,
+    /// Endpoints the node is connecting to.
+    active_nodes: Vec<String>,
+    /// Number of banned ip addresses.
+    num_banned: i32,
+}
+
+pub struct AdminQueryRoot;
+
+#[Object]
+impl AdminQueryRoot {
+    /// Connected peers.
+    async fn peers(&self, ctx: &Context<'_>) -> Vec<Peer> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        app.peers.peers().into_iter().map(Peer).collect()
+    }
+
+    /// Banned ip addresses.
+    async fn banned(&self, ctx: &Context<'_>) -> Vec<String> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        app.peers.banned().into_iter().map(|ip| ip.to_string()).collect()
+    }
+
+    /// Current node info.
+    async fn node_info(&self, ctx: &Context<'_>) -> AdminNodeInfo {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        let manager = app.manager.read().unwrap();
+
+        AdminNodeInfo {
+            node_id: Bytes(app.node_id.clone()),
+            p2p_version: app.config.chain.p2p_version,
+            outbound_ip: app.outbound_ip.clone(),
+            channel_endpoint: app.config.protocol.channel.endpoint.clone(),
+            block_height: app.chain_db.get_block_height().into(),
+            state_block: manager.latest_block_number().into(),
+            highest_peer_block: app.peers.highest_block_number().map(Long),
+            syncing: app.syncing.load(Ordering::Relaxed),
+            num_active_connections: app.num_active_connections.load(Ordering::Relaxed),
+            num_passive_connections: app.num_passive_connections.load(Ordering::Relaxed),
+            active_nodes: app.peers.active_nodes(),
+            num_banned: app.peers.banned().len() as _,
+        }
+    }
+}
+
+pub struct AdminMutationRoot;
+
+#[Object]
+impl AdminMutationRoot {
+    /// Add an endpoint to active nodes. The connection is made by the channel service when there are
+    /// free active connection slots.
+    async fn add_peer(&self, ctx: &Context<'_>, endpoint: String) -> Result<bool> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        let addr = endpoint.parse::<SocketAddr>()?;
+        if app.peers.is_banned(&addr.ip()) {
+            return Err("peer is banned".into());
+        }
+        Ok(app.peers.add_active_node(&endpoint))
+    }
+
+    /// Disconnect a peer, and remove it from active nodes.
+    async fn remove_peer(&self, ctx: &Context<'_>, endpoint: String) -> Result<bool> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        let addr = endpoint.parse::<SocketAddr>()?;
+        let removed = app.peers.remove_active_node(&endpoint);
+        let disconnected = app.peers.disconnect(&addr);
+        Ok(removed || disconnected)
+    }
+
+    /// Ban an ip address, all connected peers from it are disconnected.
+    async fn ban(&self, ctx: &Context<'_>, ip: String) -> Result<bool> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        let ip = ip.parse::<IpAddr>()?;
+        Ok(app.peers.ban(ip))
+    }
+
+    /// Remove an ip address from ban list.
+    async fn unban(&self, ctx: &Context<'_>, ip: String) -> Result<bool> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        let ip = ip.parse::<IpAddr>()?;
+        Ok(app.peers.unban(&ip))
+    }
+}
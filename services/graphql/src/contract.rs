@@ -141,6 +141,43 @@ pub struct UnfreezeBalanceContract {
     resource: ResourceCode,
 }
 
+#[derive(SimpleObject)]
+pub struct FreezeBalanceV2Contract {
+    owner_address: Address,
+    resource: ResourceCode,
+    frozen_balance: Long,
+}
+
+#[derive(SimpleObject)]
+pub struct UnfreezeBalanceV2Contract {
+    owner_address: Address,
+    resource: ResourceCode,
+    unfreeze_balance: Long,
+}
+
+#[derive(SimpleObject)]
+pub struct WithdrawExpireUnfreezeContract {
+    owner_address: Address,
+}
+
+#[derive(SimpleObject)]
+pub struct DelegateResourceContract {
+    owner_address: Address,
+    receiver_address: Address,
+    resource: ResourceCode,
+    balance: Long,
+    lock: bool,
+    lock_period: Long,
+}
+
+#[derive(SimpleObject)]
+pub struct UnDelegateResourceContract {
+    owner_address: Address,
+    receiver_address: Address,
+    resource: ResourceCode,
+    balance: Long,
+}
+
 #[derive(SimpleObject)]
 pub struct WithdrawBalanceContract {
     owner_address: Address,
@@ -334,6 +371,11 @@ pub enum Contract {
     VoteWitnessContract(VoteWitnessContract),
     FreezeBalanceContract(FreezeBalanceContract),
     UnfreezeBalanceContract(UnfreezeBalanceContract),
+    FreezeBalanceV2Contract(FreezeBalanceV2Contract),
+    UnfreezeBalanceV2Contract(UnfreezeBalanceV2Contract),
+    WithdrawExpireUnfreezeContract(WithdrawExpireUnfreezeContract),
+    DelegateResourceContract(DelegateResourceContract),
+    UnDelegateResourceContract(UnDelegateResourceContract),
     ProposalCreateContract(ProposalCreateContract),
     ProposalApproveContract(ProposalApproveContract),
     ProposalDeleteContract(ProposalDeleteContract),
@@ -373,6 +415,11 @@ impl Contract {
             VoteWitnessContract(ref inner) => inner.owner_address,
             FreezeBalanceContract(ref inner) => inner.owner_address,
             UnfreezeBalanceContract(ref inner) => inner.owner_address,
+            FreezeBalanceV2Contract(ref inner) => inner.owner_address,
+            UnfreezeBalanceV2Contract(ref inner) => inner.owner_address,
+            WithdrawExpireUnfreezeContract(ref inner) => inner.owner_address,
+            DelegateResourceContract(ref inner) => inner.owner_address,
+            UnDelegateResourceContract(ref inner) => inner.owner_address,
             ProposalCreateContract(ref inner) => inner.owner_address,
             ProposalApproveContract(ref inner) => inner.owner_address,
             ProposalDeleteContract(ref inner) => inner.owner_address,
@@ -398,6 +445,8 @@ impl Contract {
             TransferAssetContract(ref inner) => Some(inner.to_address),
             TriggerSmartContract(ref inner) => Some(inner.contract_address),
             AccountCreateContract(ref inner) => Some(inner.account_address),
+            DelegateResourceContract(ref inner) => Some(inner.receiver_address),
+            UnDelegateResourceContract(ref inner) => Some(inner.receiver_address),
             _ => None,
         }
     }
@@ -508,6 +557,69 @@ impl From<&ContractPb> for Contract {
                 };
                 Contract::UnfreezeBalanceContract(inner)
             }
+            Some(ContractType::FreezeBalanceV2Contract) => {
+                let cntr = contract_pb::FreezeBalanceV2Contract::decode(raw).unwrap();
+                let inner = FreezeBalanceV2Contract {
+                    owner_address: Address(cntr.owner_address.try_into().unwrap()),
+                    resource: if cntr.resource == 0 {
+                        ResourceCode::Bandwidth
+                    } else {
+                        ResourceCode::Energy
+                    },
+                    frozen_balance: cntr.frozen_balance.into(),
+                };
+                Contract::FreezeBalanceV2Contract(inner)
+            }
+            Some(ContractType::UnfreezeBalanceV2Contract) => {
+                let cntr = contract_pb::UnfreezeBalanceV2Contract::decode(raw).unwrap();
+                let inner = UnfreezeBalanceV2Contract {
+                    owner_address: Address(cntr.owner_address.try_into().unwrap()),
+                    resource: if cntr.resource == 0 {
+                        ResourceCode::Bandwidth
+                    } else {
+                        ResourceCode::Energy
+                    },
+                    unfreeze_balance: cntr.unfreeze_balance.into(),
+                };
+                Contract::UnfreezeBalanceV2Contract(inner)
+            }
+            Some(ContractType::WithdrawExpireUnfreezeContract) => {
+                let cntr = contract_pb::WithdrawExpireUnfreezeContract::decode(raw).unwrap();
+                let inner = WithdrawExpireUnfreezeContract {
+                    owner_address: Address(cntr.owner_address.try_into().unwrap()),
+                };
+                Contract::WithdrawExpireUnfreezeContract(inner)
+            }
+            Some(ContractType::DelegateResourceContract) => {
+                let cntr = contract_pb::DelegateResourceContract::decode(raw).unwrap();
+                let inner = DelegateResourceContract {
+                    owner_address: Address(cntr.owner_address.try_into().unwrap()),
+                    receiver_address: Address(cntr.receiver_address.try_into().unwrap()),
+                    resource: if cntr.resource == 0 {
+                        ResourceCode::Bandwidth
+                    } else {
+                        ResourceCode::Energy
+                    },
+                    balance: cntr.balance.into(),
+                    lock: cntr.lock,
+                    lock_period: cntr.lock_period.into(),
+                };
+                Contract::DelegateResourceContract(inner)
+            }
+            Some(ContractType::UnDelegateResourceContract) => {
+                let cntr = contract_pb::UnDelegateResourceContract::decode(raw).unwrap();
+                let inner = UnDelegateResourceContract {
+                    owner_address: Address(cntr.owner_address.try_into().unwrap()),
+                    receiver_address: Address(cntr.receiver_address.try_into().unwrap()),
+                    resource: if cntr.resource == 0 {
+                        ResourceCode::Bandwidth
+                    } else {
+                        ResourceCode::Energy
+                    },
+                    balance: cntr.balance.into(),
+                };
+                Contract::UnDelegateResourceContract(inner)
+            }
             Some(ContractType::WitnessCreateContract) => {
                 let cntr = contract_pb::WitnessCreateContract::decode(raw).unwrap();
                 let inner = WitnessCreateContract {
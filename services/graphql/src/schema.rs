@@ -2,6 +2,7 @@ use std::convert::TryFrom;
 use std::mem;
 use std::str;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use async_graphql::{Context, Enum, Error, InputObject, Object, Result, SimpleObject};
 use byteorder::{ByteOrder, BE};
@@ -13,7 +14,7 @@ use chain::{IndexedBlockHeader, IndexedTransaction};
 use context::AppContext;
 use proto::state;
 
-use super::contract::{AccountType, Contract};
+use super::contract::{AccountType, Contract, Permission};
 use super::model::NodeInfo;
 use super::scalar::{Address, Bytes, Bytes32, Long};
 
@@ -72,6 +73,21 @@ impl Account {
             .map_err(From::from)
     }
 
+    /// ABI of the contract as JSON string, if the account is a contract with ABI.
+    async fn abi(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        self.require_inner(ctx)?;
+        let inner = self.inner.read().unwrap();
+        if inner.as_ref().unwrap().r#type != state::AccountType::Contract as i32 {
+            return Ok(None);
+        }
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let cntr = manager.state().get(&keys::Contract(self.address.0))?;
+        Ok(cntr
+            .and_then(|cntr| cntr.abi)
+            .filter(|abi| !abi.entries.is_empty())
+            .and_then(|abi| serde_json::to_string(&abi.entries).ok()))
+    }
+
     /// Storage provides access to the storage of a contract account, indexed
     /// by its 32 byte slot identifier.
     async fn storage(&self, ctx: &Context<'_>, slot: Bytes32) -> Result<Bytes32> {
@@ -109,6 +125,31 @@ impl Account {
         Ok(inner.as_ref().unwrap().allowance.into())
     }
 
+    /// Unwithdrawn voting reward of the account, not including allowance.
+    async fn reward(&self, ctx: &Context<'_>) -> Result<Long> {
+        use manager::governance::reward::RewardUtil;
+
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let amount = RewardUtil::new(manager).query_reward(self.address.0)?;
+        Ok(amount.into())
+    }
+
+    /// Unwithdrawn voting reward of the account, in each epoch and from each voted witness.
+    async fn rewards(&self, ctx: &Context<'_>) -> Result<Vec<VoterReward>> {
+        use manager::governance::reward::RewardUtil;
+
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let details = RewardUtil::new(manager).query_reward_details(self.address.0)?;
+        Ok(details
+            .into_iter()
+            .map(|(epoch, witness, amount)| VoterReward {
+                epoch: epoch.into(),
+                witness: witness.into(),
+                amount: amount.into(),
+            })
+            .collect())
+    }
+
     /// Name of this account.
     async fn name(&self, ctx: &Context<'_>) -> Result<String> {
         self.require_inner(ctx)?;
@@ -142,12 +183,77 @@ impl Account {
     async fn frozen_balance(&self, ctx: &Context<'_>) -> Result<Long> {
         self.require_inner(ctx)?;
         let inner = self.inner.read().unwrap();
-        let amount = inner
-            .as_ref()
-            .map(|acct| acct.frozen_amount_for_bandwidth + acct.frozen_amount_for_energy + acct.delegated_out_amount)
-            .unwrap();
+        let amount = inner.as_ref().map(|acct| acct.frozen_amount()).unwrap();
         Ok(amount.into())
     }
+
+    /// Permissions of the account, the owner permission(id = 0) and active permissions(id >= 2).
+    ///
+    /// Default permissions are returned if they are not set by AccountPermissionUpdateContract.
+    async fn permissions(&self, ctx: &Context<'_>) -> Result<Vec<Permission>> {
+        use proto::common::permission::{Key as PermissionKeyPb, PermissionType};
+        use proto::common::Permission as PermissionPb;
+
+        self.require_inner(ctx)?;
+        let inner = self.inner.read().unwrap();
+        let acct = inner.as_ref().unwrap();
+
+        let default_keys = vec![PermissionKeyPb {
+            address: self.address.0.as_bytes().to_vec(),
+            weight: 1,
+        }];
+        let to_keys = |keys: &[state::PermissionKey]| {
+            keys.iter()
+                .map(|key| PermissionKeyPb {
+                    address: key.address.clone(),
+                    weight: key.weight,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let owner = match acct.owner_permission {
+            Some(ref owner) => PermissionPb {
+                r#type: PermissionType::Owner as i32,
+                id: 0,
+                name: "owner".into(),
+                threshold: owner.threshold,
+                keys: to_keys(&owner.keys),
+                ..Default::default()
+            },
+            None => PermissionPb {
+                r#type: PermissionType::Owner as i32,
+                id: 0,
+                name: "owner".into(),
+                threshold: 1,
+                keys: default_keys.clone(),
+                ..Default::default()
+            },
+        };
+        let mut perms = vec![owner];
+        if acct.active_permissions.is_empty() {
+            perms.push(PermissionPb {
+                r#type: PermissionType::Active as i32,
+                id: 2,
+                name: "active".into(),
+                threshold: 1,
+                keys: default_keys,
+                ..Default::default()
+            });
+        } else {
+            for (i, active) in acct.active_permissions.iter().enumerate() {
+                perms.push(PermissionPb {
+                    r#type: PermissionType::Active as i32,
+                    id: i as i32 + 2,
+                    name: active.permission_name.clone(),
+                    threshold: active.threshold,
+                    operations: active.operations.clone(),
+                    keys: to_keys(&active.keys),
+                    ..Default::default()
+                });
+            }
+        }
+        Ok(perms.into_iter().map(Permission::from).collect())
+    }
 }
 
 /// Asset is a TRC10 token.
@@ -363,6 +469,31 @@ fn matches_topics(topics: &Option<Vec<Vec<Bytes32>>>, target: &[Vec<u8>]) -> boo
     }
 }
 
+/// Voting reward of an account in one epoch, from one voted witness.
+#[derive(SimpleObject)]
+pub struct VoterReward {
+    epoch: Long,
+    witness: Address,
+    amount: Long,
+}
+
+/// A vote for a witness.
+#[derive(InputObject)]
+pub struct VoteInput {
+    /// Witness to vote for.
+    witness: Address,
+    /// Number of votes, 1 TRX of tron power for 1 vote.
+    count: Long,
+}
+
+/// Estimated voting reward of a vote in one epoch.
+#[derive(SimpleObject)]
+pub struct SimulatedVoteReward {
+    witness: Address,
+    count: Long,
+    reward: Long,
+}
+
 /// CallData represents the data associated with a local contract call.
 /// All fields are optional.
 #[derive(InputObject)]
@@ -439,6 +570,8 @@ pub struct SyncState {
     highest_block: Long,
     /// SolidBlock is the safe block for comfirmations.
     solid_block: Long,
+    /// PbftBlock is the latest block finalized by PBFT, 0 if PBFT is not enabled.
+    pbft_block: Long,
     /// StateBlock is the block number of StateDB.
     state_block: Long,
     /// PulledStates is the number of state entries fetched so far, or null
@@ -543,6 +676,13 @@ impl Transaction {
         Contract::from(cntr).value()
     }
 
+    /// Result is the execution receipt of the transaction, with resource usage and fees.
+    async fn result(&self, ctx: &Context<'_>) -> Result<Option<Receipt>> {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let receipt = manager.state().get(&keys::TransactionReceipt(self.inner.hash))?;
+        Ok(receipt.map(|inner| Receipt { inner }))
+    }
+
     // NOTE: for debug
     async fn receipt(&self, ctx: &Context<'_>) -> Result<String> {
         let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
@@ -562,6 +702,92 @@ impl Transaction {
     // inputData
 }
 
+/// Receipt is the execution result of a transaction.
+pub struct Receipt {
+    inner: state::TransactionReceipt,
+}
+
+impl Receipt {
+    fn resource(&self) -> state::ResourceReceipt {
+        self.inner.resource_receipt.clone().unwrap_or_default()
+    }
+}
+
+#[Object]
+impl Receipt {
+    /// Success is whether the transaction is executed successfully.
+    async fn success(&self) -> bool {
+        self.inner.success
+    }
+
+    /// Fee is the total fee burnt by the transaction, in sun.
+    async fn fee(&self) -> Long {
+        self.inner.fee.into()
+    }
+
+    /// EnergyUsed is the total energy consumed, including the part paid by contract origin.
+    async fn energy_used(&self) -> Long {
+        self.resource().energy.into()
+    }
+
+    /// EnergyUsage is the energy consumed from the caller's frozen resource.
+    async fn energy_usage(&self) -> Long {
+        self.resource().energy_usage.into()
+    }
+
+    /// OriginEnergyUsage is the energy consumed from the contract origin's frozen resource.
+    async fn origin_energy_usage(&self) -> Long {
+        self.resource().origin_energy_usage.into()
+    }
+
+    /// EnergyFee is the TRX burnt for energy, in sun.
+    async fn energy_fee(&self) -> Long {
+        self.resource().energy_fee.into()
+    }
+
+    /// BandwidthUsage is the bandwidth consumed.
+    async fn bandwidth_usage(&self) -> Long {
+        self.resource().bandwidth_usage.into()
+    }
+
+    /// BandwidthFee is the TRX burnt for bandwidth, in sun.
+    async fn bandwidth_fee(&self) -> Long {
+        self.resource().bandwidth_fee.into()
+    }
+
+    /// ContractFee is the fee of asset issue, exchange create, witness create or permission update.
+    async fn contract_fee(&self) -> Long {
+        self.resource().contract_fee.into()
+    }
+
+    /// MultisigFee is the fee of multisig transactions.
+    async fn multisig_fee(&self) -> Long {
+        self.resource().multisig_fee.into()
+    }
+
+    /// VmStatus is the result of TVM execution.
+    async fn vm_status(&self) -> VmStatus {
+        unsafe { mem::transmute(self.inner.vm_status) }
+    }
+
+    /// VmMessage is the error message of TVM execution.
+    async fn vm_message(&self) -> String {
+        String::from_utf8_lossy(&self.inner.vm_message).into_owned()
+    }
+
+    /// VmResult is the return data of TVM execution.
+    async fn vm_result(&self) -> Bytes {
+        Bytes(self.inner.vm_result.clone())
+    }
+
+    /// CreatedContract is the address of contract created by the transaction.
+    async fn created_contract(&self) -> Option<Address> {
+        ::keys::Address::try_from(&self.inner.vm_created_contract_address)
+            .ok()
+            .map(Address::from)
+    }
+}
+
 #[derive(Debug)]
 enum BlockIdentifier {
     Number(Long),
@@ -790,13 +1016,30 @@ pub struct Witness {
     inner: state::Witness,
 }
 
+impl Witness {
+    fn address(&self) -> ::keys::Address {
+        *::keys::Address::from_bytes(&self.inner.address)
+    }
+}
+
 #[Object]
 impl Witness {
+    /// Address of the witness.
+    async fn address(&self) -> Address {
+        self.address().into()
+    }
+
     /// URL of the witness.
     async fn url(&self) -> &str {
         &self.inner.url
     }
 
+    /// Whether the witness is an active witness, producing blocks in current epoch.
+    async fn is_active(&self, ctx: &Context<'_>) -> bool {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        manager.get_active_witnesses().contains(&self.address())
+    }
+
     /// Total blocks produced by the witness.
     async fn total_produced(&self) -> Long {
         self.inner.total_produced.into()
@@ -807,6 +1050,33 @@ impl Witness {
         self.inner.total_missed.into()
     }
 
+    /// Latest block produced by the witness.
+    async fn latest_block_number(&self) -> Long {
+        self.inner.latest_block_number.into()
+    }
+
+    /// Produced and missed blocks of each epoch, from `fromEpoch`(default: current epoch) to current epoch.
+    async fn statistics(&self, ctx: &Context<'_>, from_epoch: Option<Long>) -> Result<Vec<WitnessEpochStatistic>> {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let current_epoch = manager.current_epoch();
+        let from_epoch = from_epoch.map(|epoch| epoch.0).unwrap_or(current_epoch);
+        if current_epoch - from_epoch > MAX_NUMBER_OF_BATCH_ITEMS_PER_REQUEST {
+            return Err(Error::from("exceeds the maximum number of epochs per request"));
+        }
+
+        let mut stats = vec![];
+        for epoch in from_epoch.max(0)..=current_epoch {
+            if let Some(stat) = manager.state().get(&keys::WitnessStatistic(epoch, self.address()))? {
+                stats.push(WitnessEpochStatistic {
+                    epoch: epoch.into(),
+                    produced: stat.produced.into(),
+                    missed: stat.missed.into(),
+                });
+            }
+        }
+        Ok(stats)
+    }
+
     /// Brokerage rate of the witness.
     async fn brokerage(&self) -> i32 {
         self.inner.brokerage
@@ -827,6 +1097,14 @@ impl Witness {
     }
 }
 
+/// Block producing statistic of a witness in one epoch.
+#[derive(SimpleObject)]
+pub struct WitnessEpochStatistic {
+    epoch: Long,
+    produced: Long,
+    missed: Long,
+}
+
 #[derive(SimpleObject)]
 pub struct ChainParameter {
     id: i32,
@@ -834,6 +1112,132 @@ pub struct ChainParameter {
     value: i64,
 }
 
+/// State of a proposal.
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalState {
+    Pending,
+    Disapproved,
+    Approved,
+    Cancelled,
+}
+
+impl ProposalState {
+    fn from_i32(val: i32) -> Self {
+        match val {
+            1 => ProposalState::Disapproved,
+            2 => ProposalState::Approved,
+            3 => ProposalState::Cancelled,
+            _ => ProposalState::Pending,
+        }
+    }
+}
+
+pub struct Proposal {
+    inner: state::Proposal,
+}
+
+#[Object]
+impl Proposal {
+    /// ID of the proposal.
+    async fn id(&self) -> i64 {
+        self.inner.proposal_id
+    }
+
+    /// Proposer of the proposal.
+    async fn proposer(&self) -> Result<Address> {
+        let address = ::keys::Address::try_from(&self.inner.proposer_address)?;
+        Ok(address.into())
+    }
+
+    /// Proposed chain parameters.
+    async fn parameters(&self) -> Vec<ChainParameter> {
+        let mut params: Vec<_> = self
+            .inner
+            .parameters
+            .iter()
+            .map(|(&id, &value)| ChainParameter {
+                id: id as i32,
+                key: keys::ChainParameter::from_i32(id as i32)
+                    .map(|param| format!("{:?}", param))
+                    .unwrap_or_default(),
+                value,
+            })
+            .collect();
+        params.sort_by_key(|param| param.id);
+        params
+    }
+
+    /// Witnesses who have approved the proposal.
+    async fn approvals(&self) -> Vec<Address> {
+        self.inner
+            .approver_addresses
+            .iter()
+            .filter_map(|raw| ::keys::Address::try_from(raw).ok())
+            .map(Address::from)
+            .collect()
+    }
+
+    /// Creation time of the proposal.
+    async fn creation_time(&self) -> DateTime<Utc> {
+        let ts = self.inner.creation_time;
+        Utc.timestamp(ts / 1_000, ts as u32 % 1_000 * 1_000_000)
+    }
+
+    /// Expiration time of the proposal.
+    async fn expiration_time(&self) -> DateTime<Utc> {
+        let ts = self.inner.expiration_time;
+        Utc.timestamp(ts / 1_000, ts as u32 % 1_000 * 1_000_000)
+    }
+
+    /// State of the proposal.
+    async fn state(&self) -> ProposalState {
+        ProposalState::from_i32(self.inner.state)
+    }
+
+    /// Number of approvals from current active witnesses.
+    async fn approval_count(&self, ctx: &Context<'_>) -> i32 {
+        use manager::governance::proposal::ProposalUtil;
+
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        ProposalUtil::new(manager).approval_count(&self.inner) as i32
+    }
+
+    /// Number of approvals required to pass the proposal, 70% of active witnesses.
+    async fn required_approval_count(&self, ctx: &Context<'_>) -> i32 {
+        use manager::governance::proposal::ProposalUtil;
+
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        ProposalUtil::new(manager).required_approval_count() as i32
+    }
+
+    /// Maintenance time when a pending proposal is counted, and takes effect if approved. Estimated with current
+    /// maintenance interval. Null if the proposal is already processed or cancelled.
+    async fn maintenance_time(&self, ctx: &Context<'_>) -> Option<DateTime<Utc>> {
+        if ProposalState::from_i32(self.inner.state) != ProposalState::Pending {
+            return None;
+        }
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let mut ts = manager.state().must_get(&keys::DynamicProperty::NextMaintenanceTime);
+        if self.inner.expiration_time > ts {
+            let interval = manager.state().must_get(&keys::ChainParameter::MaintenanceInterval);
+            ts += (self.inner.expiration_time - ts + interval - 1) / interval * interval;
+        }
+        Some(Utc.timestamp(ts / 1_000, ts as u32 % 1_000 * 1_000_000))
+    }
+}
+
+/// A chain parameter to be changed at next maintenance.
+#[derive(SimpleObject)]
+pub struct PendingChainParameter {
+    id: i32,
+    key: String,
+    /// New value of the parameter.
+    value: i64,
+    current_value: i64,
+    /// The approved proposal setting the parameter.
+    proposal_id: i64,
+}
+
 pub struct Chain;
 
 #[Object]
@@ -873,6 +1277,37 @@ impl Chain {
         let ts = manager.state().must_get(&keys::DynamicProperty::NextMaintenanceTime);
         Utc.timestamp(ts / 1_000, ts as u32 % 1_000 * 1_000_000)
     }
+
+    /// Chain parameters to be changed at next maintenance, by pending proposals expiring before it with enough
+    /// approvals for now.
+    async fn pending_parameters(&self, ctx: &Context<'_>) -> Vec<PendingChainParameter> {
+        use manager::governance::proposal::ProposalUtil;
+
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        ProposalUtil::new(manager)
+            .pending_parameters()
+            .into_iter()
+            .map(|(param, value, proposal_id)| PendingChainParameter {
+                id: param as i32,
+                key: format!("{:?}", param),
+                value,
+                current_value: manager.state().get(&param).unwrap().unwrap_or_default(),
+                proposal_id,
+            })
+            .collect()
+    }
+
+    /// Current epoch, increased at each maintenance.
+    async fn current_epoch(&self, ctx: &Context<'_>) -> Long {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        manager.current_epoch().into()
+    }
+
+    /// Percentage of filled slots in the latest 128 slots.
+    async fn block_filled_slots_rate(&self, ctx: &Context<'_>) -> i32 {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        manager.block_filled_slots_rate() as i32
+    }
 }
 
 pub struct QueryRoot;
@@ -961,6 +1396,16 @@ impl QueryRoot {
         Ok((from.0..=to_num).map(|num| Block::from_number(Long(num))).collect())
     }
 
+    /// PbftBlock returns the latest block finalized by PBFT.
+    async fn pbft_block(&self, ctx: &Context<'_>) -> Result<Block> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        let number = app.manager.read().unwrap().pbft_block_number();
+        if number <= 0 {
+            return Err("no block is finalized by PBFT".into());
+        }
+        Ok(Block::from_number(Long(number)))
+    }
+
     // # Pending returns the current pending state.
     // pending: Pending!
 
@@ -1021,6 +1466,7 @@ impl QueryRoot {
             // FIXME: wrong impl
             highest_block: Long(db.get_block_height()),
             solid_block: Long(manager.solid_block_number()),
+            pbft_block: Long(manager.pbft_block_number()),
             state_block: Long(manager.latest_block_number()),
             pulled_states: None,
             known_states: None,
@@ -1095,6 +1541,55 @@ impl QueryRoot {
         Ok(Witness { inner: wit })
     }
 
+    /// Witnesses lists witnesses sorted by votes, or only active or inactive ones.
+    async fn witnesses(&self, ctx: &Context<'_>, active: Option<bool>) -> Result<Vec<Witness>> {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let active_witnesses = manager.get_active_witnesses();
+
+        let mut witnesses = vec![];
+        {
+            let witnesses = &mut witnesses;
+            manager.state().for_each(move |key: &keys::Witness, wit| {
+                if active.map_or(true, |active| active_witnesses.contains(&key.0) == active) {
+                    witnesses.push(Witness { inner: wit.clone() });
+                }
+            });
+        }
+        witnesses.sort_by(|a, b| {
+            b.inner
+                .vote_count
+                .cmp(&a.inner.vote_count)
+                .then_with(|| a.inner.address.cmp(&b.inner.address))
+        });
+        Ok(witnesses)
+    }
+
+    /// SimulateVote estimates voting reward per epoch of new votes, with current brokerage and vote counts of
+    /// witnesses. Transaction fee reward is not included.
+    async fn simulate_vote(&self, ctx: &Context<'_>, votes: Vec<VoteInput>) -> Result<Vec<SimulatedVoteReward>> {
+        use manager::governance::reward::RewardUtil;
+
+        if votes.is_empty() || votes.len() > constants::MAX_NUM_OF_VOTES {
+            return Err("invalid number of votes".into());
+        }
+        if votes.iter().any(|vote| vote.count.0 <= 0) {
+            return Err("vote count must be greater than 0".into());
+        }
+
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let votes: Vec<_> = votes.into_iter().map(|vote| (vote.witness.0, vote.count.0)).collect();
+        let rewards = RewardUtil::new(manager).estimate_reward(&votes)?;
+        Ok(votes
+            .into_iter()
+            .zip(rewards.into_iter())
+            .map(|((witness, count), reward)| SimulatedVoteReward {
+                witness: witness.into(),
+                count: count.into(),
+                reward: reward.into(),
+            })
+            .collect())
+    }
+
     /// Asset fetches an Tron asset(TRC10 token).
     async fn asset(&self, ctx: &Context<'_>, issuer: Option<Address>, id: Option<i64>) -> Result<Asset> {
         let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
@@ -1116,12 +1611,48 @@ impl QueryRoot {
         Ok(Asset(asset))
     }
 
+    /// Proposal fetches a proposal by its ID.
+    async fn proposal(&self, ctx: &Context<'_>, id: i64) -> Result<Proposal> {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let proposal = manager
+            .state()
+            .get(&keys::Proposal(id))?
+            .ok_or_else(|| "proposal not found")?;
+        Ok(Proposal { inner: proposal })
+    }
+
+    /// Proposals lists proposals, the latest first, optionally filtered by state.
+    async fn proposals(&self, ctx: &Context<'_>, state: Option<ProposalState>) -> Result<Vec<Proposal>> {
+        let ref manager = ctx.data_unchecked::<Arc<AppContext>>().manager.read().unwrap();
+        let mut proposals = vec![];
+        {
+            let proposals = &mut proposals;
+            manager.state().for_each(move |_key: &keys::Proposal, proposal| {
+                if state.map_or(true, |state| ProposalState::from_i32(proposal.state) == state) {
+                    proposals.push(Proposal {
+                        inner: proposal.clone(),
+                    });
+                }
+            });
+        }
+        proposals.sort_by(|a, b| b.inner.proposal_id.cmp(&a.inner.proposal_id));
+        Ok(proposals)
+    }
+
     /// Chain query.
     async fn chain(&self) -> Chain {
         Chain
     }
 }
 
+fn require_instant_seal(app: &AppContext) -> Result<()> {
+    if app.config.producer.enable && app.config.producer.instant_seal {
+        Ok(())
+    } else {
+        Err("only available in the instant-seal dev mode".into())
+    }
+}
+
 pub struct MutationRoot;
 
 #[Object]
@@ -1155,6 +1686,40 @@ impl MutationRoot {
         Ok(txn_id.into())
     }
 
+    /// MineBlock seals a block immediately, with all pending transactions. Only in the instant-seal dev mode.
+    async fn mine_block(&self, ctx: &Context<'_>) -> Result<Block> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        require_instant_seal(app)?;
+
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        app.seal_request_tx
+            .send(reply_tx)
+            .await
+            .map_err(|_| "block producer is not running")?;
+        let block = tokio::time::timeout(Duration::from_secs(10), reply_rx)
+            .await
+            .map_err(|_| "timeout while sealing block")?
+            .map_err(|_| "block producer is not running")??;
+        Ok(Block {
+            identifier: BlockIdentifier::Hash(block.header.hash.into()),
+            header: RwLock::new(Some(block.header)),
+            transactions: RwLock::new(Some(block.transactions)),
+        })
+    }
+
+    /// IncreaseTime moves the clock of block producing forward, affecting slots and maintenance.
+    /// Only in the instant-seal dev mode. Returns the total time warp, in seconds.
+    async fn increase_time(&self, ctx: &Context<'_>, seconds: i64) -> Result<Long> {
+        let app = ctx.data_unchecked::<Arc<AppContext>>();
+        require_instant_seal(app)?;
+        if seconds < 0 {
+            return Err("time can only be increased".into());
+        }
+
+        let mut manager = app.manager.write().unwrap();
+        Ok((manager.increase_time(seconds * 1_000) / 1_000).into())
+    }
+
     /// DryRunRawTransaction runs an protobuf-encoded transaction and returns the receipt as json.
     async fn dry_run_raw_transaction(&self, ctx: &Context<'_>, data: Bytes) -> Result<CallResult> {
         use chain::IndexedTransaction;
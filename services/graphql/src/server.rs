@@ -8,10 +8,11 @@ use async_graphql_warp::BadRequest;
 use http::StatusCode;
 use log::{info, trace, warn};
 use tokio::sync::broadcast;
-use warp::{Filter, Rejection};
+use warp::{Filter, Rejection, Reply};
 
 use context::AppContext;
 
+use super::admin::{AdminMutationRoot, AdminQueryRoot};
 use super::schema::{MutationRoot, QueryRoot};
 
 pub async fn graphql_server(ctx: Arc<AppContext>, mut shutdown_signal: broadcast::Receiver<()>) {
@@ -64,3 +65,61 @@ pub async fn graphql_server(ctx: Arc<AppContext>, mut shutdown_signal: broadcast
 
     fut.await;
 }
+
+pub async fn admin_graphql_server(ctx: Arc<AppContext>, mut shutdown_signal: broadcast::Receiver<()>) {
+    let config = &ctx.config.graphql.admin;
+
+    if !config.enable {
+        return;
+    }
+
+    let addr: SocketAddr = config
+        .endpoint
+        .parse()
+        .expect("malformed endpoint address for admin graphql server");
+    let token = config.token.clone().map(|token| format!("Bearer {}", token));
+    if token.is_none() {
+        warn!("admin graphql server enabled without token");
+    }
+
+    let schema = Schema::build(AdminQueryRoot, AdminMutationRoot, EmptySubscription)
+        .data(ctx.clone())
+        .finish();
+
+    let graphql_post = warp::header::optional::<String>("authorization")
+        .and(async_graphql_warp::graphql(schema))
+        .and_then(
+            move |auth: Option<String>, (schema, request): (Schema<_, _, _>, async_graphql::Request)| {
+                let authorized = token.is_none() || token == auth;
+                async move {
+                    if !authorized {
+                        return Ok::<_, Infallible>(
+                            warp::reply::with_status("UNAUTHORIZED".to_string(), StatusCode::UNAUTHORIZED)
+                                .into_response(),
+                        );
+                    }
+                    trace!("admin req: {:?}", request.query);
+                    Ok(async_graphql_warp::Response::from(schema.execute(request).await).into_response())
+                }
+            },
+        );
+
+    let routes = graphql_post.recover(|err: Rejection| async move {
+        if let Some(BadRequest(err)) = err.find() {
+            return Ok::<_, Infallible>(warp::reply::with_status(err.to_string(), StatusCode::BAD_REQUEST));
+        }
+
+        Ok(warp::reply::with_status(
+            "INTERNAL_SERVER_ERROR".to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ))
+    });
+
+    let (listening_addr, fut) = warp::serve(routes).bind_with_graceful_shutdown(addr, async move {
+        shutdown_signal.recv().await.ok();
+    });
+
+    info!("admin api listening on http://{}", listening_addr);
+
+    fut.await;
+}
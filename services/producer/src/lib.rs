@@ -10,6 +10,7 @@ use indexmap::IndexMap;
 use log::{debug, info, trace, warn};
 use std::collections::HashMap;
 use std::error::Error;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::select;
@@ -17,9 +18,10 @@ use tokio::sync::broadcast;
 use tokio::time::sleep;
 use types::H256;
 
-use chain::IndexedTransaction;
+use chain::{IndexedBlock, IndexedTransaction};
 use context::AppContext;
 use keys::{Address, KeyPair};
+use manager::Manager;
 
 pub enum State {
     Ok,
@@ -56,6 +58,21 @@ pub async fn producer_task(
         keypairs.len(),
         keypairs.keys().map(|k| k.to_string()).collect::<Vec<_>>().join(", ")
     );
+    {
+        let manager = ctx.manager.read().unwrap();
+        for (witness_address, keypair) in &keypairs {
+            if keypair.address() != manager.get_witness_signature_key(witness_address) {
+                warn!(
+                    "producer key of {} is neither the witness nor its witness permission key, blocks will be rejected",
+                    witness_address
+                );
+            }
+        }
+    }
+
+    if config.instant_seal {
+        return instant_seal_task(ctx, keypairs, termination_signal).await;
+    }
 
     // true except first block and first producer
     let mut sync_check_required = false;
@@ -88,6 +105,11 @@ pub async fn producer_task(
                 _ = sleep(Duration::from_millis(d as u64)) => {
                     // produceBlock
                     let mut manager = ctx.manager.write().unwrap();
+                    // the node is shutting down, state-db might be flushed already
+                    if !ctx.running.load(Ordering::SeqCst) {
+                        warn!("block producer closed");
+                        break;
+                    }
 
                     let slot = manager.get_slot(Utc::now().timestamp_millis() + 50);
                     if slot == 0 {
@@ -100,16 +122,21 @@ pub async fn producer_task(
 
                     match block_number {
                         1 => {
-                            info!("👀generating block #1 without sync check");
-                            // FIXME: should choose one from genesis config
-                            let (witness_address, keypair) = keypairs.iter().next().unwrap();
-                            let new_block =
-                                manager.generate_empty_block(block_timestamp, witness_address, keypair).unwrap();
-                            ctx.chain_db.insert_block(&new_block).expect("TODO: handle insert_block error");
-                            ctx.chain_db.update_block_height(new_block.number());
-                            info!("=> {:?}", new_block.hash());
-                            info!("=> produce {:?}", manager.push_generated_block(&new_block));
-                            info!("block pushed");
+                            let witness_address = manager.get_scheduled_witness(slot);
+                            if let Some(keypair) = keypairs.get(&witness_address) {
+                                info!("👀generating block #1 without sync check");
+                                let new_block =
+                                    manager.generate_empty_block(block_timestamp, &witness_address, keypair).unwrap();
+                                ctx.chain_db.insert_block(&new_block).expect("TODO: handle insert_block error");
+                                ctx.chain_db.update_block_height(new_block.number());
+                                info!("=> {:?}", new_block.hash());
+                                info!("=> produce {:?}", manager.push_generated_block(&new_block));
+                                info!("block pushed");
+                                drop(manager);
+                                advertise_block(&ctx, new_block);
+                            } else {
+                                info!("💤not my turn, pass");
+                            }
                         }
                         _ if block_number > 1 => {
                             let witness_address = manager.get_scheduled_witness(slot);
@@ -128,7 +155,9 @@ pub async fn producer_task(
                                 for txn in new_block.transactions.iter() {
                                     mempool.remove(&txn.hash);
                                 }
-
+                                drop(manager);
+                                context::pbft::on_block_applied(&ctx, &new_block);
+                                advertise_block(&ctx, new_block);
                             } else {
                                 info!("💤not my turn, pass");
                             }
@@ -147,6 +176,118 @@ pub async fn producer_task(
     Ok(())
 }
 
+/// The instant-seal dev mode. A block is sealed as soon as transactions arrive, or on a seal request,
+/// at the next slot of any witness in `keypairs`. Transactions not sealed are kept for the next block.
+async fn instant_seal_task(
+    ctx: Arc<AppContext>,
+    keypairs: HashMap<Address, KeyPair>,
+    mut termination_signal: broadcast::Receiver<()>,
+) -> Result<(), Box<dyn Error>> {
+    warn!("🔨instant-seal mode enabled, for development only");
+
+    let mut incoming_transaction_rx = ctx.incoming_transaction_tx.subscribe();
+    let mut seal_request_rx = ctx
+        .seal_request_rx
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or("seal requests are already taken")?;
+    let mut mempool: IndexMap<H256, IndexedTransaction> = IndexMap::new();
+
+    loop {
+        let mut reply = None;
+        select! {
+            Ok(txn) = incoming_transaction_rx.recv() => {
+                info!("new txn => {:?}", txn.hash);
+                mempool.insert(txn.hash, txn);
+            }
+            Some(reply_tx) = seal_request_rx.recv() => {
+                debug!("seal requested");
+                reply = Some(reply_tx);
+            }
+            _ = termination_signal.recv().fuse() => {
+                warn!("block producer closed");
+                break;
+            }
+        }
+        // pack all pending transactions into the block
+        while let Ok(txn) = incoming_transaction_rx.try_recv() {
+            mempool.insert(txn.hash, txn);
+        }
+
+        let mut manager = ctx.manager.write().unwrap();
+        if !ctx.running.load(Ordering::SeqCst) {
+            warn!("block producer closed");
+            break;
+        }
+        let sealed = seal_block(&ctx, &mut manager, &keypairs, mempool.values()).map_err(|e| e.to_string());
+        match sealed {
+            Ok(ref block) => {
+                for txn in block.transactions.iter() {
+                    mempool.remove(&txn.hash);
+                }
+            }
+            Err(ref e) => {
+                warn!("seal block failed: {}", e);
+                // A failed transaction fails the whole block, drop it so that the rest can be sealed later.
+                mempool.retain(|_, txn| manager.pre_push_transaction(txn).is_ok());
+            }
+        }
+        // NOTE: PBFT voting requires the manager.
+        drop(manager);
+        if let Ok(ref block) = sealed {
+            context::pbft::on_block_applied(&ctx, block);
+            advertise_block(&ctx, block.clone());
+        }
+        if let Some(reply_tx) = reply {
+            let _ = reply_tx.send(sealed);
+        }
+    }
+
+    Ok(())
+}
+
+fn seal_block<'a>(
+    ctx: &AppContext,
+    manager: &mut Manager,
+    keypairs: &HashMap<Address, KeyPair>,
+    transactions: impl Iterator<Item = &'a IndexedTransaction>,
+) -> Result<IndexedBlock, Box<dyn Error>> {
+    let first_slot = manager.get_slot(manager.current_timestamp()).max(1);
+    let max_slots =
+        constants::MAX_NUM_OF_ACTIVE_WITNESSES as i64 * constants::NUM_OF_CONSECUTIVE_BLOCKS_PER_ROUND as i64;
+    let (slot, witness_address, keypair) = (first_slot..first_slot + max_slots)
+        .find_map(|slot| {
+            let witness_address = manager.get_scheduled_witness(slot);
+            keypairs.get(&witness_address).map(|kp| (slot, witness_address, kp))
+        })
+        .ok_or("none of producer keypairs is an active witness")?;
+
+    let block_number = manager.latest_block_number() + 1;
+    let block_timestamp = manager.get_slot_timestamp(slot);
+    info!(
+        "🔨sealing block #{} slot={} with {}",
+        block_number, slot, witness_address
+    );
+    let new_block = manager.generate_and_push_block(
+        transactions,
+        block_number,
+        block_timestamp,
+        i64::MAX,
+        &witness_address,
+        keypair,
+    )?;
+    ctx.chain_db.insert_block(&new_block)?;
+    ctx.chain_db.update_block_height(new_block.number());
+    Ok(new_block)
+}
+
+/// Relay a produced block to peers.
+fn advertise_block(ctx: &AppContext, block: IndexedBlock) {
+    ctx.recent_block_ids.write().unwrap().insert(block.header.hash);
+    let _ = ctx.advertising_block_tx.send(block);
+}
+
 fn load_keypairs_from_config(config: &config::ProducerConfig) -> HashMap<Address, KeyPair> {
     let mut keypairs: HashMap<Address, KeyPair> = Default::default();
     for key in &config.keypair {
@@ -157,7 +298,7 @@ fn load_keypairs_from_config(config: &config::ProducerConfig) -> HashMap<Address
             .expect("invalid private key");
         let addr: Address = key.address.parse().expect("Invalid address");
         if keypair.address() != addr {
-            warn!("inconsistent address and private key found, might be a multisig");
+            info!("{} uses a witness permission key {}", addr, keypair.address());
         }
         keypairs.insert(addr, keypair);
     }
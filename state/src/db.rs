@@ -7,14 +7,17 @@ use std::path::Path;
 
 use ::keys::Address;
 use config::genesis::GenesisConfig;
-use config::ChainConfig;
-use log::info;
+use config::{ChainConfig, RocksDbConfig};
+use log::{info, warn};
 use proto::common::AccountType;
 use proto::state as state_pb;
-use rocks::prelude::*;
+use rocks::prelude::{ColumnFamilyOptions, CompressionType, DBOptions, DEFAULT_COLUMN_FAMILY_NAME};
+use storage::{prefix_upper_bound, MemoryStorage, RocksStorage, Storage, WriteBatch};
+use types::H256;
 
 use super::keys;
 use super::parameter::default_parameters_from_config;
+use super::trie::{self, Trie};
 use super::DynamicProperty;
 
 pub type BoxError = Box<dyn ::std::error::Error>;
@@ -23,7 +26,9 @@ pub struct OverlayWriteBatch {
     wb: WriteBatch,
     // CF => (Key => Value)
     // TODO: replace with VecMap
-    cache: HashMap<u32, BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+    cache: HashMap<usize, BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+    // Address => AccountStateEntity, accounts to be applied to the account state trie.
+    account_states: BTreeMap<Vec<u8>, Vec<u8>>,
 }
 
 impl std::ops::Deref for OverlayWriteBatch {
@@ -38,41 +43,35 @@ impl OverlayWriteBatch {
         OverlayWriteBatch {
             wb: WriteBatch::new(),
             cache: HashMap::new(),
+            account_states: BTreeMap::new(),
         }
     }
 
-    pub fn with_capacity(cap: usize) -> Self {
-        OverlayWriteBatch {
-            wb: WriteBatch::with_reserved_bytes(cap),
-            cache: HashMap::new(),
-        }
-    }
-
-    pub fn put(&mut self, col: &ColumnFamilyHandle, key: &[u8], value: &[u8]) {
-        self.wb.put_cf(col, key, value);
+    pub fn put(&mut self, col: usize, key: &[u8], value: &[u8]) {
+        self.wb.put(col, key, value);
         self.cache
-            .entry(col.id())
+            .entry(col)
             .or_default()
             .insert(key.to_owned(), Some(value.to_owned()));
     }
 
-    pub fn delete(&mut self, col: &ColumnFamilyHandle, key: &[u8]) {
-        self.wb.delete_cf(col, key);
-        self.cache.entry(col.id()).or_default().insert(key.to_owned(), None);
+    pub fn delete(&mut self, col: usize, key: &[u8]) {
+        self.wb.delete(col, key);
+        self.cache.entry(col).or_default().insert(key.to_owned(), None);
     }
 
     // Ok(None) => deleted
     // Err(_)   => non-exist
-    pub fn get(&self, col: &ColumnFamilyHandle, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    pub fn get(&self, col: usize, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
         self.cache
-            .get(&col.id())
+            .get(&col)
             .and_then(|cf| cf.get(key).cloned())
             .ok_or(io::Error::new(io::ErrorKind::NotFound, ""))
     }
 
     // None => deleted or not-found
-    pub fn get_by_prefix(&self, col: &ColumnFamilyHandle, prefix: &[u8]) -> Option<Box<[u8]>> {
-        self.cache.get(&col.id()).and_then(|cf| {
+    pub fn get_by_prefix(&self, col: usize, prefix: &[u8]) -> Option<Box<[u8]>> {
+        self.cache.get(&col).and_then(|cf| {
             cf.iter()
                 .filter(|(key, value)| key.starts_with(prefix) && value.is_some())
                 .map(|(_, value)| value.clone().unwrap().into_boxed_slice())
@@ -80,9 +79,9 @@ impl OverlayWriteBatch {
         })
     }
 
-    pub fn iter<'a>(&'a self, col: &ColumnFamilyHandle) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+    pub fn iter<'a>(&'a self, col: usize) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
         self.cache
-            .get(&col.id())
+            .get(&col)
             .map(|cf| {
                 Box::new(cf.iter().filter(|(_, value)| value.is_some()).map(|(key, value)| {
                     (
@@ -98,11 +97,11 @@ impl OverlayWriteBatch {
     /// where the key starts with the given prefix.
     pub fn iter_with_prefix<'a>(
         &'a self,
-        col: &ColumnFamilyHandle,
+        col: usize,
         prefix: &'a [u8],
     ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
         self.cache
-            .get(&col.id())
+            .get(&col)
             .map(|cf| {
                 Box::new(
                     cf.iter()
@@ -120,14 +119,14 @@ impl OverlayWriteBatch {
 }
 
 pub struct OverlayDB {
-    inner: DB,
+    inner: Box<dyn Storage>,
     // Use push_back to add to the queue, and pop_front to remove from the queue.
     // push_back to add a new layer, pop_front to sync a layer to db, clear to discard all layers.
     layers: VecDeque<OverlayWriteBatch>,
 }
 
 impl OverlayDB {
-    pub fn new(inner: DB) -> Self {
+    pub fn new(inner: Box<dyn Storage>) -> Self {
         OverlayDB {
             inner,
             // ceiling(27 - 27 * 70%) = 9
@@ -147,7 +146,7 @@ impl OverlayDB {
 
     pub fn finalize_layers(&mut self) -> Result<(), BoxError> {
         for layer in self.layers.drain(..) {
-            self.inner.write(WriteOptions::default_instance(), &layer.wb)?;
+            self.inner.write(&layer.wb)?;
         }
         Ok(())
     }
@@ -158,41 +157,31 @@ impl OverlayDB {
     }
 
     /// Get a value by key.
-    pub fn get(&self, col: &ColumnFamilyHandle, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    pub fn get(&self, col: usize, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
         for layer in self.layers.iter().rev() {
             if let Ok(val) = layer.get(col, key) {
                 return Ok(val);
             }
         }
-        match self.inner.get_cf(ReadOptions::default_instance(), col, key) {
-            Ok(val) => Ok(Some(val.to_vec())),
-            Err(e) if e.is_not_found() => Ok(None),
-            // Err(e) if e.is_not_found() => Err(io::Error::new(io::ErrorKind::NotFound, "")),
-            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e.to_string())),
-        }
+        self.inner.get(col, key)
     }
 
     /// Get a value by key, skip top n layers.
-    pub fn get_skipped(&self, n: usize, col: &ColumnFamilyHandle, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    pub fn get_skipped(&self, n: usize, col: usize, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
         for layer in self.layers.iter().rev().skip(n) {
             if let Ok(val) = layer.get(col, key) {
                 return Ok(val);
             }
         }
-        match self.inner.get_cf(ReadOptions::default_instance(), col, key) {
-            Ok(val) => Ok(Some(val.to_vec())),
-            Err(e) if e.is_not_found() => Ok(None),
-            // Err(e) if e.is_not_found() => Err(io::Error::new(io::ErrorKind::NotFound, "")),
-            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e.to_string())),
-        }
+        self.inner.get(col, key)
     }
 
     /// Get the first value matching the given prefix.
-    pub fn get_by_prefix(&self, col: &ColumnFamilyHandle, prefix: &[u8]) -> Option<Box<[u8]>> {
+    pub fn get_by_prefix(&self, col: usize, prefix: &[u8]) -> Option<Box<[u8]>> {
         let mut deleted = HashSet::<&[u8]>::new();
 
         for layer in self.layers.iter().rev() {
-            if let Some(cache) = layer.cache.get(&col.id()) {
+            if let Some(cache) = layer.cache.get(&col) {
                 for (key, value) in cache.iter().filter(|(key, _)| key.starts_with(prefix)) {
                     if deleted.contains(&**key) {
                         continue;
@@ -209,29 +198,27 @@ impl OverlayDB {
             }
         }
 
-        for (key, value) in self
-            .inner
-            .new_iterator_cf(&ReadOptions::default().iterate_lower_bound(prefix), col)
-        {
-            if !key.starts_with(prefix) {
-                return None;
-            }
-            if deleted.contains(key) {
-                continue;
-            }
-            return Some(value.to_vec().into_boxed_slice());
-        }
-        None
+        let mut found = None;
+        self.inner
+            .scan(col, prefix, prefix_upper_bound(prefix).as_deref(), &mut |key, value| {
+                if deleted.contains(key) {
+                    return true;
+                }
+                found = Some(value.to_vec().into_boxed_slice());
+                false
+            })
+            .expect("corrupted db");
+        found
     }
 
-    pub fn for_each<F>(&self, col: &ColumnFamilyHandle, mut func: F)
+    pub fn for_each<F>(&self, col: usize, mut func: F)
     where
         F: FnMut(&[u8], &[u8]) -> (),
     {
         let mut visited: HashSet<&[u8]> = HashSet::new();
 
         for layer in self.layers.iter().rev() {
-            if let Some(cache) = layer.cache.get(&col.id()) {
+            if let Some(cache) = layer.cache.get(&col) {
                 for (key, value) in cache.iter() {
                     if visited.contains(&**key) {
                         continue;
@@ -247,28 +234,27 @@ impl OverlayDB {
             }
         }
 
-        for (key, value) in self.inner.new_iterator_cf(&ReadOptions::default(), col) {
-            if visited.contains(key) {
-                continue;
-            }
-            func(key, value);
-        }
+        self.inner
+            .scan(col, &[], None, &mut |key, value| {
+                if !visited.contains(key) {
+                    func(key, value);
+                }
+                true
+            })
+            .expect("corrupted db");
     }
 
     /// Iterate over the data for a given column, returning all key/value pairs
     /// where the key starts with the given prefix.
-    pub fn for_each_by_prefix<F>(&self, col: &ColumnFamilyHandle, prefix: &[u8], mut func: F)
+    pub fn for_each_by_prefix<F>(&self, col: usize, prefix: &[u8], mut func: F)
     where
         F: FnMut(&[u8], &[u8]) -> (),
     {
         let mut visited = HashSet::<&[u8]>::new();
 
         for layer in self.layers.iter().rev() {
-            if let Some(cache) = layer.cache.get(&col.id()) {
+            if let Some(cache) = layer.cache.get(&col) {
                 for (key, value) in cache.iter().filter(|(key, _)| key.starts_with(prefix)) {
-                    if !key.starts_with(prefix) {
-                        continue;
-                    }
                     if visited.contains(&**key) {
                         continue;
                     }
@@ -283,21 +269,16 @@ impl OverlayDB {
             }
         }
 
-        for (key, value) in self
-            .inner
-            .new_iterator_cf(&ReadOptions::default().iterate_lower_bound(prefix), col)
-        {
-            if !key.starts_with(prefix) {
-                return;
-            }
-            if visited.contains(key) {
-                continue;
-            }
-            func(key, value);
-        }
+        self.inner
+            .scan_prefix(col, prefix, &mut |key, value| {
+                if !visited.contains(key) {
+                    func(key, value);
+                }
+            })
+            .expect("corrupted db");
     }
 
-    pub fn delete(&mut self, col: &ColumnFamilyHandle, key: &[u8]) -> io::Result<()> {
+    pub fn delete(&mut self, col: usize, key: &[u8]) -> io::Result<()> {
         let wb = self
             .layers
             .back_mut()
@@ -306,43 +287,11 @@ impl OverlayDB {
         Ok(())
     }
 
-    pub fn delete_by_prefix(&mut self, col: &ColumnFamilyHandle, prefix: &[u8]) -> io::Result<()> {
-        let mut visited = HashSet::<&[u8]>::new();
+    pub fn delete_by_prefix(&mut self, col: usize, prefix: &[u8]) -> io::Result<()> {
         let mut deleted = HashSet::<Vec<u8>>::new();
-
-        for layer in self.layers.iter().rev() {
-            if let Some(cache) = layer.cache.get(&col.id()) {
-                for (key, value) in cache.iter().filter(|(key, _)| key.starts_with(prefix)) {
-                    if !key.starts_with(prefix) {
-                        continue;
-                    }
-                    if visited.contains(&**key) {
-                        continue;
-                    }
-                    visited.insert(key);
-                    match value {
-                        Some(_) => {
-                            deleted.insert(key.to_vec());
-                        }
-                        None => (),
-                    }
-                }
-            }
-        }
-
-        for key in self
-            .inner
-            .new_iterator_cf(&ReadOptions::default().iterate_lower_bound(prefix), col)
-            .keys()
-        {
-            if !key.starts_with(prefix) {
-                return Ok(());
-            }
-            if visited.contains(key) {
-                continue;
-            }
+        self.for_each_by_prefix(col, prefix, |key, _| {
             deleted.insert(key.to_vec());
-        }
+        });
         for key in &deleted {
             self.delete(col, key)?;
         }
@@ -369,22 +318,35 @@ pub const COL_TRANSACTION_LOG: usize = 13;
 pub const COL_ACCOUNT_INDEX: usize = 14;
 pub const COL_VOTER_REWARD: usize = 15;
 pub const COL_EXCHANGE: usize = 16;
+/// Nodes of the account state trie.
+pub const COL_ACCOUNT_STATE: usize = 17;
+pub const COL_VOTE_CHANGE: usize = 18;
+pub const COL_WITNESS_STATISTIC: usize = 19;
+pub const NUM_COLS: usize = 20;
 
 /// The State DB derived from Chain DB.
 pub struct StateDB {
     db: OverlayDB,
-    cols: Vec<ColumnFamily>,
+    // Record updated accounts for the account state trie.
+    track_account_states: bool,
 }
 
 impl Drop for StateDB {
     fn drop(&mut self) {
-        info!("state-db closed successfully, all cached layers will be droped");
+        if self.db.layers.is_empty() {
+            info!("state-db closed successfully");
+        } else {
+            warn!(
+                "state-db closed successfully, {} uncommitted layers are droped",
+                self.db.layers.len()
+            );
+        }
     }
 }
 
-fn col_descs_for_state_db() -> Vec<ColumnFamilyDescriptor> {
+fn col_descs_for_state_db() -> Vec<(&'static str, ColumnFamilyOptions)> {
     vec![
-        ColumnFamilyDescriptor::new(
+        (
             DEFAULT_COLUMN_FAMILY_NAME,
             ColumnFamilyOptions::default()
                 .optimize_for_small_db()
@@ -393,40 +355,40 @@ fn col_descs_for_state_db() -> Vec<ColumnFamilyDescriptor> {
                 .compression(CompressionType::NoCompression),
         ),
         // address => Account
-        ColumnFamilyDescriptor::new("account", ColumnFamilyOptions::default().optimize_for_point_lookup(128)),
+        ("account", ColumnFamilyOptions::default().optimize_for_point_lookup(128)),
         // address => AccountResource
-        /*ColumnFamilyDescriptor::new(
+        /*(
             "account-resource",
             ColumnFamilyOptions::default().optimize_for_point_lookup(128),
         ),*/
         // <<from_address, to_address>> => AccountResourceDelegation
-        ColumnFamilyDescriptor::new(
+        (
             "resource-delegation",
             ColumnFamilyOptions::default().optimize_for_point_lookup(128),
         ),
         // to_address => [from_address]
-        ColumnFamilyDescriptor::new(
+        (
             "resource-delegation-index",
             ColumnFamilyOptions::default().optimize_for_point_lookup(128),
         ),
         // address => Votes
-        ColumnFamilyDescriptor::new("account-votes", ColumnFamilyOptions::default()),
+        ("account-votes", ColumnFamilyOptions::default()),
         // address => Contract
-        ColumnFamilyDescriptor::new("contract", ColumnFamilyOptions::default().optimize_for_point_lookup(32)),
+        ("contract", ColumnFamilyOptions::default().optimize_for_point_lookup(32)),
         // address => Code
-        ColumnFamilyDescriptor::new(
+        (
             "contract-code",
             ColumnFamilyOptions::default().optimize_for_point_lookup(128),
         ),
         // <<contract_address: Address, storage_key: H256>> => H256
-        ColumnFamilyDescriptor::new(
+        (
             "contract-storage",
             ColumnFamilyOptions::default()
                 .optimize_for_point_lookup(32)
                 .prefix_extractor_fixed(32),
         ),
         // <<Address>> => Witness
-        ColumnFamilyDescriptor::new(
+        (
             "witness",
             ColumnFamilyOptions::default()
                 .optimize_for_small_db()
@@ -435,7 +397,7 @@ fn col_descs_for_state_db() -> Vec<ColumnFamilyDescriptor> {
                 .compression(CompressionType::NoCompression),
         ),
         // <<id: u64>> => Proposal
-        ColumnFamilyDescriptor::new(
+        (
             "proposal",
             ColumnFamilyOptions::default()
                 .optimize_for_small_db()
@@ -444,82 +406,107 @@ fn col_descs_for_state_db() -> Vec<ColumnFamilyDescriptor> {
                 .compression(CompressionType::NoCompression),
         ),
         // <<id: u64>> => Asset
-        ColumnFamilyDescriptor::new(
+        (
             "asset",
             ColumnFamilyOptions::default()
                 .optimize_for_small_db()
                 .optimize_for_point_lookup(16),
         ),
         // <<txid: H256>> -> TransactionReceipt
-        ColumnFamilyDescriptor::new(
+        (
             "transaction-receipt",
             ColumnFamilyOptions::default().optimize_for_point_lookup(16),
         ),
         // <<txid: H256>> -> InternalTransaction
-        ColumnFamilyDescriptor::new(
+        (
             "internal-transaction",
             ColumnFamilyOptions::default().optimize_for_point_lookup(16),
         ),
         // <<Address, Topic: H256, [IndexedParam]>> => Transaction
-        ColumnFamilyDescriptor::new(
+        (
             "transaction-log",
             ColumnFamilyOptions::default().prefix_extractor_fixed(32),
         ),
         // <<account_name: str>> => Address
-        ColumnFamilyDescriptor::new(
+        (
             "account-index",
             ColumnFamilyOptions::default()
                 .optimize_for_point_lookup(16)
                 .compression(CompressionType::NoCompression),
         ),
-        ColumnFamilyDescriptor::new(
+        (
             "voter-reward",
             ColumnFamilyOptions::default()
                 .optimize_for_small_db()
                 .optimize_for_point_lookup(16),
         ),
-        ColumnFamilyDescriptor::new(
+        (
             "exchange",
             ColumnFamilyOptions::default()
                 .optimize_for_small_db()
                 .optimize_for_point_lookup(16),
         ),
+        // <<node_hash: H256>> => account state trie node
+        (
+            "account-state",
+            ColumnFamilyOptions::default()
+                .optimize_for_point_lookup(32)
+                .compression(CompressionType::NoCompression),
+        ),
+        // address => VoteChange
+        (
+            "vote-change",
+            ColumnFamilyOptions::default()
+                .optimize_for_small_db()
+                .optimize_for_point_lookup(16),
+        ),
+        // <<epoch: i64, Address>> => WitnessEpochStatistic
+        (
+            "witness-statistic",
+            ColumnFamilyOptions::default()
+                .optimize_for_small_db()
+                .optimize_for_point_lookup(16),
+        ),
     ]
 }
 
 impl StateDB {
-    pub fn new<P: AsRef<Path>>(db_path: P) -> StateDB {
+    pub fn new<P: AsRef<Path>>(db_path: P, config: &RocksDbConfig) -> StateDB {
         std::fs::create_dir_all(&db_path).expect("create db directory");
 
-        let db_options = DBOptions::default()
-            .create_if_missing(true)
-            .create_missing_column_families(true)
-            .increase_parallelism(num_cpus::get() as _)
-            .allow_mmap_reads(true) // for Cuckoo table
-            .max_open_files(1024);
-
         let column_families = col_descs_for_state_db();
 
-        let (db, cols) = DB::open_with_column_families(&db_options, db_path, column_families).unwrap();
+        let db = RocksStorage::open(config, db_path, column_families).unwrap();
+
+        StateDB::with_storage(Box::new(db))
+    }
+
+    /// Create a state-db in memory, all states are lost when dropped.
+    pub fn new_in_memory() -> StateDB {
+        StateDB::with_storage(Box::new(MemoryStorage::new(NUM_COLS)))
+    }
 
+    pub fn with_storage(storage: Box<dyn Storage>) -> StateDB {
         StateDB {
-            db: OverlayDB::new(db),
-            cols,
+            db: OverlayDB::new(storage),
+            track_account_states: false,
         }
     }
 }
 
 impl StateDB {
     pub fn new_layer(&mut self) -> &mut OverlayWriteBatch {
-        self.db.push_layer(OverlayWriteBatch::with_capacity(4 * 1024));
+        self.db.push_layer(OverlayWriteBatch::new());
         self.db.layers.back_mut().unwrap()
     }
 
     pub fn finalize_layer(&mut self) {
-        self.db
-            .layers
-            .pop_front()
-            .map(|wb| self.db.inner.write(WriteOptions::default_instance(), &wb));
+        self.db.layers.pop_front().map(|wb| self.db.inner.write(&wb));
+    }
+
+    /// Number of in-memory layers.
+    pub fn num_layers(&self) -> usize {
+        self.db.layers.len()
     }
 
     pub fn discard_last_layer(&mut self) -> io::Result<()> {
@@ -536,37 +523,45 @@ impl StateDB {
             .layers
             .back_mut()
             .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "no db layers found"))?;
-        wb.put(&self.cols[K::COL], key.key().as_ref(), &*K::value(&value));
+        let raw_key = key.key();
+        let raw_value = K::value(&value);
+        wb.put(K::COL, raw_key.as_ref(), &*raw_value);
+        if K::COL == COL_ACCOUNT && self.track_account_states {
+            let acct = <keys::Account as keys::Key<state_pb::Account>>::parse_value(&*raw_value);
+            let entity = trie::account_state_entity(raw_key.as_ref(), acct.balance, acct.allowance);
+            wb.account_states.insert(raw_key.as_ref().to_vec(), entity);
+        }
         Ok(())
     }
 
+    // NOTE: Deleted accounts are kept in the account state trie, the same as java-tron.
     pub fn delete_key<T, K: keys::Key<T>>(&mut self, key: &K) -> Result<(), BoxError> {
-        self.db.delete(&self.cols[K::COL], key.key().as_ref())?;
+        self.db.delete(K::COL, key.key().as_ref())?;
         Ok(())
     }
 
-    pub fn delete_by_prefix(&mut self, col: &ColumnFamilyHandle, prefix: &[u8]) -> Result<(), BoxError> {
+    pub fn delete_by_prefix(&mut self, col: usize, prefix: &[u8]) -> Result<(), BoxError> {
         self.db.delete_by_prefix(col, prefix)?;
         Ok(())
     }
 
     pub fn get<T, K: keys::Key<T>>(&self, key: &K) -> Result<Option<T>, BoxError> {
         self.db
-            .get(&self.cols[K::COL], key.key().as_ref())
+            .get(K::COL, key.key().as_ref())
             .map(|maybe_raw| maybe_raw.map(|raw| K::parse_value(&raw)))
             .map_err(|e| e.into())
     }
 
     pub fn get_skipped<T, K: keys::Key<T>>(&self, n: usize, key: &K) -> Result<Option<T>, BoxError> {
         self.db
-            .get_skipped(n, &self.cols[K::COL], key.key().as_ref())
+            .get_skipped(n, K::COL, key.key().as_ref())
             .map(|maybe_raw| maybe_raw.map(|raw| K::parse_value(&raw)))
             .map_err(|e| e.into())
     }
 
     pub fn must_get_skipped<T, K: keys::Key<T>>(&self, n: usize, key: &K) -> T {
         self.db
-            .get_skipped(n, &self.cols[K::COL], key.key().as_ref())
+            .get_skipped(n, K::COL, key.key().as_ref())
             .map(|maybe_raw| maybe_raw.map(|raw| K::parse_value(&raw)))
             .expect("corrupted db")
             .expect("key must exist")
@@ -574,7 +569,7 @@ impl StateDB {
 
     pub fn must_get<T, K: keys::Key<T>>(&self, key: &K) -> T {
         self.db
-            .get(&self.cols[K::COL], key.key().as_ref())
+            .get(K::COL, key.key().as_ref())
             .map(|maybe_raw| maybe_raw.map(|raw| K::parse_value(&raw)))
             .expect("corrupted db")
             .expect("key must exist")
@@ -591,7 +586,7 @@ impl StateDB {
     where
         F: FnMut(&K, &T) -> (),
     {
-        self.db.for_each(&self.cols[K::COL], move |key, value| {
+        self.db.for_each(K::COL, move |key, value| {
             if let Some(key) = K::parse_key(key) {
                 func(&key, &K::parse_value(value));
             }
@@ -602,12 +597,49 @@ impl StateDB {
     where
         F: FnMut(&K, &T) -> (),
     {
-        self.db
-            .for_each_by_prefix(&self.cols[K::COL], prefix, move |key, value| {
-                if let Some(key) = K::parse_key(key) {
-                    func(&key, &K::parse_value(value));
-                }
-            });
+        self.db.for_each_by_prefix(K::COL, prefix, move |key, value| {
+            if let Some(key) = K::parse_key(key) {
+                func(&key, &K::parse_value(value));
+            }
+        });
+    }
+
+    /// Start or stop recording accounts updated by `put_key` for the account state trie.
+    pub fn track_account_states(&mut self, enabled: bool) {
+        self.track_account_states = enabled;
+    }
+
+    /// Apply accounts recorded since the last update to the account state trie of `root`, and
+    /// return the new root. New trie nodes are written to the current layer.
+    pub fn update_account_state_trie(&mut self, root: &H256) -> Result<H256, BoxError> {
+        let mut account_states = BTreeMap::new();
+        for layer in self.db.layers.iter_mut() {
+            account_states.append(&mut layer.account_states);
+        }
+
+        let db = &self.db;
+        let load = |hash: &H256| db.get(COL_ACCOUNT_STATE, hash.as_bytes()).ok().flatten();
+        let mut state_trie = Trie::new(root);
+        for (address, entity) in &account_states {
+            state_trie.insert(&trie::account_state_key(address), entity, &load)?;
+        }
+        let (root, nodes) = state_trie.commit();
+
+        let wb = self
+            .db
+            .layers
+            .back_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "no db layers found"))?;
+        for (hash, node) in nodes {
+            wb.put(COL_ACCOUNT_STATE, hash.as_bytes(), &node);
+        }
+        Ok(root)
+    }
+
+    /// Flush memtables and WAL, cancel background jobs. In-memory layers are untouched.
+    pub unsafe fn prepare_close(&self) {
+        info!("closing state-db ...");
+        self.db.inner.prepare_close();
     }
 
     pub fn init_genesis(&mut self, genesis: &GenesisConfig, chain: &ChainConfig) -> Result<(), BoxError> {
@@ -619,7 +651,6 @@ impl StateDB {
                 "state-db is already inited, db version: {}, block number: {}, block hash: {:?}",
                 db_ver, latest_block_numer, latest_block_hash
             );
-
             return Ok(());
         }
 
@@ -702,15 +733,11 @@ impl StateDB {
 }
 
 pub struct ReadOnlySolidStateDB {
-    db: DB,
-    cols: Vec<ColumnFamily>,
+    db: RocksStorage,
 }
 
-unsafe impl Send for ReadOnlySolidStateDB {}
-unsafe impl Sync for ReadOnlySolidStateDB {}
-
 impl ReadOnlySolidStateDB {
-    pub fn new<P1: AsRef<Path>, P2: AsRef<Path>>(db_path: P1, tmp_path: P2) -> StateDB {
+    pub fn new<P1: AsRef<Path>, P2: AsRef<Path>>(db_path: P1, tmp_path: P2) -> ReadOnlySolidStateDB {
         let db_options = DBOptions::default()
             .increase_parallelism(num_cpus::get() as _)
             .allow_mmap_reads(true) // for Cuckoo table
@@ -718,24 +745,65 @@ impl ReadOnlySolidStateDB {
 
         let column_families = col_descs_for_state_db();
 
-        let (db, cols) =
-            DB::open_as_secondary_with_column_families(&db_options, db_path, tmp_path, column_families).unwrap();
+        let db = RocksStorage::open_as_secondary(&db_options, db_path, tmp_path, column_families).unwrap();
 
-        StateDB {
-            db: OverlayDB::new(db),
-            cols,
-        }
+        ReadOnlySolidStateDB { db }
     }
 
     pub fn get<T, K: keys::Key<T>>(&self, key: &K) -> Result<Option<T>, BoxError> {
         self.db
-            .get_cf(ReadOptions::default_instance(), &self.cols[K::COL], key.key().as_ref())
-            .map(|raw| Some(K::parse_value(&raw)))
-            .or_else(|e| if e.is_not_found() { Ok(None) } else { Err(e) })
+            .get(K::COL, key.key().as_ref())
+            .map(|maybe_raw| maybe_raw.map(|raw| K::parse_value(&raw)))
             .map_err(|e| e.into())
     }
 
     pub fn catch_up_with_primary(&self) {
-        let _ = self.db.try_catch_up_with_primary();
+        self.db.catch_up_with_primary();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_state_trie_follows_tracked_accounts() {
+        let mut db = StateDB::new_in_memory();
+        let addr: Address = "TPswDDCAWhJAZGdHPidFg5nEf8TkNToDX1".parse().unwrap();
+        let mut acct = state_pb::Account {
+            balance: 1_000_000,
+            ..Default::default()
+        };
+
+        db.new_layer();
+        // not tracked
+        db.put_key(keys::Account(addr), acct.clone()).unwrap();
+        let empty_root = db.update_account_state_trie(&H256::zero()).unwrap();
+        assert_eq!(empty_root, trie::empty_trie_hash());
+
+        db.track_account_states(true);
+        db.new_layer();
+        acct.balance = 2_000_000;
+        db.put_key(keys::Account(addr), acct.clone()).unwrap();
+        // accounts recorded in a discarded layer are dropped
+        db.discard_last_layer().unwrap();
+        acct.balance = 1_000_000;
+        db.put_key(keys::Account(addr), acct.clone()).unwrap();
+        db.track_account_states(false);
+
+        let root = db.update_account_state_trie(&empty_root).unwrap();
+        assert_eq!(
+            root,
+            "ed0514a19231741bea2e8736b853001d10455701e6617814f49f8d79422d1158"
+                .parse::<H256>()
+                .unwrap()
+        );
+        // recorded accounts are consumed, and the trie is reopened from stored nodes
+        assert_eq!(db.update_account_state_trie(&root).unwrap(), root);
+
+        db.track_account_states(true);
+        db.put_key(keys::Account(addr), acct).unwrap();
+        db.delete_key(&keys::Account(addr)).unwrap();
+        assert_eq!(db.update_account_state_trie(&root).unwrap(), root);
     }
 }
@@ -142,6 +142,30 @@ impl Key<H256> for LatestBlockHash {
     }
 }
 
+/// Account state root in the header of latest block, zero if empty.
+#[derive(Debug)]
+pub struct AccountStateRoot;
+
+impl Key<H256> for AccountStateRoot {
+    type Target = &'static str;
+    const COL: usize = super::db::COL_DEFAULT;
+
+    fn key(&self) -> Self::Target {
+        "kAccountStateRoot"
+    }
+
+    fn value(val: &H256) -> Cow<[u8]> {
+        val.as_bytes().into()
+    }
+
+    fn parse_value(raw: &[u8]) -> H256 {
+        if raw.len() != 32 {
+            panic!("malformed kAccountStateRoot");
+        }
+        H256::from_slice(raw)
+    }
+}
+
 #[derive(Debug)]
 pub struct BlockFilledSlots;
 
@@ -217,6 +241,33 @@ impl Key<pb::WitnessVoterReward> for VoterReward {
     }
 }
 
+/// Block producing statistic for one epoch of a witness.
+/// `<<epoch: i64, Address>> => WitnessEpochStatistic { produced, missed }`
+#[derive(Debug)]
+pub struct WitnessStatistic(pub i64, pub Address);
+
+impl Key<pb::WitnessEpochStatistic> for WitnessStatistic {
+    type Target = Vec<u8>;
+    const COL: usize = super::db::COL_WITNESS_STATISTIC;
+
+    fn key(&self) -> Self::Target {
+        let mut raw = vec![0u8; 8 + 20];
+        raw[0..8].copy_from_slice(&self.0.to_be_bytes()[..]);
+        raw[8..].copy_from_slice(self.1.as_tvm_bytes());
+        raw
+    }
+
+    fn value(val: &pb::WitnessEpochStatistic) -> Cow<[u8]> {
+        let mut buf = BytesMut::with_capacity(val.encoded_len());
+        val.encode(&mut buf).unwrap();
+        Cow::from(buf.to_vec())
+    }
+
+    fn parse_value(raw: &[u8]) -> pb::WitnessEpochStatistic {
+        pb::WitnessEpochStatistic::decode(raw).unwrap()
+    }
+}
+
 #[derive(Debug)]
 pub struct Account(pub Address);
 
@@ -286,6 +337,32 @@ impl Key<pb::ResourceDelegation> for ResourceDelegation {
     }
 }
 
+/// Stake 2.0 resource delegation, from_address, to_address, locked or not.
+///
+/// Expiration timestamps are only used by locked delegations.
+#[derive(Debug)]
+pub struct ResourceDelegationV2(pub Address, pub Address, pub bool);
+
+impl Key<pb::ResourceDelegation> for ResourceDelegationV2 {
+    type Target = Vec<u8>;
+    const COL: usize = super::db::COL_RESOURCE_DELEGATION;
+
+    fn key(&self) -> Self::Target {
+        // NOTE: One byte longer than a legacy `ResourceDelegation` key.
+        [self.0.as_bytes(), self.1.as_bytes(), &[self.2 as u8 + 1]].concat()
+    }
+
+    fn value(val: &pb::ResourceDelegation) -> Cow<[u8]> {
+        let mut buf = BytesMut::with_capacity(val.encoded_len());
+        val.encode(&mut buf).unwrap();
+        Cow::from(buf.to_vec())
+    }
+
+    fn parse_value(raw: &[u8]) -> pb::ResourceDelegation {
+        pb::ResourceDelegation::decode(raw).unwrap()
+    }
+}
+
 /// Reverse index for resource delegation info, to_address.
 #[derive(Debug)]
 pub struct ResourceDelegationIndex(pub Address);
@@ -340,6 +417,33 @@ impl Key<pb::Votes> for Votes {
     }
 }
 
+/// `<<Address>> => VoteChange { old_votes: [Vote], new_votes: [Vote] }`
+#[derive(Debug)]
+pub struct VoteChange(pub Address);
+
+impl Key<pb::VoteChange> for VoteChange {
+    type Target = Vec<u8>;
+    const COL: usize = super::db::COL_VOTE_CHANGE;
+
+    fn key(&self) -> Self::Target {
+        self.0.as_bytes().to_vec()
+    }
+
+    fn value(val: &pb::VoteChange) -> Cow<[u8]> {
+        let mut buf = BytesMut::with_capacity(val.encoded_len());
+        val.encode(&mut buf).unwrap();
+        Cow::from(buf.to_vec())
+    }
+
+    fn parse_value(raw: &[u8]) -> pb::VoteChange {
+        pb::VoteChange::decode(raw).unwrap()
+    }
+
+    fn parse_key(raw: &[u8]) -> Option<Self> {
+        Some(VoteChange(*Address::from_bytes(raw)))
+    }
+}
+
 /// `Address => pb::SmartContract`
 #[derive(Debug)]
 pub struct Contract(pub Address);
@@ -5,3 +5,4 @@ pub mod db;
 pub mod keys;
 pub mod parameter;
 mod property;
+mod trie;
@@ -46,6 +46,10 @@ pub fn default_parameters() -> impl IntoIterator<Item = (ChainParameter, i64)> {
         (AllowTvmShieldedUpgrade, 0),
         (AllowTvmIstanbulUpgrade, 0),
         (AllowProtoFilterNum, 0),
+        (AllowPbft, 0),
+        (AllowTransactionFeePool, 0),
+        (AllowBlackholeOptimization, 0),
+        (UnfreezeDelayDays, 0),
     ];
 }
 
@@ -97,5 +101,9 @@ pub fn default_parameters_from_config(
         (AllowTvmShieldedUpgrade, config.allow_tvm_shielded_upgrade as i64),
         (AllowTvmIstanbulUpgrade, config.allow_tvm_istanbul_upgrade as i64),
         (AllowProtoFilterNum, 0),
+        (AllowPbft, 0),
+        (AllowTransactionFeePool, 0),
+        (AllowBlackholeOptimization, 0),
+        (UnfreezeDelayDays, 0),
     ];
 }
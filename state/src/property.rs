@@ -24,6 +24,8 @@ pub enum DynamicProperty {
     LatestBlockNumber,
     // LatestBlockHash,
     LatestSolidBlockNumber,
+    /// Latest block finalized by PBFT commits, when `AllowPbft` is on.
+    LatestPbftBlockNumber,
 
     IsMaintenance,
     NextMaintenanceTime,
@@ -62,6 +64,14 @@ pub enum DynamicProperty {
     GlobalFreeBandwidthUsed,
     /// Renamed: PublicNetTime = 0
     GlobalFreeBandwidthLatestSlot,
+
+    // * Fees
+    /// Transaction fees to be paid to the block producer, when `AllowTransactionFeePool` is on.
+    TransactionFeePool,
+    /// Burned TRX, deducted from the total supply, when `AllowBlackholeOptimization` is on.
+    ///
+    /// Renamed: BurnTrxAmount
+    BurnedTrx,
     // * Unused and deprecated
     // ! Why a block scoped variable is saved to store?
     // BlockEnergyUsage
@@ -102,6 +112,7 @@ impl DynamicProperty {
             // will be overwriten when apply genesis block
             (LatestBlockNumber, -1),
             (LatestSolidBlockNumber, -1),
+            (LatestPbftBlockNumber, 0),
             // * maintenance
             (IsMaintenance, 0),
             // FIXME: should be after genesis timestamp
@@ -120,6 +131,8 @@ impl DynamicProperty {
             // (TotalEnergyTargetLimit, 90_000_000_000 / 14400)
             (TotalEnergyAverageUsage, 0),
             (TotalEnergyAverageSlot, 0),
+            (TransactionFeePool, 0),
+            (BurnedTrx, 0),
         ];
     }
 
@@ -0,0 +1,440 @@
+//! Merkle Patricia trie of account states, the same as `AccountStateRoot` of java-tron.
+//!
+//! Nodes are RLP encoded and hashed with keccak256, nodes shorter than 32 bytes are embedded in
+//! their parents. Nodes are never deleted, so the trie of any root written before can be reopened.
+
+use std::io;
+use std::mem;
+
+use types::H256;
+
+use super::db::BoxError;
+
+/// Root hash of an empty trie, `keccak256(rlp(""))`.
+pub fn empty_trie_hash() -> H256 {
+    crypto::keccak256(&[0x80])
+}
+
+/// Value of an account in the account state trie, java-tron's `AccountStateEntity`.
+///
+/// It is an `Account` message of java-tron with only `address`, `balance` and `allowance` set.
+pub fn account_state_entity(address: &[u8], balance: i64, allowance: i64) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(2 + address.len() + 2 * 11);
+    // address = 3, bytes
+    buf.push(0x1a);
+    buf.push(address.len() as u8);
+    buf.extend_from_slice(address);
+    // balance = 4, int64
+    if balance != 0 {
+        buf.push(0x20);
+        encode_varint(balance as u64, &mut buf);
+    }
+    // allowance = 11, int64
+    if allowance != 0 {
+        buf.push(0x58);
+        encode_varint(allowance as u64, &mut buf);
+    }
+    buf
+}
+
+/// Key of an account in the account state trie, the RLP encoded address.
+pub fn account_state_key(address: &[u8]) -> Vec<u8> {
+    rlp_bytes(address)
+}
+
+fn encode_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[derive(Default)]
+enum Node {
+    #[default]
+    Empty,
+    Leaf(Vec<u8>, Vec<u8>),
+    Extension(Vec<u8>, Box<Node>),
+    Branch(Box<[Node; 16]>, Vec<u8>),
+    /// A node stored in db, not loaded yet.
+    Hash(H256),
+}
+
+/// A trie opened from a root hash, updated in memory until `commit`.
+pub struct Trie {
+    root: Node,
+}
+
+impl Trie {
+    /// Open the trie of `root`, a zero hash is treated as an empty trie.
+    pub fn new(root: &H256) -> Trie {
+        let root = if root.is_zero() || *root == empty_trie_hash() {
+            Node::Empty
+        } else {
+            Node::Hash(*root)
+        };
+        Trie { root }
+    }
+
+    /// Insert or update a key. `load` fetches encoded nodes by hash.
+    ///
+    /// NOTE: Deletion is not supported, accounts are never removed from the account state trie.
+    pub fn insert<F>(&mut self, key: &[u8], value: &[u8], load: &F) -> Result<(), BoxError>
+    where
+        F: Fn(&H256) -> Option<Vec<u8>>,
+    {
+        let root = mem::take(&mut self.root);
+        self.root = insert(root, &to_nibbles(key), value.to_vec(), load)?;
+        Ok(())
+    }
+
+    /// Encode updated nodes, returns the root hash and the `(hash, node)` pairs to be stored.
+    pub fn commit(self) -> (H256, Vec<(H256, Vec<u8>)>) {
+        let mut nodes = vec![];
+        let root = match self.root {
+            Node::Empty => empty_trie_hash(),
+            Node::Hash(hash) => hash,
+            ref node => {
+                let encoded = encode_node(node, &mut nodes);
+                let hash = crypto::keccak256(&encoded);
+                nodes.push((hash, encoded));
+                hash
+            }
+        };
+        (root, nodes)
+    }
+}
+
+fn insert<F>(node: Node, path: &[u8], value: Vec<u8>, load: &F) -> Result<Node, BoxError>
+where
+    F: Fn(&H256) -> Option<Vec<u8>>,
+{
+    let node = match node {
+        Node::Empty => Node::Leaf(path.to_vec(), value),
+        Node::Hash(hash) => {
+            let raw = load(&hash).ok_or_else(|| malformed(&format!("missing trie node {:?}", hash)))?;
+            let (item, _) = decode_item(&raw)?;
+            insert(decode_node(item)?, path, value, load)?
+        }
+        Node::Leaf(key, old_value) => {
+            let n = common_prefix_len(&key, path);
+            if n == key.len() && n == path.len() {
+                return Ok(Node::Leaf(key, value));
+            }
+            let branch = Node::Branch(Default::default(), vec![]);
+            let branch = insert(branch, &key[n..], old_value, load)?;
+            let branch = insert(branch, &path[n..], value, load)?;
+            if n > 0 {
+                Node::Extension(path[..n].to_vec(), Box::new(branch))
+            } else {
+                branch
+            }
+        }
+        Node::Extension(key, child) => {
+            let n = common_prefix_len(&key, path);
+            if n == key.len() {
+                return Ok(Node::Extension(key, Box::new(insert(*child, &path[n..], value, load)?)));
+            }
+            let mut children: Box<[Node; 16]> = Default::default();
+            children[key[n] as usize] = if n + 1 == key.len() {
+                *child
+            } else {
+                Node::Extension(key[n + 1..].to_vec(), child)
+            };
+            let branch = insert(Node::Branch(children, vec![]), &path[n..], value, load)?;
+            if n > 0 {
+                Node::Extension(key[..n].to_vec(), Box::new(branch))
+            } else {
+                branch
+            }
+        }
+        Node::Branch(mut children, branch_value) => {
+            if path.is_empty() {
+                Node::Branch(children, value)
+            } else {
+                let idx = path[0] as usize;
+                let child = mem::take(&mut children[idx]);
+                children[idx] = insert(child, &path[1..], value, load)?;
+                Node::Branch(children, branch_value)
+            }
+        }
+    };
+    Ok(node)
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count()
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|b| vec![b >> 4, b & 0x0f]).collect()
+}
+
+// * encoding
+
+fn encode_node(node: &Node, nodes: &mut Vec<(H256, Vec<u8>)>) -> Vec<u8> {
+    match node {
+        Node::Empty => rlp_bytes(&[]),
+        Node::Leaf(path, value) => rlp_list(&[rlp_bytes(&hex_prefix(path, true)), rlp_bytes(value)]),
+        Node::Extension(path, child) => {
+            rlp_list(&[rlp_bytes(&hex_prefix(path, false)), encode_reference(child, nodes)])
+        }
+        Node::Branch(children, value) => {
+            let mut items: Vec<Vec<u8>> = children.iter().map(|child| encode_reference(child, nodes)).collect();
+            items.push(rlp_bytes(value));
+            rlp_list(&items)
+        }
+        Node::Hash(hash) => rlp_bytes(hash.as_bytes()),
+    }
+}
+
+/// Encode a child node in its parent, nodes shorter than 32 bytes are embedded.
+fn encode_reference(node: &Node, nodes: &mut Vec<(H256, Vec<u8>)>) -> Vec<u8> {
+    match node {
+        Node::Empty | Node::Hash(_) => encode_node(node, nodes),
+        _ => {
+            let encoded = encode_node(node, nodes);
+            if encoded.len() < 32 {
+                encoded
+            } else {
+                let hash = crypto::keccak256(&encoded);
+                nodes.push((hash, encoded));
+                rlp_bytes(hash.as_bytes())
+            }
+        }
+    }
+}
+
+fn hex_prefix(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut buf = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        buf.push(((flag + 1) << 4) | path[0]);
+        &path[1..]
+    } else {
+        buf.push(flag << 4);
+        path
+    };
+    buf.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    buf
+}
+
+fn rlp_length_prefix(len: usize, offset: u8, buf: &mut Vec<u8>) {
+    if len <= 55 {
+        buf.push(offset + len as u8);
+    } else {
+        let be = (len as u64).to_be_bytes();
+        let be = &be[be.iter().take_while(|&&b| b == 0).count()..];
+        buf.push(offset + 55 + be.len() as u8);
+        buf.extend_from_slice(be);
+    }
+}
+
+fn rlp_bytes(data: &[u8]) -> Vec<u8> {
+    if data.len() == 1 && data[0] < 0x80 {
+        return data.to_vec();
+    }
+    let mut buf = Vec::with_capacity(data.len() + 9);
+    rlp_length_prefix(data.len(), 0x80, &mut buf);
+    buf.extend_from_slice(data);
+    buf
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload_len = items.iter().map(|item| item.len()).sum();
+    let mut buf = Vec::with_capacity(payload_len + 9);
+    rlp_length_prefix(payload_len, 0xc0, &mut buf);
+    for item in items {
+        buf.extend_from_slice(item);
+    }
+    buf
+}
+
+// * decoding
+
+enum RlpItem<'a> {
+    Bytes(&'a [u8]),
+    List(Vec<RlpItem<'a>>),
+}
+
+fn malformed(msg: &str) -> BoxError {
+    io::Error::new(io::ErrorKind::InvalidData, msg).into()
+}
+
+/// Decode an RLP item, returns the item and the remaining bytes.
+fn decode_item(raw: &[u8]) -> Result<(RlpItem<'_>, &[u8]), BoxError> {
+    let (&prefix, rest) = raw.split_first().ok_or_else(|| malformed("empty rlp"))?;
+    let (is_list, offset, len) = match prefix {
+        0x00..=0x7f => return Ok((RlpItem::Bytes(&raw[..1]), rest)),
+        0x80..=0xb7 => (false, 0, (prefix - 0x80) as usize),
+        0xb8..=0xbf => (false, (prefix - 0xb7) as usize, 0),
+        0xc0..=0xf7 => (true, 0, (prefix - 0xc0) as usize),
+        0xf8..=0xff => (true, (prefix - 0xf7) as usize, 0),
+    };
+    let len = if offset > 0 {
+        if rest.len() < offset {
+            return Err(malformed("truncated rlp length"));
+        }
+        rest[..offset].iter().fold(0usize, |acc, &b| (acc << 8) | b as usize)
+    } else {
+        len
+    };
+    let rest = &rest[offset..];
+    if rest.len() < len {
+        return Err(malformed("truncated rlp"));
+    }
+    let (payload, rest) = rest.split_at(len);
+    if !is_list {
+        return Ok((RlpItem::Bytes(payload), rest));
+    }
+    let mut items = vec![];
+    let mut payload = payload;
+    while !payload.is_empty() {
+        let (item, remaining) = decode_item(payload)?;
+        items.push(item);
+        payload = remaining;
+    }
+    Ok((RlpItem::List(items), rest))
+}
+
+fn decode_node(item: RlpItem) -> Result<Node, BoxError> {
+    let items = match item {
+        RlpItem::List(items) => items,
+        RlpItem::Bytes(_) => return Err(malformed("trie node must be a list")),
+    };
+    match items.len() {
+        2 => {
+            let mut items = items.into_iter();
+            let encoded_path = match items.next() {
+                Some(RlpItem::Bytes(path)) if !path.is_empty() => path,
+                _ => return Err(malformed("malformed trie node path")),
+            };
+            let flag = encoded_path[0] >> 4;
+            // odd length paths start from the low nibble of the first byte
+            let skipped = if flag & 1 == 1 { 1 } else { 2 };
+            let path = to_nibbles(encoded_path).split_off(skipped);
+            if flag & 2 == 2 {
+                match items.next() {
+                    Some(RlpItem::Bytes(value)) => Ok(Node::Leaf(path, value.to_vec())),
+                    _ => Err(malformed("malformed trie leaf value")),
+                }
+            } else {
+                Ok(Node::Extension(
+                    path,
+                    Box::new(decode_reference(items.next().unwrap())?),
+                ))
+            }
+        }
+        17 => {
+            let mut children: Box<[Node; 16]> = Default::default();
+            let mut items = items.into_iter();
+            for child in children.iter_mut() {
+                *child = decode_reference(items.next().unwrap())?;
+            }
+            match items.next() {
+                Some(RlpItem::Bytes(value)) => Ok(Node::Branch(children, value.to_vec())),
+                _ => Err(malformed("malformed trie branch value")),
+            }
+        }
+        _ => Err(malformed("malformed trie node")),
+    }
+}
+
+fn decode_reference(item: RlpItem) -> Result<Node, BoxError> {
+    match item {
+        RlpItem::Bytes([]) => Ok(Node::Empty),
+        RlpItem::Bytes(raw) if raw.len() == 32 => Ok(Node::Hash(H256::from_slice(raw))),
+        RlpItem::Bytes(_) => Err(malformed("malformed trie node reference")),
+        list => decode_node(list),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::keys::Address;
+    use std::collections::HashMap;
+
+    fn root_of(pairs: &[(&[u8], &[u8])]) -> H256 {
+        let mut trie = Trie::new(&H256::zero());
+        for (key, value) in pairs {
+            trie.insert(key, value, &|_: &H256| None).unwrap();
+        }
+        trie.commit().0
+    }
+
+    fn h256(s: &str) -> H256 {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ethereum_trie_vectors() {
+        assert_eq!(
+            root_of(&[]),
+            h256("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+        );
+        assert_eq!(
+            root_of(&[(b"A", &[b'a'; 50])]),
+            h256("d23786fb4a010da3ce639d66d5e904a11dbc02746d1ce25029e53290cabf28ab")
+        );
+        // The result is independent of the order of insertion.
+        assert_eq!(
+            root_of(&[
+                (b"doge", b"coin"),
+                (b"do", b"verb"),
+                (b"horse", b"stallion"),
+                (b"dog", b"puppy"),
+            ]),
+            h256("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+        );
+    }
+
+    #[test]
+    fn reopen_trie_from_stored_nodes() {
+        let addr1 = "TPswDDCAWhJAZGdHPidFg5nEf8TkNToDX1".parse::<Address>().unwrap();
+        let addr1 = addr1.as_bytes();
+        let addr2 = "TALSWqjhNCaXi5YWPh9DvZgvtYRSfqVUwq".parse::<Address>().unwrap();
+        let addr2 = addr2.as_bytes();
+        let mut store = HashMap::new();
+        let load = |hash: &H256| store.get(hash).cloned();
+
+        let mut trie = Trie::new(&empty_trie_hash());
+        trie.insert(
+            &account_state_key(addr1),
+            &account_state_entity(addr1, 1_000_000, 0),
+            &load,
+        )
+        .unwrap();
+        let (root, nodes) = trie.commit();
+        assert_eq!(
+            root,
+            h256("ed0514a19231741bea2e8736b853001d10455701e6617814f49f8d79422d1158")
+        );
+        store.extend(nodes);
+
+        let load = |hash: &H256| store.get(hash).cloned();
+        let mut trie = Trie::new(&root);
+        trie.insert(&account_state_key(addr2), &account_state_entity(addr2, 0, 0), &load)
+            .unwrap();
+        let (root, nodes) = trie.commit();
+        assert_eq!(
+            root,
+            h256("dd3164e013838d380f1c58be9b70cf46e2ee0dd6b742f82d73488118eeafe693")
+        );
+        store.extend(nodes);
+
+        let load = |hash: &H256| store.get(hash).cloned();
+        let mut trie = Trie::new(&root);
+        trie.insert(
+            &account_state_key(addr1),
+            &account_state_entity(addr1, 500_000, 16_000_000),
+            &load,
+        )
+        .unwrap();
+        assert_eq!(
+            trie.commit().0,
+            h256("c0a838cc39ea1271d3027569c35818c8009dc1bca9e0e77f3969ec40df019315")
+        );
+    }
+}
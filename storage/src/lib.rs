@@ -0,0 +1,182 @@
+//! Storage engines behind chain-db and state-db.
+//!
+//! Columns are addressed by index, in the order they are opened.
+
+use std::collections::VecDeque;
+use std::io;
+
+pub use self::memory::MemoryStorage;
+pub use self::rocksdb::RocksStorage;
+
+mod memory;
+mod rocksdb;
+
+/// A key-value storage with column families.
+pub trait Storage: Send + Sync {
+    /// Get a value by key. `Ok(None)` if not found.
+    fn get(&self, col: usize, key: &[u8]) -> io::Result<Option<Vec<u8>>>;
+
+    fn put(&self, col: usize, key: &[u8], value: &[u8]) -> io::Result<()>;
+
+    fn delete(&self, col: usize, key: &[u8]) -> io::Result<()>;
+
+    /// Apply a write batch atomically.
+    fn write(&self, batch: &WriteBatch) -> io::Result<()>;
+
+    /// Visit key/value pairs in `[lower_bound, upper_bound)` in key order, until `func` returns false.
+    ///
+    /// No upper bound if `upper_bound` is None.
+    fn scan(
+        &self,
+        col: usize,
+        lower_bound: &[u8],
+        upper_bound: Option<&[u8]>,
+        func: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+    ) -> io::Result<()>;
+
+    /// Visit all key/value pairs where the key starts with the given prefix.
+    fn scan_prefix(&self, col: usize, prefix: &[u8], func: &mut dyn FnMut(&[u8], &[u8])) -> io::Result<()> {
+        let upper_bound = prefix_upper_bound(prefix);
+        self.scan(col, prefix, upper_bound.as_deref(), &mut |key, value| {
+            func(key, value);
+            true
+        })
+    }
+
+    /// Integer property of the whole db, engine specific.
+    fn property(&self, _name: &str) -> Option<u64> {
+        None
+    }
+
+    /// Integer property summed over all columns, engine specific.
+    fn accumulated_property(&self, _name: &str) -> Option<u64> {
+        None
+    }
+
+    fn compact(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Flush memtables and WAL, cancel background jobs.
+    unsafe fn prepare_close(&self) {}
+}
+
+impl dyn Storage {
+    /// Iterate over key/value pairs in `[lower_bound, upper_bound)` in key order. No upper bound if `upper_bound` is
+    /// None.
+    ///
+    /// Pairs are fetched in pages. A storage error is yielded as the last item.
+    pub fn iter_from<'a>(&'a self, col: usize, lower_bound: &[u8], upper_bound: Option<&[u8]>) -> Iter<'a> {
+        Iter {
+            storage: self,
+            col,
+            next_key: Some(lower_bound.to_owned()),
+            upper_bound: upper_bound.map(ToOwned::to_owned),
+            buf: VecDeque::new(),
+        }
+    }
+
+    /// Iterate over all key/value pairs in key order.
+    pub fn iter<'a>(&'a self, col: usize) -> Iter<'a> {
+        self.iter_from(col, &[], None)
+    }
+}
+
+/// The smallest key greater than all keys starting with `prefix`. None if there's no such key.
+pub fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut upper_bound = prefix.to_vec();
+    while let Some(last) = upper_bound.pop() {
+        if last < 0xff {
+            upper_bound.push(last + 1);
+            return Some(upper_bound);
+        }
+    }
+    None
+}
+
+const ITER_PAGE_SIZE: usize = 1024;
+
+/// Iterator over a column of a storage.
+pub struct Iter<'a> {
+    storage: &'a dyn Storage,
+    col: usize,
+    // None => no more pages
+    next_key: Option<Vec<u8>>,
+    upper_bound: Option<Vec<u8>>,
+    buf: VecDeque<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = io::Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            let lower_bound = self.next_key.take()?;
+            let buf = &mut self.buf;
+            let scanned = self
+                .storage
+                .scan(self.col, &lower_bound, self.upper_bound.as_deref(), &mut |key, value| {
+                    buf.push_back((key.to_vec(), value.to_vec()));
+                    buf.len() < ITER_PAGE_SIZE
+                });
+            if let Err(e) = scanned {
+                self.buf.clear();
+                return Some(Err(e));
+            }
+            if self.buf.len() == ITER_PAGE_SIZE {
+                // the smallest key greater than the last one
+                let mut next_key = self.buf.back().unwrap().0.clone();
+                next_key.push(0);
+                self.next_key = Some(next_key);
+            }
+        }
+        self.buf.pop_front().map(Ok)
+    }
+}
+
+pub(crate) enum BatchOp {
+    Put(usize, Vec<u8>, Vec<u8>),
+    Delete(usize, Vec<u8>),
+}
+
+/// A batch of writes, to be applied atomically.
+#[derive(Default)]
+pub struct WriteBatch {
+    ops: Vec<BatchOp>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        WriteBatch { ops: Vec::new() }
+    }
+
+    pub fn put(&mut self, col: usize, key: &[u8], value: &[u8]) {
+        self.ops.push(BatchOp::Put(col, key.to_owned(), value.to_owned()));
+    }
+
+    /// Put with a key made of multiple parts, and a value made of multiple parts.
+    pub fn putv(&mut self, col: usize, key: &[&[u8]], value: &[&[u8]]) {
+        self.ops.push(BatchOp::Put(col, key.concat(), value.concat()));
+    }
+
+    pub fn delete(&mut self, col: usize, key: &[u8]) {
+        self.ops.push(BatchOp::Delete(col, key.to_owned()));
+    }
+
+    /// Delete with a key made of multiple parts.
+    pub fn deletev(&mut self, col: usize, key: &[&[u8]]) {
+        self.ops.push(BatchOp::Delete(col, key.concat()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.ops.clear();
+    }
+}
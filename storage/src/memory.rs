@@ -0,0 +1,143 @@
+//! The in-memory storage engine, for testing and dev nodes.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::ops::Bound;
+use std::sync::RwLock;
+
+use super::{BatchOp, Storage, WriteBatch};
+
+/// An in-memory storage, all data is lost when dropped.
+pub struct MemoryStorage {
+    cols: RwLock<Vec<BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new(num_cols: usize) -> Self {
+        MemoryStorage {
+            cols: RwLock::new(vec![BTreeMap::new(); num_cols]),
+        }
+    }
+
+    fn check_col(&self, col: usize) -> io::Result<()> {
+        if col < self.cols.read().unwrap().len() {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "column not found"))
+        }
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, col: usize, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        self.check_col(col)?;
+        Ok(self.cols.read().unwrap()[col].get(key).cloned())
+    }
+
+    fn put(&self, col: usize, key: &[u8], value: &[u8]) -> io::Result<()> {
+        self.check_col(col)?;
+        self.cols.write().unwrap()[col].insert(key.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    fn delete(&self, col: usize, key: &[u8]) -> io::Result<()> {
+        self.check_col(col)?;
+        self.cols.write().unwrap()[col].remove(key);
+        Ok(())
+    }
+
+    fn write(&self, batch: &WriteBatch) -> io::Result<()> {
+        let mut cols = self.cols.write().unwrap();
+        let num_cols = cols.len();
+        if batch.ops.iter().any(|op| match *op {
+            BatchOp::Put(col, ..) | BatchOp::Delete(col, ..) => col >= num_cols,
+        }) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "column not found"));
+        }
+        for op in &batch.ops {
+            match op {
+                BatchOp::Put(col, key, value) => {
+                    cols[*col].insert(key.clone(), value.clone());
+                }
+                BatchOp::Delete(col, key) => {
+                    cols[*col].remove(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn scan(
+        &self,
+        col: usize,
+        lower_bound: &[u8],
+        upper_bound: Option<&[u8]>,
+        func: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+    ) -> io::Result<()> {
+        self.check_col(col)?;
+        let upper_bound = match upper_bound {
+            Some(upper_bound) if upper_bound <= lower_bound => return Ok(()),
+            Some(upper_bound) => Bound::Excluded(upper_bound),
+            None => Bound::Unbounded,
+        };
+        // NOTE: snapshot the range, so that `func` can write to the storage.
+        let items: Vec<_> = self.cols.read().unwrap()[col]
+            .range::<[u8], _>((Bound::Included(lower_bound), upper_bound))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        for (key, value) in &items {
+            if !func(key, value) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prefix_upper_bound;
+
+    #[test]
+    fn test_write_batch_and_scan() {
+        let db = MemoryStorage::new(2);
+        let mut wb = WriteBatch::new();
+        wb.put(1, b"ab", b"1");
+        wb.putv(1, &[&b"a"[..], &b"c"[..]], &[&b"2"[..]]);
+        wb.put(1, b"b", b"3");
+        wb.put(0, b"ab", b"0");
+        db.write(&wb).unwrap();
+
+        let mut found = vec![];
+        db.scan_prefix(1, b"a", &mut |key, value| found.push((key.to_vec(), value.to_vec())))
+            .unwrap();
+        assert_eq!(
+            found,
+            vec![(b"ab".to_vec(), b"1".to_vec()), (b"ac".to_vec(), b"2".to_vec())]
+        );
+
+        let mut wb = WriteBatch::new();
+        wb.delete(1, b"ab");
+        wb.put(2, b"x", b"x");
+        assert!(db.write(&wb).is_err());
+        assert_eq!(db.get(1, b"ab").unwrap(), Some(b"1".to_vec()));
+
+        let storage: &dyn Storage = &db;
+        let keys: Vec<_> = storage.iter_from(1, b"ac", None).map(|kv| kv.unwrap().0).collect();
+        assert_eq!(keys, vec![b"ac".to_vec(), b"b".to_vec()]);
+        let keys: Vec<_> = storage.iter_from(1, b"a", Some(b"b")).map(|kv| kv.unwrap().0).collect();
+        assert_eq!(keys, vec![b"ab".to_vec(), b"ac".to_vec()]);
+        assert!(storage.iter_from(1, b"b", Some(b"a")).next().is_none());
+        let errors: Vec<_> = storage.iter_from(2, b"", None).collect();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].is_err());
+
+        assert_eq!(prefix_upper_bound(b"a\xff"), Some(b"b".to_vec()));
+        assert_eq!(prefix_upper_bound(b"\xff\xff"), None);
+
+        db.delete(1, b"ab").unwrap();
+        assert_eq!(db.get(1, b"ab").unwrap(), None);
+        assert_eq!(db.get(0, b"ab").unwrap(), Some(b"0".to_vec()));
+    }
+}
@@ -0,0 +1,248 @@
+//! The RocksDB storage engine.
+
+use std::error::Error;
+use std::io;
+use std::path::Path;
+
+use config::rocksdb::{ColumnFamilyConfig, Compression, WalRecoveryMode};
+use config::RocksDbConfig;
+use log::info;
+use rocks::prelude::WriteBatch as RocksWriteBatch;
+use rocks::prelude::*;
+
+use super::{BatchOp, Storage, WriteBatch};
+
+fn to_io_error<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// RocksDB with column families, in the order of column family descriptors.
+pub struct RocksStorage {
+    db: DB,
+    cols: Vec<ColumnFamily>,
+}
+
+// NOTE: rocksdb handles are thread-safe.
+unsafe impl Send for RocksStorage {}
+unsafe impl Sync for RocksStorage {}
+
+impl RocksStorage {
+    /// Open with options from config. Options of each column family are built-in tuning overridden by config.
+    pub fn open<P: AsRef<Path>>(
+        config: &RocksDbConfig,
+        db_path: P,
+        column_families: Vec<(&str, ColumnFamilyOptions)>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let db_options = db_options_from_config(config, db_path.as_ref());
+        let column_families = column_families
+            .into_iter()
+            .map(|(name, options)| {
+                let cf_config = match config.cf.get(name) {
+                    Some(cf_config) => config.defaultcf.merge(cf_config),
+                    None => config.defaultcf.clone(),
+                };
+                ColumnFamilyDescriptor::new(name, apply_cf_config(options, &cf_config))
+            })
+            .collect();
+        let (db, cols) = DB::open_with_column_families(&db_options, db_path, column_families)?;
+        Ok(RocksStorage { db, cols })
+    }
+
+    /// Open as a secondary instance, which follows the primary instance by `catch_up_with_primary`.
+    pub fn open_as_secondary<P1: AsRef<Path>, P2: AsRef<Path>>(
+        db_options: &DBOptions,
+        db_path: P1,
+        secondary_path: P2,
+        column_families: Vec<(&str, ColumnFamilyOptions)>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let column_families = column_families
+            .into_iter()
+            .map(|(name, options)| ColumnFamilyDescriptor::new(name, options))
+            .collect();
+        let (db, cols) =
+            DB::open_as_secondary_with_column_families(db_options, db_path, secondary_path, column_families)?;
+        Ok(RocksStorage { db, cols })
+    }
+
+    pub fn catch_up_with_primary(&self) {
+        let _ = self.db.try_catch_up_with_primary();
+    }
+
+    fn col(&self, col: usize) -> io::Result<&ColumnFamily> {
+        self.cols
+            .get(col)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "column not found"))
+    }
+}
+
+impl Storage for RocksStorage {
+    fn get(&self, col: usize, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        match self.db.get_cf(ReadOptions::default_instance(), self.col(col)?, key) {
+            Ok(val) => Ok(Some(val.to_vec())),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(to_io_error(e)),
+        }
+    }
+
+    fn put(&self, col: usize, key: &[u8], value: &[u8]) -> io::Result<()> {
+        self.col(col)?
+            .put(WriteOptions::default_instance(), key, value)
+            .map_err(to_io_error)
+    }
+
+    fn delete(&self, col: usize, key: &[u8]) -> io::Result<()> {
+        let mut wb = RocksWriteBatch::new();
+        wb.delete_cf(self.col(col)?, key);
+        self.db
+            .write(WriteOptions::default_instance(), &wb)
+            .map_err(to_io_error)
+    }
+
+    fn write(&self, batch: &WriteBatch) -> io::Result<()> {
+        let mut wb = RocksWriteBatch::with_reserved_bytes(1024);
+        for op in &batch.ops {
+            match op {
+                BatchOp::Put(col, key, value) => {
+                    wb.put_cf(self.col(*col)?, key, value);
+                }
+                BatchOp::Delete(col, key) => {
+                    wb.delete_cf(self.col(*col)?, key);
+                }
+            }
+        }
+        self.db
+            .write(WriteOptions::default_instance(), &wb)
+            .map_err(to_io_error)
+    }
+
+    fn scan(
+        &self,
+        col: usize,
+        lower_bound: &[u8],
+        upper_bound: Option<&[u8]>,
+        func: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+    ) -> io::Result<()> {
+        let col = self.col(col)?;
+        let mut ropts = ReadOptions::default().iterate_lower_bound(lower_bound);
+        if let Some(upper_bound) = upper_bound {
+            ropts = ropts.iterate_upper_bound(upper_bound);
+        }
+        for (key, value) in self.db.new_iterator_cf(&ropts, col) {
+            if !func(key, value) {
+                break;
+            }
+        }
+        drop(ropts); // holds lifetime of bound slice.
+        Ok(())
+    }
+
+    fn property(&self, name: &str) -> Option<u64> {
+        self.db.get_int_property(name)
+    }
+
+    fn accumulated_property(&self, name: &str) -> Option<u64> {
+        Some(
+            self.cols
+                .iter()
+                .map(|cf| cf.get_int_property(name).unwrap_or_default())
+                .sum(),
+        )
+    }
+
+    fn compact(&self) -> io::Result<()> {
+        for cf in &self.cols {
+            cf.compact_range(&Default::default(), ..).map_err(to_io_error)?;
+        }
+        Ok(())
+    }
+
+    unsafe fn prepare_close(&self) {
+        info!("flush db ... {:?}", self.db.flush(&FlushOptions::default()));
+        info!("cancal background work ...");
+        self.db.cancel_background_work(/* wait: */ true);
+        info!("syncing WAL ... {:?}", self.db.sync_wal());
+    }
+}
+
+fn db_options_from_config(config: &RocksDbConfig, db_path: &Path) -> DBOptions {
+    let mut db_options = DBOptions::default()
+        .create_if_missing(config.create_if_missing)
+        .create_missing_column_families(true)
+        .increase_parallelism(num_cpus::get() as _)
+        .allow_mmap_reads(true) // for Cuckoo table
+        .max_open_files(config.max_open_files)
+        .wal_ttl_seconds(config.wal_ttl_seconds)
+        .wal_size_limit_mb(config.wal_size_limit.as_mb());
+
+    if let Some(n) = config.max_background_jobs {
+        db_options = db_options.max_background_jobs(n);
+    }
+    if let Some(n) = config.max_sub_compactions {
+        db_options = db_options.max_subcompactions(n);
+    }
+    if let Some(size) = config.max_manifest_file_size {
+        db_options = db_options.max_manifest_file_size(size.as_bytes() as _);
+    }
+    if let Some(mode) = config.wal_recovery_mode {
+        let mode = match mode {
+            WalRecoveryMode::TolerateCorruptedTailRecords => WALRecoveryMode::TolerateCorruptedTailRecords,
+            WalRecoveryMode::AbsoluteConsistency => WALRecoveryMode::AbsoluteConsistency,
+            WalRecoveryMode::PointInTimeRecovery => WALRecoveryMode::PointInTimeRecovery,
+            WalRecoveryMode::SkipAnyCorruptedRecords => WALRecoveryMode::SkipAnyCorruptedRecords,
+        };
+        db_options = db_options.wal_recovery_mode(mode);
+    }
+    if let Some(ref wal_dir) = config.wal_dir {
+        // chain-db and state-db can not share a WAL directory
+        let wal_dir = Path::new(wal_dir).join(db_path.file_name().unwrap_or_default());
+        db_options = db_options.wal_dir(wal_dir);
+    }
+    if config.enable_statistics {
+        db_options = db_options.statistics(Some(Statistics::new()));
+    }
+    if let Some(period) = config.stats_dump_period {
+        db_options = db_options.stats_dump_period_sec(period.as_secs() as _);
+    }
+    if config.compaction_readahead_size.as_bytes() > 0 {
+        db_options = db_options.compaction_readahead_size(config.compaction_readahead_size.as_bytes() as _);
+    }
+    db_options
+}
+
+fn apply_cf_config(mut options: ColumnFamilyOptions, config: &ColumnFamilyConfig) -> ColumnFamilyOptions {
+    if let Some(compression) = config.compression {
+        options = options.compression(compression_type(compression));
+    }
+    if let Some(ref levels) = config.compression_per_level {
+        let levels: Vec<_> = levels.iter().copied().map(compression_type).collect();
+        options = options.compression_per_level(&levels);
+    }
+    if let Some(n) = config.num_levels {
+        options = options.num_levels(n);
+    }
+    if let Some(size) = config.write_buffer_size {
+        options = options.write_buffer_size(size.as_bytes() as _);
+    }
+    if let Some(n) = config.max_write_buffer_number {
+        options = options.max_write_buffer_number(n);
+    }
+    if let Some(size) = config.target_file_size_base {
+        options = options.target_file_size_base(size.as_bytes());
+    }
+    if let Some(n) = config.level0_file_num_compaction_trigger {
+        options = options.level0_file_num_compaction_trigger(n);
+    }
+    options
+}
+
+fn compression_type(compression: Compression) -> CompressionType {
+    match compression {
+        Compression::No => CompressionType::NoCompression,
+        Compression::Snappy => CompressionType::SnappyCompression,
+        Compression::Zlib => CompressionType::ZlibCompression,
+        Compression::Bz2 => CompressionType::BZip2Compression,
+        Compression::Lz4 => CompressionType::LZ4Compression,
+        Compression::Lz4hc => CompressionType::LZ4HCCompression,
+        Compression::Zstd => CompressionType::ZSTD,
+    }
+}